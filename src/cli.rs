@@ -1,2 +1,4 @@
+pub mod epub;
 pub mod files;
+pub mod office;
 pub mod view;
@@ -0,0 +1,119 @@
+#![cfg(feature = "cli")]
+
+use anyhow::{Result, anyhow};
+use latinga::HujjatTuri;
+use regex::Regex;
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+use std::sync::LazyLock;
+use zip::ZipArchive;
+
+const CONTAINER: &str = "META-INF/container.xml";
+
+static RE_ROOTFILE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<rootfile\b[^>]*?\bfull-path="([^"]+)""#).unwrap());
+static RE_ITEM: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"<item\b[^>]*>").unwrap());
+static RE_ITEMREF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"<itemref\b[^>]*?\bidref="([^"]+)""#).unwrap());
+static RE_ATTR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([\w:-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+/// Document types of the parts of an EPUB, found by walking the spine of
+/// its package document: spine chapters and the navigation document are
+/// `Html`, the package document and NCX table of contents are `Epub`.
+/// Returns `None` for archives that are not EPUBs.
+pub fn parts<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+) -> Result<Option<HashMap<String, HujjatTuri>>> {
+    if archive.index_for_name(CONTAINER).is_none() {
+        return Ok(None);
+    }
+    let container = read_entry(archive, CONTAINER)?;
+    let opf_path = RE_ROOTFILE
+        .captures(&container)
+        .map(|c| c[1].to_string())
+        .ok_or_else(|| anyhow!("{CONTAINER}: rootfile topilmadi"))?;
+    let opf = read_entry(archive, &opf_path)?;
+    let base = opf_path.rsplit_once('/').map_or("", |(dir, _)| dir);
+
+    // id -> (href, media type, properties)
+    let mut manifest = HashMap::new();
+    for item in RE_ITEM.find_iter(&opf) {
+        let attrs: HashMap<&str, &str> = RE_ATTR
+            .captures_iter(item.as_str())
+            .map(|c| (c.get(1).unwrap().as_str(), c.get(2).unwrap().as_str()))
+            .collect();
+        if let (Some(id), Some(href)) = (attrs.get("id"), attrs.get("href")) {
+            let media_type = attrs.get("media-type").copied().unwrap_or("");
+            let properties = attrs.get("properties").copied().unwrap_or("");
+            manifest.insert(*id, (resolve(base, href), media_type, properties));
+        }
+    }
+
+    let mut parts = HashMap::from([(opf_path.clone(), HujjatTuri::Epub)]);
+    for (path, media_type, properties) in manifest.values() {
+        if *media_type == "application/x-dtbncx+xml" {
+            parts.insert(path.clone(), HujjatTuri::Epub);
+        } else if properties.split_whitespace().any(|p| p == "nav") {
+            parts.insert(path.clone(), HujjatTuri::Html);
+        }
+    }
+    for idref in RE_ITEMREF.captures_iter(&opf) {
+        if let Some((path, media_type, _)) = manifest.get(&idref[1])
+            && matches!(*media_type, "application/xhtml+xml" | "text/html")
+        {
+            parts.insert(path.clone(), HujjatTuri::Html);
+        }
+    }
+    Ok(Some(parts))
+}
+
+fn read_entry<R: Read + Seek>(archive: &mut ZipArchive<R>, name: &str) -> Result<String> {
+    let mut text = String::new();
+    archive.by_name(name)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Resolves a manifest `href` against the directory of the package
+/// document into an archive entry name.
+fn resolve(base: &str, href: &str) -> String {
+    let href = href.split('#').next().unwrap_or(href);
+    let mut segments: Vec<String> = base
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    for segment in href.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            s => segments.push(percent_decode(s)),
+        }
+    }
+    segments.join("/")
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).unwrap_or_else(|_| text.to_string())
+}
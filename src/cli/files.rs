@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 use glob::glob;
+use latinga::{HujjatTuri, KodTili, TarjimaFormati};
 use std::collections::BTreeSet;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
@@ -56,10 +57,26 @@ pub fn discover_files(files: &[PathBuf], pattern: Option<&str>) -> Result<BTreeS
     Ok(ts)
 }
 
+/// Extensions made of two parts, kept together when a suffix is added.
+const COMPOUND_EXTENSIONS: &[&str] = &["fb2.zip"];
+
 /// Generates a new path by appending a suffix to the file stem.
 pub fn get_output_path(path: &Path, suffix: &str) -> PathBuf {
-    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("");
-    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let (stem, extension) = COMPOUND_EXTENSIONS
+        .iter()
+        .find_map(|compound| {
+            let split = name.len().checked_sub(compound.len() + 1)?;
+            let extension = name.get(split + 1..)?;
+            (name.as_bytes()[split] == b'.' && extension.eq_ignore_ascii_case(compound))
+                .then(|| (&name[..split], extension))
+        })
+        .unwrap_or_else(|| {
+            (
+                path.file_stem().and_then(|s| s.to_str()).unwrap_or(""),
+                path.extension().and_then(|e| e.to_str()).unwrap_or(""),
+            )
+        });
 
     let new_filename = if extension.is_empty() {
         format!("{stem}{suffix}")
@@ -74,3 +91,98 @@ pub fn get_output_path(path: &Path, suffix: &str) -> PathBuf {
 pub fn sanitize_string(s: &mut String) {
     s.retain(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.');
 }
+
+/// How the document type of each input is chosen (`-p, --profil`).
+#[derive(Debug, Clone, Copy)]
+pub enum Profile {
+    /// From the file extension, then the content.
+    Auto,
+    /// Source code; the language comes from the file extension.
+    Code,
+    /// The same type for every input.
+    Fixed(HujjatTuri),
+}
+
+impl Profile {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(Self::Auto),
+            "kod" | "code" => Some(Self::Code),
+            other => HujjatTuri::nomdan(other).map(Self::Fixed),
+        }
+    }
+}
+
+/// Picks the document type of an input: an explicit profile wins, then the
+/// file extension, then the content, then `fallback`.
+pub fn document_type(
+    path: Option<&Path>,
+    content: &str,
+    profile: Profile,
+    fallback: HujjatTuri,
+) -> HujjatTuri {
+    if let Profile::Fixed(turi) = profile {
+        return turi;
+    }
+    let detected = path
+        .and_then(extension_type)
+        .or_else(|| HujjatTuri::matndan_aniqla(content));
+
+    match (profile, detected) {
+        (Profile::Code, Some(turi @ HujjatTuri::Kod(_))) | (Profile::Auto, Some(turi)) => turi,
+        _ => fallback,
+    }
+}
+
+/// Maps a file extension to a document type.
+fn extension_type(path: &Path) -> Option<HujjatTuri> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+
+    let turi = match extension.as_deref()? {
+        "txt" => HujjatTuri::Matn,
+        "md" | "markdown" => HujjatTuri::Markdown,
+        "html" | "htm" => HujjatTuri::Html,
+        // Android keeps its resources in `res/values*/` directories
+        "xml"
+            if path
+                .parent()
+                .and_then(Path::file_name)
+                .and_then(|d| d.to_str())
+                .is_some_and(|d| d.starts_with("values")) =>
+        {
+            HujjatTuri::Tarjima(TarjimaFormati::Android)
+        }
+        "xml" | "xhtml" | "svg" => HujjatTuri::Xml,
+        "tex" | "ltx" | "sty" | "cls" => HujjatTuri::Latex,
+        "json" | "jsonc" | "jsonl" => HujjatTuri::Json,
+        "yaml" | "yml" => HujjatTuri::Yaml,
+        "toml" => HujjatTuri::Toml,
+        "po" | "pot" => HujjatTuri::Po,
+        "srt" | "vtt" => HujjatTuri::Subtitr,
+        "fodt" | "fods" | "fodp" => HujjatTuri::Odf,
+        "opf" | "ncx" => HujjatTuri::Epub,
+        "fb2" => HujjatTuri::Fb2,
+        "rtf" => HujjatTuri::Rtf,
+        "sql" => HujjatTuri::Sql,
+        "csv" => HujjatTuri::Csv,
+        "tsv" | "tab" => HujjatTuri::Tsv,
+        "strings" => HujjatTuri::Tarjima(TarjimaFormati::Strings),
+        "stringsdict" => HujjatTuri::Tarjima(TarjimaFormati::Stringsdict),
+        "arb" => HujjatTuri::Tarjima(TarjimaFormati::Arb),
+        "properties" => HujjatTuri::Tarjima(TarjimaFormati::Properties),
+        "xlf" | "xliff" => HujjatTuri::Tarjima(TarjimaFormati::Xliff),
+        "rs" => HujjatTuri::Kod(KodTili::Rust),
+        "py" | "pyw" => HujjatTuri::Kod(KodTili::Python),
+        "js" | "mjs" | "cjs" | "jsx" | "ts" | "mts" | "cts" | "tsx" => {
+            HujjatTuri::Kod(KodTili::JavaScript)
+        }
+        "java" => HujjatTuri::Kod(KodTili::Java),
+        "php" | "phtml" => HujjatTuri::Kod(KodTili::Php),
+        "go" => HujjatTuri::Kod(KodTili::Go),
+        _ => return None,
+    };
+    Some(turi)
+}
@@ -0,0 +1,102 @@
+#![cfg(feature = "cli")]
+
+use super::epub;
+use anyhow::Result;
+use latinga::{HujjatTuri, Oegirgich};
+use std::io::{Cursor, Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Extensions of Word, Excel and PowerPoint packages, including
+/// macro-enabled documents and templates, of OpenDocument packages and of
+/// EPUB e-books.
+const PACKAGE_EXTENSIONS: &[&str] = &[
+    "docx", "docm", "dotx", "dotm", "xlsx", "xlsm", "xltx", "xltm", "pptx", "pptm", "potx", "potm",
+    "ppsx", "ppsm", "odt", "ott", "ods", "ots", "odp", "otp", "epub",
+];
+
+/// Name of the OpenDocument and EPUB entry that must come first, uncompressed.
+const MIMETYPE: &str = "mimetype";
+
+/// Whether `path` is an Office Open XML, OpenDocument or EPUB package, or
+/// a zipped FictionBook (`.fb2.zip`).
+pub fn is_package(path: &Path) -> bool {
+    let zipped_fb2 = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.to_ascii_lowercase().ends_with(".fb2.zip"));
+    zipped_fb2
+        || path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| PACKAGE_EXTENSIONS.iter().any(|x| x.eq_ignore_ascii_case(e)))
+}
+
+/// Document type of a package entry that may hold text or languages:
+/// everything XML under the document, workbook or presentation folders of
+/// an Office Open XML package, and the content, styles and metadata of an
+/// OpenDocument package (embedded objects included), and the book of a
+/// zipped FictionBook. Relationships, manifests, media and thumbnails are
+/// copied as they are.
+fn text_part(name: &str) -> Option<HujjatTuri> {
+    let file = name.rsplit('/').next().unwrap_or(name);
+    if name.ends_with(".xml")
+        && ["word/", "xl/", "ppt/"]
+            .iter()
+            .any(|dir| name.starts_with(dir))
+    {
+        Some(HujjatTuri::Ofis)
+    } else if matches!(file, "content.xml" | "styles.xml" | "meta.xml") {
+        Some(HujjatTuri::Odf)
+    } else if file.to_ascii_lowercase().ends_with(".fb2") {
+        Some(HujjatTuri::Fb2)
+    } else {
+        None
+    }
+}
+
+/// Converts the text parts of a package and copies every other entry
+/// unchanged, in the original order and with the original compression.
+/// A `mimetype` entry is written first and stored. The parts of an EPUB
+/// are the ones its package document lists.
+pub fn convert_package(package: &[u8], trans: &Oegirgich) -> Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(Cursor::new(package))?;
+    let epub_parts = epub::parts(&mut archive)?;
+    let mut output = ZipWriter::new(Cursor::new(Vec::with_capacity(package.len())));
+
+    if let Some(i) = archive.index_for_name(MIMETYPE) {
+        let mut mimetype = Vec::new();
+        archive.by_index(i)?.read_to_end(&mut mimetype)?;
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        output.start_file(MIMETYPE, stored)?;
+        output.write_all(&mimetype)?;
+    }
+
+    for i in 0..archive.len() {
+        let (name, options) = {
+            let entry = archive.by_index_raw(i)?;
+            let options = SimpleFileOptions::default()
+                .compression_method(entry.compression())
+                .last_modified_time(entry.last_modified().unwrap_or_default());
+            (entry.name().to_string(), options)
+        };
+        if name == MIMETYPE {
+            continue;
+        }
+        let turi = match &epub_parts {
+            Some(parts) => parts.get(&name).copied(),
+            None => text_part(&name),
+        };
+        let Some(turi) = turi else {
+            output.raw_copy_file(archive.by_index_raw(i)?)?;
+            continue;
+        };
+
+        let mut content = String::new();
+        archive.by_index(i)?.read_to_string(&mut content)?;
+        output.start_file(name, options)?;
+        trans.hujjatni_oegir(&content, turi, &mut output)?;
+    }
+    Ok(output.finish()?.into_inner())
+}
@@ -50,7 +50,27 @@ BAYROQLAR:
   -a, --atoqli         Atoqli otlar luğati (tutuq belgisi bilan ajratilişi uçun)
   -q, --qalqon         Ifodali himoya qoliplari (ögirilmaydigan qismlar uçun)
   -n, --qalqon-fayl    Ifodali himoya qoliplari fayli yölagi
+  -o, --ochiq          Himoyalangan qismlar içida ham ögiriladigan ifoda (masalan: "// (.*)")
+  -g, --ochiq-atribut  Har qanday tamğada ögiriladigan atributlar (masalan: "pre[title],*[aria-label]")
+  -k, --muqaddima      Muqaddimada (front matter) ögiriladigan kalitlar (fitrat: "title,description,tags")
+  -r, --kalitlar       JSON/YAML/TOML'da ögiriladigan qiymatlar yöli (masalan: "messages.*.text", fitrat: barça satrlar)
+      --ustunlar       CSV/TSV va SQL'da ögiriladigan ustunlar nomi yoki tartib raqami (masalan: "nomi,3", fitrat: barçasi)
+      --jadvallar      SQL dampida ma'lumoti ögiriladigan jadvallar (masalan: "public.kitoblar", fitrat: barçasi)
+      --nusxa          Ustunni öz örnida ögirmay, yoniga ögirilgan nusxasini qöşiş (--nusxa=qöşimça, fitrat: "_lotin")
+  -x, --latex          LaTeX buyruqlari jadvali (fayl yölagi yoki 'glossary:{{s}}{{c}};acro:{{s}}[c]{{c}}')
+  -e, --belgilar       Umumiy qalqon belgilari (masalan: "<<< >>>;[[ ]]", fitrat: "{{] [}}")
+  -s, --belgilarni-saqla Umumiy qalqon belgilarini çiqarmada saqlaş (qayta ögiriş uçun)
+  -p, --profil         Hujjat turi: matn, markdown, html, xml, latex, json, yaml, toml, po, android, ios, arb, properties, xliff, subtitr, csv, tsv, ofis, odf, epub, fb2, rtf, sql, ijtimoiy, kod, rust, python, ... (fitrat: kengaytma yoki matnga qarab)
+      --yoq            Qalqonlarni yoqiş (masalan: "latex,rim")
+      --oechir         Qalqonlarni öçiriş: umumiy, latex, html, url, email, rim, belgi, kalit-qiymat, kod, ijtimoiy,
+                       birlik, formula, versiya, fayl, identifikator, hex
+  -l, --tilsizni-qoldir HTML/XML'da lang belgilanmagan qismlarni ögirmaslik
+  -i, --izohlar        Dastur kodidagi izohlarni ham ögiriş (fitrat: faqat satrlar)
+      --heshteglar     Ijtimoiy profilda #heşteglar matnini ham ögiriş
+      --asl-matn       PO kataloglarida msgid matnini ham ögiriş (asl tili özbekça bölsa)
+      --noaniqni-qoldir PO kataloglaridagi noaniq (fuzzy) yozuvlarni ögirmaslik
   -b, --batafsil       Bajarilgan işlar tafsilotini körsatiş
+  -d, --qalqon-koersat Himoyalangan qismlarni qaysi qalqon ushlaganini körsatiş (ansi yoki html)
   -t, --tekshir        Imlo va qoidalarni tekşiriş (ihtiyoriy: körsatiladigan hatolar soni)
   -y, --yordam         Özbekça yordam (uşbu ekran)
   -h, --help           English help
@@ -83,7 +103,27 @@ FLAGS:
   -a, --atoqli         Proper nouns dictionary path
   -q, --qalqon         Regex protection pattern
   -n, --qalqon-fayl    File containing regex protection patterns
+  -o, --ochiq          Regex converted even inside shielded spans (e.g. "// (.*)")
+  -g, --ochiq-atribut  Attributes converted on any element (e.g. "pre[title],*[aria-label]")
+  -k, --muqaddima      Front matter keys to convert (default: "title,description,tags")
+  -r, --kalitlar       Key paths of the JSON/YAML/TOML values to convert (e.g. "messages.*.text", default: all strings)
+      --ustunlar       CSV/TSV and SQL columns to convert, by name or 1-based index (e.g. "nomi,3", default: all)
+      --jadvallar      SQL dump tables whose data is converted (e.g. "public.kitoblar", default: all)
+      --nusxa          Keep the columns and add converted copies next to them (--nusxa=suffix, default: "_lotin")
+  -x, --latex          LaTeX command table path or 'glossary:{{s}}{{c}};acro:{{s}}[c]{{c}}'
+  -e, --belgilar       Universal shield markers (e.g. "<<< >>>;[[ ]]", default: "{{] [}}")
+  -s, --belgilarni-saqla Keep universal shield markers in the output (for repeated passes)
+  -p, --profil         Document profile: plain, markdown, html, xml, latex, json, yaml, toml, po, android, ios, arb, properties, xliff, subtitle, csv, tsv, office, odf, epub, fb2, rtf, sql, social, code, rust, python, ... (default: by extension or content)
+      --yoq            Enable shields (e.g. "latex,roman")
+      --oechir         Disable shields: universal, latex, html, url, email, roman, entity, key-value, code, social,
+                       unit, formula, version, file, identifier, hex
+  -l, --tilsizni-qoldir Leave HTML/XML content without a lang tag unconverted
+  -i, --izohlar        Also convert comments in source files (default: string literals only)
+      --heshteglar     Also convert #hashtag text in the social profile
+      --asl-matn       Also convert msgid strings of PO catalogs (Uzbek source language)
+      --noaniqni-qoldir Leave fuzzy entries of PO catalogs unconverted
   -b, --batafsil       Verbose details mode
+  -d, --qalqon-koersat Show which shield claimed each protected span (ansi or html)
   -t, --tekshir        Text validation (Optional: number of detailed errors)
   -y, --yordam         Uzbek help
   -h, --help           English help (this screen)
@@ -1,9 +1,60 @@
-use crate::Tartib;
 use crate::dictionary::Dictionary;
+use crate::symbols::{
+    FRONT_MATTER_KEYS, FULLY_PROTECTED_TAGS, LATEX_COMMANDS, TRANSLITERABLE_ATTRIBUTES,
+};
+use crate::types::{KeyPattern, LatexArg};
+use crate::{HujjatTuri, Qalqon, Tartib};
+use regex::Regex;
+use std::collections::HashMap;
 
 pub struct Sozlama {
     pub tartib: Tartib,
     pub lughat: Dictionary,
+    /// Default document type used by `oegir`, `oqimni_oegir` and `tekshir`.
+    pub hujjat: HujjatTuri,
+    /// Front matter keys whose values are converted; all other keys are shielded.
+    pub muqaddima_kalitlari: Vec<String>,
+    /// HTML attributes whose values are converted; all other attributes are shielded.
+    pub atributlar: Vec<String>,
+    /// HTML elements shielded together with their whole content.
+    pub himoyali_tamghalar: Vec<String>,
+    /// Whether HTML/XML elements without a language tag are converted.
+    pub tilsizni_oegir: bool,
+    /// Whether comments are converted along with string literals in source files.
+    pub izohlarni_oegir: bool,
+    /// Key paths of the JSON, YAML and TOML values to convert; empty means
+    /// every string value.
+    pub(crate) kalit_yollari: Vec<KeyPattern>,
+    /// Whether the text of #hashtags is converted by the social shield.
+    pub heshteglarni_oegir: bool,
+    /// Whether the `msgid` source strings of a gettext catalog are converted
+    /// along with the translations.
+    pub asl_matnni_oegir: bool,
+    /// Whether fuzzy gettext entries are converted.
+    pub noaniqlarni_oegir: bool,
+    /// CSV/TSV and SQL dump columns to convert, by name or 1-based index;
+    /// empty means every column.
+    pub ustunlar: Vec<String>,
+    /// SQL dump tables whose `INSERT` and `COPY` data is converted; empty
+    /// means every table.
+    pub jadvallar: Vec<String>,
+    /// When set, converted columns keep their original and get a converted
+    /// copy named with this suffix (`nomi` → `nomi_lotin`).
+    pub nusxa_qoeshimchasi: Option<String>,
+    /// LaTeX commands whose arguments are shielded or converted.
+    pub(crate) latex_buyruqlari: HashMap<String, Vec<LatexArg>>,
+    /// Built-in shields switched on or off regardless of the document type.
+    pub(crate) qalqonlar: HashMap<Qalqon, bool>,
+    /// Opening and closing markers of the universal shield.
+    pub qalqon_belgilari: Vec<(String, String)>,
+    /// Whether universal shield markers are kept in the output.
+    pub belgilarni_saqla: bool,
+    /// Inverse shield patterns: matches (or their first capture group) are
+    /// converted even inside shielded spans.
+    pub(crate) ochiq_qoliplar: Vec<Regex>,
+    /// `tag[attr]` selectors of attribute values converted on any element,
+    /// protected ones included. A missing tag or `*` matches every element.
+    pub(crate) ochiq_atributlar: Vec<(Option<String>, String)>,
 }
 
 impl Sozlama {
@@ -11,7 +62,32 @@ impl Sozlama {
     pub fn yangi(tartib: Tartib) -> Self {
         let mut lughat = Dictionary::new();
         Self::setup_dictionary(&mut lughat, tartib);
-        Self { tartib, lughat }
+        Self {
+            tartib,
+            lughat,
+            hujjat: HujjatTuri::default(),
+            muqaddima_kalitlari: to_list(FRONT_MATTER_KEYS),
+            atributlar: to_list(TRANSLITERABLE_ATTRIBUTES),
+            himoyali_tamghalar: to_list(FULLY_PROTECTED_TAGS),
+            tilsizni_oegir: true,
+            izohlarni_oegir: false,
+            kalit_yollari: Vec::new(),
+            heshteglarni_oegir: false,
+            asl_matnni_oegir: false,
+            noaniqlarni_oegir: true,
+            ustunlar: Vec::new(),
+            jadvallar: Vec::new(),
+            nusxa_qoeshimchasi: None,
+            latex_buyruqlari: LATEX_COMMANDS
+                .iter()
+                .filter_map(|(name, spec)| Some((name.to_string(), LatexArg::parse_spec(spec)?)))
+                .collect(),
+            qalqonlar: HashMap::new(),
+            qalqon_belgilari: vec![("{]".to_string(), "[}".to_string())],
+            belgilarni_saqla: false,
+            ochiq_qoliplar: Vec::new(),
+            ochiq_atributlar: Vec::new(),
+        }
     }
 
     /// Private helper to orchestrate the loading of all embedded assets
@@ -20,6 +96,153 @@ impl Sozlama {
         dict.load_defaults(mode);
     }
 
+    pub fn hujjat_turini_belgila(&mut self, turi: HujjatTuri) {
+        self.hujjat = turi;
+    }
+
+    /// Replaces the list of convertible front matter keys (one key per line).
+    pub fn muqaddima_kalitlarini_belgila(&mut self, c: &str) {
+        self.muqaddima_kalitlari = parse_list(c);
+    }
+
+    /// Replaces the list of convertible HTML attributes (one name per line).
+    pub fn atributlarni_belgila(&mut self, c: &str) {
+        self.atributlar = parse_list(c);
+    }
+
+    /// Replaces the list of fully protected HTML elements (one name per line).
+    pub fn himoyali_tamghalarni_belgila(&mut self, c: &str) {
+        self.himoyali_tamghalar = parse_list(c);
+    }
+
+    /// Chooses whether untagged HTML/XML content is converted along with `uz` content.
+    pub fn tilsizni_oegirishni_belgila(&mut self, qiymat: bool) {
+        self.tilsizni_oegir = qiymat;
+    }
+
+    /// Chooses whether source code comments are converted.
+    pub fn izohlarni_oegirishni_belgila(&mut self, qiymat: bool) {
+        self.izohlarni_oegir = qiymat;
+    }
+
+    /// Replaces the key paths of the data values to convert, one pattern
+    /// per line (`messages.*.text`, `**.title`). Empty lines are skipped.
+    pub fn kalit_yollarini_belgila(&mut self, c: &str) {
+        self.kalit_yollari = c.lines().filter_map(KeyPattern::parse).collect();
+    }
+
+    /// Chooses whether the text of #hashtags is converted (`#Тошкент` → `#Toshkent`).
+    pub fn heshteglarni_oegirishni_belgila(&mut self, qiymat: bool) {
+        self.heshteglarni_oegir = qiymat;
+    }
+
+    /// Chooses whether `msgid` strings are converted too, for catalogs whose
+    /// source language is Uzbek.
+    pub fn asl_matnni_oegirishni_belgila(&mut self, qiymat: bool) {
+        self.asl_matnni_oegir = qiymat;
+    }
+
+    /// Chooses whether the translations of fuzzy gettext entries are converted.
+    pub fn noaniqlarni_oegirishni_belgila(&mut self, qiymat: bool) {
+        self.noaniqlarni_oegir = qiymat;
+    }
+
+    /// Replaces the CSV/TSV and SQL columns to convert (one name or index
+    /// per line).
+    pub fn ustunlarni_belgila(&mut self, c: &str) {
+        self.ustunlar = parse_list(c);
+    }
+
+    /// Replaces the SQL dump tables to convert (one name per line). With
+    /// tables or columns selected, only the values of `INSERT` rows and
+    /// `COPY` data are converted.
+    pub fn jadvallarni_belgila(&mut self, c: &str) {
+        self.jadvallar = parse_list(c);
+    }
+
+    /// Sets the suffix of converted column copies; an empty suffix converts
+    /// columns in place.
+    pub fn nusxa_qoeshimchasini_belgila(&mut self, qoeshimcha: &str) {
+        let qoeshimcha = qoeshimcha.trim();
+        self.nusxa_qoeshimchasi = (!qoeshimcha.is_empty()).then(|| qoeshimcha.to_string());
+    }
+
+    /// Adds inverse shield patterns, one regex per line.
+    pub fn ochiq_qoliplarni_yukla(&mut self, c: &str) -> Result<(), regex::Error> {
+        for line in parse_list(c) {
+            self.ochiq_qoliplar.push(Regex::new(&line)?);
+        }
+        Ok(())
+    }
+
+    /// Adds attribute selectors such as `pre[title]` or `*[aria-label]`,
+    /// one per line. Malformed lines are skipped.
+    pub fn ochiq_atributlarni_yukla(&mut self, c: &str) {
+        for line in parse_list(c) {
+            let Some((tag, attr)) = line
+                .strip_suffix(']')
+                .and_then(|l| l.split_once('['))
+                .map(|(tag, attr)| (tag.trim(), attr.trim()))
+            else {
+                continue;
+            };
+            if attr.is_empty() {
+                continue;
+            }
+            let tag = (!tag.is_empty() && tag != "*").then(|| tag.to_ascii_lowercase());
+            self.ochiq_atributlar.push((tag, attr.to_ascii_lowercase()));
+        }
+    }
+
+    /// Switches a built-in shield on or off for every document type.
+    pub fn qalqonni_belgila(&mut self, qalqon: Qalqon, yoqilgan: bool) {
+        self.qalqonlar.insert(qalqon, yoqilgan);
+    }
+
+    /// Replaces the universal shield markers, one `open close` pair per line
+    /// (e.g. `<<< >>>`). Lines without exactly two markers are skipped.
+    pub fn qalqon_belgilarini_belgila(&mut self, c: &str) {
+        self.qalqon_belgilari = c
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(open), Some(close), None) => Some((open.to_string(), close.to_string())),
+                    _ => None,
+                }
+            })
+            .collect();
+    }
+
+    /// Chooses whether universal shield markers are kept (for repeated passes).
+    pub fn belgilarni_saqlashni_belgila(&mut self, qiymat: bool) {
+        self.belgilarni_saqla = qiymat;
+    }
+
+    /// Whether `qalqon` runs for a document of type `hujjat`.
+    pub(crate) fn qalqon_yoqilgan(&self, hujjat: HujjatTuri, qalqon: Qalqon) -> bool {
+        self.qalqonlar
+            .get(&qalqon)
+            .copied()
+            .unwrap_or_else(|| hujjat.qalqon_fitrati(qalqon))
+    }
+
+    /// Adds or overrides LaTeX commands, one `name:spec` per line
+    /// (e.g. `glossary:{s}{c}`). Malformed lines are skipped.
+    pub fn latex_buyruqlarini_yukla(&mut self, c: &str) {
+        for line in c.lines() {
+            let Some((name, spec)) = line.split_once(':') else {
+                continue;
+            };
+            let name = name.trim().trim_start_matches('\\');
+            if let Some(args) = LatexArg::parse_spec(spec)
+                && !name.is_empty()
+            {
+                self.latex_buyruqlari.insert(name.to_string(), args);
+            }
+        }
+    }
+
     pub fn qalqonlarni_yukla(&mut self, c: &str) -> Result<(), regex::Error> {
         self.lughat.load_qalqons(c)
     }
@@ -36,3 +259,15 @@ impl Sozlama {
         self.lughat.load_healing(c);
     }
 }
+
+fn to_list(items: &[&str]) -> Vec<String> {
+    items.iter().map(|k| k.to_string()).collect()
+}
+
+fn parse_list(c: &str) -> Vec<String> {
+    c.lines()
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .map(str::to_string)
+        .collect()
+}
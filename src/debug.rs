@@ -0,0 +1,124 @@
+use crate::shield::{Shield, Source};
+use crate::types::Chunk;
+use crate::{HujjatTuri, Koerinish, Sozlama};
+use std::fmt::Write;
+
+/// Separates the chunks handed to the engine.
+const ANSI_BOUNDARY: &str = "\x1b[2m¦\x1b[0m";
+const ANSI_RESET: &str = "\x1b[0m";
+/// Background colours cycled through the shield kinds.
+const ANSI_BACKGROUNDS: &[&str] = &["41", "43", "44", "45", "46", "100"];
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>latinga</title>
+<style>
+pre { white-space: pre-wrap; font: 14px/1.6 monospace; }
+.chunk { border-left: 1px dotted #999; }
+.convert { text-decoration: underline #16a34a; }
+mark { background: #fde68a; }
+sup { font-size: 10px; color: #b45309; }
+</style>
+</head>
+<body>
+<pre>"#;
+const HTML_TAIL: &str = "</pre>\n</body>\n</html>\n";
+
+/// Renders `input` with every shielded span highlighted and labelled with
+/// the shield that produced it, and with the chunk boundaries marked.
+pub(crate) fn render(
+    input: &str,
+    config: &Sozlama,
+    hujjat: HujjatTuri,
+    koerinish: Koerinish,
+) -> String {
+    let mut out = String::with_capacity(input.len() * 2);
+    if koerinish == Koerinish::Html {
+        out.push_str(HTML_HEAD);
+    }
+
+    let mut tokens = Shield::trace(input, config, hujjat);
+    let mut first = true;
+    while let Some(chunk) = tokens.next() {
+        let runs = tokens.labels(&chunk);
+        match koerinish {
+            Koerinish::Ansi => {
+                if !first {
+                    out.push_str(ANSI_BOUNDARY);
+                }
+                ansi_chunk(&mut out, &chunk, &runs);
+            }
+            Koerinish::Html => html_chunk(&mut out, &chunk, &runs),
+        }
+        first = false;
+    }
+
+    if koerinish == Koerinish::Html {
+        out.push_str(HTML_TAIL);
+    }
+    out
+}
+
+fn ansi_chunk(out: &mut String, chunk: &Chunk, runs: &[(&str, Source)]) {
+    for &(text, source) in runs {
+        match chunk {
+            Chunk::Safe(_) => out.push_str(text),
+            Chunk::Attribute(..) | Chunk::Literal(..) | Chunk::Escaped(_) => {
+                let _ = write!(out, "\x1b[32m{text}{ANSI_RESET}");
+            }
+            Chunk::Shielded(_) | Chunk::Recased(..) => {
+                let label = source.label();
+                let hash: usize = label.bytes().map(usize::from).sum();
+                let colour = ANSI_BACKGROUNDS[hash % ANSI_BACKGROUNDS.len()];
+                let _ = write!(
+                    out,
+                    "\x1b[{colour};97m{text}{ANSI_RESET}\x1b[2m[{label}]{ANSI_RESET}"
+                );
+            }
+        }
+    }
+}
+
+fn html_chunk(out: &mut String, chunk: &Chunk, runs: &[(&str, Source)]) {
+    let kind = match chunk {
+        Chunk::Safe(_) => "safe",
+        Chunk::Shielded(_) | Chunk::Recased(..) => "shielded",
+        Chunk::Attribute(..) => "attribute",
+        Chunk::Literal(..) => "literal",
+        Chunk::Escaped(_) => "escaped",
+    };
+    let _ = write!(out, r#"<span class="chunk {kind}" title="{kind}">"#);
+    for &(text, source) in runs {
+        let text = escape_html(text);
+        match chunk {
+            Chunk::Safe(_) => out.push_str(&text),
+            Chunk::Attribute(..) | Chunk::Literal(..) | Chunk::Escaped(_) => {
+                let _ = write!(out, r#"<span class="convert">{text}</span>"#);
+            }
+            Chunk::Shielded(_) | Chunk::Recased(..) => {
+                let label = source.label();
+                let _ = write!(
+                    out,
+                    r#"<mark class="{label}" title="{label}">{text}</mark><sup>{label}</sup>"#
+                );
+            }
+        }
+    }
+    out.push_str("</span>");
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -1,7 +1,6 @@
 use crate::Tartib;
 use crate::trie::Trie;
-use aho_corasick::{AhoCorasick, AhoCorasickBuilder, MatchKind};
-use regex::Regex;
+use regex::{Regex, RegexBuilder};
 use std::borrow::Cow;
 use std::collections::HashSet;
 
@@ -12,15 +11,53 @@ const DEFAULT_SUBSTITUTIONS: &str = include_str!("../dict/almashuvchilar.txt");
 const DEFAULT_HEALS: &str = include_str!("../dict/tuzatishlar.txt");
 const DEFAULT_NIQOBS: &str = include_str!("../dict/qalqonlar.txt");
 
+/// Matching options of a literal shield, listed after `|` in the shield file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LiteralFlags {
+    /// `aniq`: match the listed case only.
+    pub exact_case: bool,
+    /// `qism`: match inside words too.
+    pub substring: bool,
+    /// `shakl`: write every match in the listed case.
+    pub recase: bool,
+}
+
+impl LiteralFlags {
+    /// Parses a flag list such as `aniq qism`. Returns `None` if any name is
+    /// unknown, so the `|` is read as part of the literal.
+    fn parse(list: &str) -> Option<Self> {
+        let mut flags = Self::default();
+        let mut any = false;
+        for name in list.split([' ', '\t', ',']).filter(|n| !n.is_empty()) {
+            match name.to_lowercase().as_str() {
+                "aniq" | "exact" => flags.exact_case = true,
+                "qism" | "substring" => flags.substring = true,
+                "soez" | "word" => flags.substring = false,
+                "shakl" | "recase" => flags.recase = true,
+                _ => return None,
+            }
+            any = true;
+        }
+        any.then_some(flags)
+    }
+}
+
+/// A fixed string from the shield file.
+#[derive(Debug, Clone)]
+pub struct LiteralShield {
+    pub text: String,
+    pub flags: LiteralFlags,
+}
+
 pub struct Dictionary {
-    // Group A: High-performance fixed string matcher
-    pub qalqons_ac: Option<AhoCorasick>,
+    // Group A: Fixed strings, one case-folding matcher per flag set
+    pub qalqons_lit: Vec<(Regex, LiteralFlags)>,
 
     // Group B: Complex patterns that require full regex engine
     pub qalqons_re: Vec<Regex>,
 
-    // Group C: Persistent storage for literals (needed to rebuild AC)
-    pub qalqons_literals: Vec<String>,
+    // Group C: Persistent storage for literals (needed to rebuild Group A)
+    pub qalqons_literals: Vec<LiteralShield>,
 
     pub substitutions_trie: Trie,
     pub healing_trie: Trie,
@@ -42,7 +79,7 @@ impl Dictionary {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            qalqons_ac: None,
+            qalqons_lit: Vec::new(),
             qalqons_re: Vec::new(),
             qalqons_literals: Vec::new(),
             substitutions_trie: Trie::new(),
@@ -133,24 +170,57 @@ impl Dictionary {
             if is_regex {
                 self.qalqons_re.push(Regex::new(line)?);
             } else {
-                self.qalqons_literals.push(line.to_string());
+                let (text, flags) = line
+                    .rsplit_once('|')
+                    .and_then(|(text, list)| Some((text.trim(), LiteralFlags::parse(list)?)))
+                    .filter(|(text, _)| !text.is_empty())
+                    .unwrap_or((line, LiteralFlags::default()));
+                self.qalqons_literals.push(LiteralShield {
+                    text: text.to_string(),
+                    flags,
+                });
             }
         }
 
-        // 2. Rebuild the Automaton with ALL literals
-        if !self.qalqons_literals.is_empty() {
-            let ac = AhoCorasickBuilder::new()
-                .ascii_case_insensitive(true)
-                .match_kind(MatchKind::LeftmostLongest)
-                .build(&self.qalqons_literals)
-                .map_err(|e| regex::Error::Syntax(e.to_string()))?;
-
-            self.qalqons_ac = Some(ac);
+        // 2. Rebuild the matchers with ALL literals. Longer literals come
+        // first so the leftmost match is also the longest.
+        let mut literals: Vec<&LiteralShield> = self.qalqons_literals.iter().collect();
+        literals.sort_by_key(|l| std::cmp::Reverse(l.text.len()));
+        let mut groups: Vec<(LiteralFlags, Vec<String>)> = Vec::new();
+        for literal in literals {
+            let escaped = regex::escape(&literal.text);
+            match groups.iter_mut().find(|(flags, _)| *flags == literal.flags) {
+                Some((_, group)) => group.push(escaped),
+                None => groups.push((literal.flags, vec![escaped])),
+            }
         }
+        self.qalqons_lit = groups
+            .into_iter()
+            .map(|(flags, group)| {
+                let re = RegexBuilder::new(&group.join("|"))
+                    .case_insensitive(!flags.exact_case)
+                    .build()?;
+                Ok((re, flags))
+            })
+            .collect::<Result<_, regex::Error>>()?;
 
         Ok(())
     }
 
+    /// The `shakl` literal written in the file for a `matched` text.
+    #[must_use]
+    pub fn recased_literal(&self, matched: &str) -> Option<usize> {
+        let folded = matched.to_lowercase();
+        self.qalqons_literals.iter().position(|l| {
+            l.flags.recase
+                && if l.flags.exact_case {
+                    l.text == matched
+                } else {
+                    l.text.to_lowercase() == folded
+                }
+        })
+    }
+
     // --- LOOKUP LOGIC ---
 
     #[must_use]
@@ -5,15 +5,16 @@ pub(crate) mod latin;
 pub(crate) use cyrillic::CyrillicMapper;
 pub(crate) use latin::LatinMapper;
 
-use crate::shield::Shield;
-use crate::types::Chunk;
+use crate::shield::{Shield, epub, fb2, odf, office, rtf, sql, table};
+use crate::types::{Chunk, decode_unicode_escapes, encode_unicode_escapes, escape_literal};
 use crate::{
-    Sozlama, Tartib,
+    HujjatTuri, Sozlama, Tartib, TekshiruvHatosi,
     symbols::{BARCHA_TUTUQ_TURLARI, CYR_VOWELS, MAP_1_TO_1, OKINA, OKINA_STR, TUTUQ, TUTUQ_STR},
 };
 use regex::Regex;
 use std::borrow::Cow;
 use std::io::{self, Write};
+use std::ops::Range;
 use std::sync::LazyLock;
 
 // Optimization: Pre-compile regex for 'sh'/'ch' collision detection in Joriy mode
@@ -24,12 +25,20 @@ pub(crate) static RE_SH: LazyLock<Regex> =
 /// Stateless wrapper around configuration.
 pub struct Engine<'a> {
     pub(crate) config: &'a Sozlama,
+    hujjat: HujjatTuri,
 }
 
 impl<'a> Engine<'a> {
     #[must_use]
     pub fn new(config: &'a Sozlama) -> Self {
-        Self { config }
+        Self::with_document(config, config.hujjat)
+    }
+
+    /// Creates an engine that shields input as the given document type,
+    /// overriding the configured default.
+    #[must_use]
+    pub fn with_document(config: &'a Sozlama, hujjat: HujjatTuri) -> Self {
+        Self { config, hujjat }
     }
 
     /// Convenience method for string-to-string conversion.
@@ -41,37 +50,409 @@ impl<'a> Engine<'a> {
         String::from_utf8(buf).unwrap_or_else(|_| input.to_string())
     }
 
+    /// Converts plain text like `run`, also returning the source and output
+    /// offsets at which the conversion is in step: all of the source before
+    /// each first offset became all of the output before the second.
+    fn run_marked(&self, input: &str) -> (String, Vec<(usize, usize)>) {
+        let mut buf = Vec::with_capacity(input.len());
+        let mut marks = Vec::new();
+        self.convert_shielded(input, &mut buf, Some(&mut marks))
+            .expect("In-memory buffer write failed");
+        let converted = String::from_utf8(buf).unwrap_or_else(|_| input.to_string());
+        (converted, marks)
+    }
+
     /// Core streaming conversion method.
     /// Uses a reusable buffer to minimize allocation during word processing.
     pub fn convert_stream<W: Write + ?Sized>(&self, input: &str, writer: &mut W) -> io::Result<()> {
-        let mut chunks = Shield::tokenize(input, self.config).peekable();
+        self.convert_reporting(input, writer).map(drop)
+    }
+
+    /// Like `convert_stream`, but also returns the unbalanced universal shield
+    /// markers that the conversion left as plain text. Documents converted
+    /// cell by cell or run by run report none.
+    pub fn convert_reporting<'i, W: Write + ?Sized>(
+        &self,
+        input: &'i str,
+        writer: &mut W,
+    ) -> io::Result<Vec<TekshiruvHatosi<'i>>> {
+        let streamed = match self.hujjat {
+            HujjatTuri::Csv | HujjatTuri::Tsv => self.convert_table(input, writer),
+            HujjatTuri::Ofis => {
+                let runs = office::paragraphs(input);
+                self.convert_runs(input, runs, office::language_edits(input), writer)
+            }
+            HujjatTuri::Odf => {
+                let runs = odf::paragraphs(input);
+                self.convert_runs(input, runs, odf::language_edits(input), writer)
+            }
+            HujjatTuri::Epub => {
+                let entries = epub::paragraphs(input);
+                self.convert_runs(input, entries, epub::language_edits(input), writer)
+            }
+            HujjatTuri::Fb2 => {
+                let runs = fb2::paragraphs(input);
+                self.convert_runs(input, runs, fb2::language_edits(input), writer)
+            }
+            HujjatTuri::Rtf => self.convert_rtf(input, writer),
+            HujjatTuri::Sql => self.convert_sql(input, writer),
+            _ => return self.convert_shielded(input, writer, None),
+        };
+        streamed.map(|()| Vec::new())
+    }
+
+    /// Converts the chunks of the shield, returning its marker warnings.
+    /// Records the offsets at chunk and word boundaries into `marks`.
+    fn convert_shielded<'i, W: Write + ?Sized>(
+        &self,
+        input: &'i str,
+        writer: &mut W,
+        mut marks: Option<&mut Vec<(usize, usize)>>,
+    ) -> io::Result<Vec<TekshiruvHatosi<'i>>> {
+        let mut tokens = Shield::tokenize(input, self.config, self.hujjat);
+        let writer = &mut Tally::new(writer);
         let mut prev_char: Option<char> = None;
 
         // OPTIMIZATION: Reusable buffer for words.
         // Allocated ONCE, reused for every word in the stream.
         let mut word_buffer = String::with_capacity(64);
-
-        while let Some(chunk) = chunks.next() {
+        // Converted attribute values and literals are re-escaped before they are written.
+        let mut quoted_buffer: Vec<u8> = Vec::new();
+
+        // One chunk of lookahead, kept with its input range
+        let mut next = tokens.next().map(|chunk| (chunk, tokens.span()));
+        while let Some((chunk, span)) = next.take() {
+            next = tokens.next().map(|chunk| (chunk, tokens.span()));
+            let next_is_shielded =
+                matches!(next, Some((Chunk::Shielded(_) | Chunk::Recased(..), _)));
+            if let (Some(marks), Some(span)) = (marks.as_deref_mut(), &span) {
+                marks.push((span.start, writer.written));
+            }
             match chunk {
                 Chunk::Shielded(text) => {
                     writer.write_all(text.as_bytes())?;
                     prev_char = text.chars().last();
                 }
+                Chunk::Recased(_, idx) => {
+                    let text = &self.config.lughat.qalqons_literals[idx].text;
+                    writer.write_all(text.as_bytes())?;
+                    prev_char = text.chars().last();
+                }
                 Chunk::Safe(text) => {
-                    let next_is_shielded = matches!(chunks.peek(), Some(Chunk::Shielded(_)));
-
                     let mut processor =
                         ChunkProcessor::new(text, prev_char, next_is_shielded, self.config);
 
                     // Pass the reused buffer to avoid inner loop allocations
-                    processor.process_to_writer(writer, &mut word_buffer)?;
+                    let word_marks = marks.as_deref_mut().zip(span.as_ref().map(|s| s.start));
+                    processor.process_to_writer(writer, &mut word_buffer, word_marks)?;
+
+                    prev_char = text.chars().last();
+                }
+                Chunk::Attribute(text, quote) => {
+                    self.convert_quoted(
+                        text,
+                        prev_char,
+                        next_is_shielded,
+                        &mut quoted_buffer,
+                        &mut word_buffer,
+                    )?;
+                    let converted = String::from_utf8_lossy(&quoted_buffer);
+                    writer.write_all(quote.escape(&converted).as_bytes())?;
 
                     prev_char = text.chars().last();
                 }
+                Chunk::Escaped(text) => {
+                    // Converted as the characters the escapes stand for
+                    let decoded = decode_unicode_escapes(text);
+                    self.convert_quoted(
+                        &decoded,
+                        prev_char,
+                        next_is_shielded,
+                        &mut quoted_buffer,
+                        &mut word_buffer,
+                    )?;
+                    let converted = String::from_utf8_lossy(&quoted_buffer);
+                    writer.write_all(encode_unicode_escapes(&converted, text).as_bytes())?;
+
+                    prev_char = decoded.chars().last();
+                }
+                Chunk::Literal(text, quote) => {
+                    self.convert_quoted(
+                        text,
+                        prev_char,
+                        next_is_shielded,
+                        &mut quoted_buffer,
+                        &mut word_buffer,
+                    )?;
+                    let converted = String::from_utf8_lossy(&quoted_buffer);
+                    writer.write_all(escape_literal(&converted, quote).as_bytes())?;
+
+                    prev_char = text.chars().last();
+                }
+            }
+            if let (Some(marks), Some(span)) = (marks.as_deref_mut(), span) {
+                marks.push((span.end, writer.written));
+            }
+        }
+        Ok(tokens.marker_warnings())
+    }
+
+    /// Converts the selected columns of a CSV/TSV document row by row, so
+    /// that the whole file is never held in memory. With a copy suffix the
+    /// original column is kept and a converted copy is added after it.
+    fn convert_table<W: Write + ?Sized>(&self, input: &str, writer: &mut W) -> io::Result<()> {
+        let delimiter = table::delimiter(input, self.hujjat);
+        let mut rows = table::Rows::new(input, delimiter);
+        let Some(header) = rows.next() else {
+            return Ok(());
+        };
+        let selected = table::selected_columns(input, &header, &self.config.ustunlar);
+        let suffix = self.config.nusxa_qoeshimchasi.as_deref();
+        // Cells are short plain text
+        let cells = Engine::with_document(self.config, HujjatTuri::Matn);
+
+        for (i, field) in header.fields.iter().enumerate() {
+            if i > 0 {
+                writer.write_all(&[delimiter])?;
+            }
+            writer.write_all(input[field.range.clone()].as_bytes())?;
+            if let Some(suffix) = suffix
+                && selected[i]
+            {
+                let name = format!("{}{suffix}", table::column_name(input, field));
+                writer.write_all(&[delimiter])?;
+                write_cell(writer, &name, field.quoted, delimiter)?;
             }
         }
+        writer.write_all(input[header.terminator].as_bytes())?;
+
+        for row in rows {
+            for (i, field) in row.fields.iter().enumerate() {
+                if i > 0 {
+                    writer.write_all(&[delimiter])?;
+                }
+                let raw = &input[field.range.clone()];
+                if !selected.get(i).copied().unwrap_or(false) {
+                    writer.write_all(raw.as_bytes())?;
+                    continue;
+                }
+                let text = &input[field.content.clone()];
+                let converted = if field.quoted {
+                    cells.run(&text.replace("\"\"", "\""))
+                } else {
+                    cells.run(text)
+                };
+                if suffix.is_some() {
+                    writer.write_all(raw.as_bytes())?;
+                    writer.write_all(&[delimiter])?;
+                } else if field.quoted {
+                    // Keeps a missing closing quote missing and stray bytes after it
+                    writer.write_all(b"\"")?;
+                    writer.write_all(converted.replace('"', "\"\"").as_bytes())?;
+                    writer.write_all(&input.as_bytes()[field.content.end..field.range.end])?;
+                    continue;
+                }
+                write_cell(writer, &converted, field.quoted, delimiter)?;
+            }
+            writer.write_all(input[row.terminator].as_bytes())?;
+        }
         Ok(())
     }
+
+    /// Converts the text runs of an office document part paragraph by
+    /// paragraph and applies the language `edits`. The converted paragraph
+    /// is split back into its runs where the converted text before each run
+    /// boundary ends, so a word split across differently formatted runs
+    /// keeps its casing and dictionary spelling.
+    fn convert_runs<W: Write + ?Sized>(
+        &self,
+        input: &str,
+        paragraphs: Vec<Vec<Range<usize>>>,
+        edits: Vec<(Range<usize>, &'static str)>,
+        writer: &mut W,
+    ) -> io::Result<()> {
+        // Run text is plain text with character references
+        let cells = Engine::with_document(self.config, HujjatTuri::Matn);
+        let mut edits: Vec<(Range<usize>, Cow<'_, str>)> = edits
+            .into_iter()
+            .map(|(range, text)| (range, Cow::Borrowed(text)))
+            .collect();
+
+        for runs in paragraphs {
+            let texts: Vec<&str> = runs.iter().map(|run| &input[run.clone()]).collect();
+            let Some(pieces) = convert_split(&cells, &texts) else {
+                continue;
+            };
+            for ((run, text), piece) in runs.into_iter().zip(texts).zip(pieces) {
+                if piece != text {
+                    edits.push((run, Cow::Owned(piece)));
+                }
+            }
+        }
+        write_edits(input, edits, writer)
+    }
+
+    /// Converts the text runs of an RTF document paragraph by paragraph,
+    /// like `convert_runs`, and writes the changed runs back as RTF.
+    fn convert_rtf<W: Write + ?Sized>(&self, input: &str, writer: &mut W) -> io::Result<()> {
+        let cells = Engine::with_document(self.config, HujjatTuri::Matn);
+        let mut edits = Vec::new();
+
+        for segments in rtf::paragraphs(input) {
+            let texts: Vec<&str> = segments.iter().map(|s| s.text.as_str()).collect();
+            let Some(pieces) = convert_split(&cells, &texts) else {
+                continue;
+            };
+            for (segment, piece) in segments.iter().zip(pieces) {
+                if piece != segment.text {
+                    edits.push((
+                        segment.range.clone(),
+                        Cow::Owned(rtf::encode(&piece, segment)),
+                    ));
+                }
+            }
+        }
+        write_edits(input, edits, writer)
+    }
+
+    /// Converts the selected string literals and `COPY` fields of an SQL
+    /// dump as they are read, allocating only for the literal at hand.
+    /// Text between backslash escapes is converted on its own and quotes in
+    /// the converted text are doubled.
+    fn convert_sql<W: Write + ?Sized>(&self, input: &str, writer: &mut W) -> io::Result<()> {
+        // Literals are short plain text
+        let cells = Engine::with_document(self.config, HujjatTuri::Matn);
+        let literals = sql::Literals::new(input, &self.config.jadvallar, &self.config.ustunlar);
+        let mut pos = 0;
+
+        for literal in literals.filter(|l| l.selected) {
+            for run in literal.runs(input) {
+                let text = &input[run.clone()];
+                let converted = if literal.escapes == sql::Escapes::Copy {
+                    cells.run(text)
+                } else {
+                    cells.run(&text.replace("''", "'")).replace('\'', "''")
+                };
+                if converted == text {
+                    continue;
+                }
+                writer.write_all(&input.as_bytes()[pos..run.start])?;
+                writer.write_all(converted.as_bytes())?;
+                pos = run.end;
+            }
+        }
+        writer.write_all(&input.as_bytes()[pos..])
+    }
+
+    /// Converts a quoted value into `buffer` so it can be re-escaped before writing.
+    fn convert_quoted(
+        &self,
+        text: &str,
+        prev_char: Option<char>,
+        next_is_shielded: bool,
+        buffer: &mut Vec<u8>,
+        word_buffer: &mut String,
+    ) -> io::Result<()> {
+        let mut processor = ChunkProcessor::new(text, prev_char, next_is_shielded, self.config);
+        buffer.clear();
+        processor.process_to_writer(&mut Tally::new(buffer), word_buffer, None)
+    }
+}
+
+/// Converts `runs` as one text and splits the result back into runs at the
+/// output offsets of the run boundaries. A boundary inside a word is placed
+/// after the conversion of the word's start. Returns `None` when the
+/// conversion changes nothing.
+fn convert_split(cells: &Engine<'_>, runs: &[&str]) -> Option<Vec<String>> {
+    let text = runs.concat();
+    let (converted, marks) = cells.run_marked(&text);
+    if converted == text {
+        return None;
+    }
+    let mut pieces = Vec::with_capacity(runs.len());
+    let mut boundary = 0;
+    let mut start = 0;
+    for (i, run) in runs.iter().enumerate() {
+        boundary += run.len();
+        let end = if i + 1 == runs.len() {
+            converted.len()
+        } else {
+            let idx = marks.partition_point(|&(source, _)| source <= boundary);
+            let (source, output) = idx.checked_sub(1).map_or((0, 0), |i| marks[i]);
+            let limit = marks
+                .get(idx)
+                .map_or(converted.len(), |&(_, output)| output);
+            let mut end = if source == boundary {
+                output
+            } else {
+                output + cells.run(&text[source..boundary]).len()
+            }
+            .clamp(start, limit.max(start));
+            while !converted.is_char_boundary(end) {
+                end -= 1;
+            }
+            end
+        };
+        pieces.push(converted[start..end].to_string());
+        start = end;
+    }
+    Some(pieces)
+}
+
+/// Writes `input` with the ranges of `edits` replaced.
+fn write_edits<W: Write + ?Sized>(
+    input: &str,
+    mut edits: Vec<(Range<usize>, Cow<'_, str>)>,
+    writer: &mut W,
+) -> io::Result<()> {
+    edits.sort_by_key(|(range, _)| range.start);
+    let mut pos = 0;
+    for (range, text) in edits {
+        writer.write_all(&input.as_bytes()[pos..range.start])?;
+        writer.write_all(text.as_bytes())?;
+        pos = range.end;
+    }
+    writer.write_all(&input.as_bytes()[pos..])
+}
+
+/// Writes a CSV/TSV cell, quoting it when it was quoted or now needs quotes.
+fn write_cell<W: Write + ?Sized>(
+    writer: &mut W,
+    text: &str,
+    quoted: bool,
+    delimiter: u8,
+) -> io::Result<()> {
+    let needs_quotes = text
+        .bytes()
+        .any(|b| b == delimiter || matches!(b, b'"' | b'\n' | b'\r'));
+    if !quoted && !needs_quotes {
+        return writer.write_all(text.as_bytes());
+    }
+    write!(writer, "\"{}\"", text.replace('"', "\"\""))
+}
+
+/// A writer that counts the bytes written through it.
+struct Tally<'w, W: ?Sized> {
+    inner: &'w mut W,
+    written: usize,
+}
+
+impl<'w, W: Write + ?Sized> Tally<'w, W> {
+    fn new(inner: &'w mut W) -> Self {
+        Self { inner, written: 0 }
+    }
+}
+
+impl<W: Write + ?Sized> Write for Tally<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = self.inner.write(buf)?;
+        self.written += len;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 /// Context-aware processor for a specific "Safe" text chunk.
@@ -93,15 +474,23 @@ impl<'a> ChunkProcessor<'a> {
         }
     }
 
+    /// Converts the chunk into `writer`. With `marks` and the input offset of
+    /// the chunk, records the offsets between words (see `Engine::run_marked`).
     fn process_to_writer<W: Write + ?Sized>(
         &mut self,
-        writer: &mut W,
+        writer: &mut Tally<'_, W>,
         word_buffer: &mut String,
+        mut marks: Option<(&mut Vec<(usize, usize)>, usize)>,
     ) -> io::Result<()> {
         let mut byte_idx = 0;
         let mut prev_char = self.prev_char_boundary;
 
         while byte_idx < self.text.len() {
+            if let Some((marks, offset)) = marks.as_mut()
+                && word_buffer.is_empty()
+            {
+                marks.push((*offset + byte_idx, writer.written));
+            }
             let rest = &self.text[byte_idx..];
             let c = rest.chars().next().unwrap();
             let char_len = c.len_utf8();
@@ -1,4 +1,5 @@
 mod config;
+mod debug;
 mod dictionary;
 mod engine;
 mod messages;
@@ -16,4 +17,7 @@ pub use config::Sozlama;
 pub use messages::HabarKaliti;
 pub use symbols::{BARCHA_TUTUQ_TURLARI, ODATIY_TIRNOQ, OKINA, TESKARI_TIRNOQ, TUTUQ};
 pub use translator::Oegirgich;
-pub use types::{Tartib, TekshiruvHatosi, TekshiruvHulosasi};
+pub use types::{
+    HujjatTuri, KodTili, Koerinish, Qalqon, TarjimaFormati, Tartib, TekshiruvHatosi,
+    TekshiruvHulosasi,
+};
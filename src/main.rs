@@ -1,6 +1,6 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::Parser;
-use latinga::{HabarKaliti, Oegirgich, Sozlama, Tartib};
+use latinga::{HabarKaliti, Koerinish, Oegirgich, Qalqon, Sozlama, Tartib, TekshiruvHatosi};
 use memmap2::MmapOptions;
 use std::collections::BTreeSet;
 use std::fs::{self, File};
@@ -8,7 +8,7 @@ use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
 
 mod cli;
-use cli::{files, view};
+use cli::{files, office, view};
 
 /// Default number of detailed errors to show in validation mode
 const DEFAULT_VALIDATION_LIMIT: usize = 5;
@@ -40,12 +40,69 @@ struct Cli {
     #[arg(short = 'n', long = "qalqon-fayl")]
     qalqon_fayl: Option<PathBuf>,
 
+    #[arg(short = 'o', long = "ochiq", action = clap::ArgAction::Append)]
+    ochiq: Vec<String>,
+
+    #[arg(short = 'g', long = "ochiq-atribut")]
+    ochiq_atribut: Option<String>,
+
+    #[arg(short = 'k', long = "muqaddima")]
+    muqaddima: Option<String>,
+
+    #[arg(short = 'r', long = "kalitlar")]
+    kalitlar: Option<String>,
+
+    #[arg(long = "ustunlar")]
+    ustunlar: Option<String>,
+
+    #[arg(long = "jadvallar")]
+    jadvallar: Option<String>,
+
+    #[arg(long = "nusxa", require_equals = true, num_args = 0..=1)]
+    nusxa: Option<Option<String>>,
+
+    #[arg(short = 'x', long = "latex")]
+    latex: Option<String>,
+
+    #[arg(short = 'e', long = "belgilar")]
+    belgilar: Option<String>,
+
+    #[arg(short = 's', long = "belgilarni-saqla")]
+    belgilarni_saqla: bool,
+
+    #[arg(short = 'p', long = "profil")]
+    profil: Option<String>,
+
+    #[arg(long = "yoq")]
+    yoq: Option<String>,
+
+    #[arg(long = "oechir")]
+    oechir: Option<String>,
+
+    #[arg(short = 'l', long = "tilsizni-qoldir")]
+    tilsizni_qoldir: bool,
+
+    #[arg(short = 'i', long = "izohlar")]
+    izohlar: bool,
+
+    #[arg(long = "heshteglar")]
+    heshteglar: bool,
+
+    #[arg(long = "asl-matn")]
+    asl_matn: bool,
+
+    #[arg(long = "noaniqni-qoldir")]
+    noaniqni_qoldir: bool,
+
     #[arg(short = 'b', long = "batafsil")]
     batafsil: bool,
 
     #[arg(short = 'm', long = "almashtir")]
     almashtir: Option<String>,
 
+    #[arg(short = 'd', long = "qalqon-koersat")]
+    qalqon_koersat: Option<Option<String>>,
+
     #[arg(short = 't', long = "tekshir")]
     tekshir: Option<Option<usize>>,
 
@@ -84,6 +141,12 @@ fn main() -> Result<()> {
 
     // 3. Build Configuration and Discover Files
     let config = build_config(&cli)?;
+    let profile = match &cli.profil {
+        Some(name) => {
+            files::Profile::parse(name).ok_or_else(|| anyhow!("Nomaʼlum profil: {name}"))?
+        }
+        None => files::Profile::Auto,
+    };
     let translator = Oegirgich::yangi(config);
     let targets = files::discover_files(&cli.files, cli.fayl_qolipi.as_deref())?;
 
@@ -99,20 +162,27 @@ fn main() -> Result<()> {
         Some(Some(n)) => Some(n),
     };
 
-    if let Some(error_limit) = validation_limit {
+    if let Some(format) = &cli.qalqon_koersat {
+        let koerinish = match format.as_deref().map(str::to_ascii_lowercase).as_deref() {
+            None | Some("ansi") => Koerinish::Ansi,
+            Some("html") => Koerinish::Html,
+            Some(other) => return Err(anyhow!("Nomaʼlum koʻrinish: {other}")),
+        };
+        show_shields(&targets, &translator, profile, koerinish)?;
+    } else if let Some(error_limit) = validation_limit {
         let has_errors = if targets.is_empty() {
-            validate_stdin(&translator, error_limit)?
+            validate_stdin(&translator, profile, error_limit)?
         } else {
-            validate_files(&targets, &translator, error_limit)?
+            validate_files(&targets, &translator, profile, error_limit)?
         };
 
         if has_errors {
             std::process::exit(1);
         }
     } else if targets.is_empty() {
-        process_stdin(&translator)?;
+        process_stdin(&translator, profile)?;
     } else {
-        process_files(&targets, &translator, &cli, &current_suffix)?;
+        process_files(&targets, &translator, &cli, profile, &current_suffix)?;
     }
 
     Ok(())
@@ -146,12 +216,86 @@ fn build_config(cli: &Cli) -> Result<Sozlama> {
         }
     }
 
+    if let Some(raw_input) = &cli.muqaddima {
+        // Resolve input: File Path OR Comma-delimited String
+        let content = resolve_input_source(raw_input, ',')?;
+        cfg.muqaddima_kalitlarini_belgila(&content);
+    }
+    if let Some(raw_input) = &cli.kalitlar {
+        // Resolve input: File Path OR Comma-delimited String
+        let content = resolve_input_source(raw_input, ',')?;
+        cfg.kalit_yollarini_belgila(&content);
+    }
+    if let Some(raw_input) = &cli.ustunlar {
+        // Resolve input: File Path OR Comma-delimited String
+        let content = resolve_input_source(raw_input, ',')?;
+        cfg.ustunlarni_belgila(&content);
+    }
+    if let Some(raw_input) = &cli.jadvallar {
+        // Resolve input: File Path OR Comma-delimited String
+        let content = resolve_input_source(raw_input, ',')?;
+        cfg.jadvallarni_belgila(&content);
+    }
+    if let Some(suffix) = &cli.nusxa {
+        cfg.nusxa_qoeshimchasini_belgila(suffix.as_deref().unwrap_or("_lotin"));
+    }
+    if let Some(raw_input) = &cli.latex {
+        // Resolve input: File Path OR Semicolon-delimited String
+        let content = resolve_input_source(raw_input, ';')?;
+        cfg.latex_buyruqlarini_yukla(&content);
+    }
+    if cli.tilsizni_qoldir {
+        cfg.tilsizni_oegirishni_belgila(false);
+    }
+    if cli.izohlar {
+        cfg.izohlarni_oegirishni_belgila(true);
+    }
+    if cli.heshteglar {
+        cfg.heshteglarni_oegirishni_belgila(true);
+    }
+    if cli.asl_matn {
+        cfg.asl_matnni_oegirishni_belgila(true);
+    }
+    if cli.noaniqni_qoldir {
+        cfg.noaniqlarni_oegirishni_belgila(false);
+    }
+    if let Some(raw_input) = &cli.belgilar {
+        // Resolve input: File Path OR Semicolon-delimited String
+        let content = resolve_input_source(raw_input, ';')?;
+        cfg.qalqon_belgilarini_belgila(&content);
+    }
+    if cli.belgilarni_saqla {
+        cfg.belgilarni_saqlashni_belgila(true);
+    }
+    for (raw_input, yoqilgan) in [(&cli.yoq, true), (&cli.oechir, false)] {
+        let Some(raw_input) = raw_input else {
+            continue;
+        };
+        // Comma-delimited shield names
+        for name in raw_input
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+        {
+            let qalqon = Qalqon::nomdan(name).ok_or_else(|| anyhow!("Nomaʼlum qalqon: {name}"))?;
+            cfg.qalqonni_belgila(qalqon, yoqilgan);
+        }
+    }
+
     for n in &cli.qalqon {
         cfg.qalqonlarni_yukla(n)?;
     }
     if let Some(p) = &cli.qalqon_fayl {
         cfg.qalqonlarni_yukla(&fs::read_to_string(p)?)?;
     }
+    for n in &cli.ochiq {
+        cfg.ochiq_qoliplarni_yukla(n)?;
+    }
+    if let Some(raw_input) = &cli.ochiq_atribut {
+        // Resolve input: File Path OR Comma-delimited String
+        let content = resolve_input_source(raw_input, ',')?;
+        cfg.ochiq_atributlarni_yukla(&content);
+    }
 
     Ok(cfg)
 }
@@ -170,15 +314,53 @@ fn resolve_input_source(input: &str, delimiter: char) -> Result<String> {
     }
 }
 
-fn process_stdin(trans: &Oegirgich) -> Result<()> {
+/// Prints the inputs with their shielded spans highlighted instead of converting them.
+fn show_shields(
+    files: &BTreeSet<PathBuf>,
+    trans: &Oegirgich,
+    profile: files::Profile,
+    koerinish: Koerinish,
+) -> Result<()> {
+    let stdout = io::stdout();
+    let mut writer = io::BufWriter::new(stdout.lock());
+
+    if files.is_empty() {
+        let mut buf = String::new();
+        io::stdin().read_to_string(&mut buf)?;
+        let hujjat = files::document_type(None, &buf, profile, trans.sozlama.hujjat);
+        writer.write_all(
+            trans
+                .qalqonlarni_koersat(&buf, hujjat, koerinish)
+                .as_bytes(),
+        )?;
+    }
+    for p in files {
+        let content = fs::read_to_string(p)?;
+        let hujjat = files::document_type(Some(p), &content, profile, trans.sozlama.hujjat);
+        if koerinish == Koerinish::Ansi {
+            writeln!(writer, "\x1b[1m{}\x1b[0m", p.display())?;
+        }
+        writer.write_all(
+            trans
+                .qalqonlarni_koersat(&content, hujjat, koerinish)
+                .as_bytes(),
+        )?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn process_stdin(trans: &Oegirgich, profile: files::Profile) -> Result<()> {
     let mut buf = String::new();
     io::stdin().read_to_string(&mut buf)?;
     if !buf.is_empty() {
         // STREAMING: Write directly to stdout lock via BufWriter for performance
         let stdout = io::stdout();
         let mut writer = io::BufWriter::new(stdout.lock());
-        trans.oqimni_oegir(&buf, &mut writer)?;
+        let hujjat = files::document_type(None, &buf, profile, trans.sozlama.hujjat);
+        let warnings = trans.hujjatni_oegir(&buf, hujjat, &mut writer)?;
         writer.flush()?;
+        print_warnings(Path::new("stdin"), &buf, &warnings, trans);
     }
     Ok(())
 }
@@ -187,6 +369,7 @@ fn process_files(
     files: &BTreeSet<PathBuf>,
     trans: &Oegirgich,
     cli: &Cli,
+    profile: files::Profile,
     suffix: &str,
 ) -> Result<()> {
     use rayon::prelude::*;
@@ -194,7 +377,7 @@ fn process_files(
     // Parallel processing for file conversion
     let successful_count: usize = files
         .par_iter()
-        .map(|p| process_single_file(p, trans, cli, suffix))
+        .map(|p| process_single_file(p, trans, cli, profile, suffix))
         .sum();
 
     if cli.batafsil {
@@ -206,7 +389,13 @@ fn process_files(
     Ok(())
 }
 
-fn process_single_file(p: &PathBuf, trans: &Oegirgich, cli: &Cli, suffix: &str) -> usize {
+fn process_single_file(
+    p: &PathBuf,
+    trans: &Oegirgich,
+    cli: &Cli,
+    profile: files::Profile,
+    suffix: &str,
+) -> usize {
     let p_str = p.to_string_lossy();
 
     if cli.batafsil {
@@ -218,24 +407,43 @@ fn process_single_file(p: &PathBuf, trans: &Oegirgich, cli: &Cli, suffix: &str)
 
     // Use IIFE to handle Result logic concisely
     let result = (|| -> Result<()> {
+        // Office documents are zip packages of XML parts
+        if office::is_package(p) {
+            let converted = office::convert_package(&fs::read(p)?, trans)?;
+            if cli.ustidan_yoz {
+                files::atomic_write_stream(p, |writer| writer.write_all(&converted))?;
+            } else {
+                fs::write(files::get_output_path(p, suffix), converted)?;
+            }
+            return Ok(());
+        }
+
         let file = File::open(p)?;
 
         // Zero-Copy: Map file to memory
         // SAFETY: We assume the file is not modified externally during processing
         let mmap = unsafe { MmapOptions::new().map(&file)? };
         let content = std::str::from_utf8(&mmap)?;
+        let hujjat = files::document_type(Some(p), content, profile, trans.sozlama.hujjat);
 
-        if cli.ustidan_yoz {
+        let warnings = if cli.ustidan_yoz {
             // STREAMING: Atomic write with a streaming closure
-            files::atomic_write_stream(p, |writer| trans.oqimni_oegir(content, writer))?;
+            let mut warnings = Vec::new();
+            files::atomic_write_stream(p, |writer| {
+                warnings = trans.hujjatni_oegir(content, hujjat, writer)?;
+                Ok(())
+            })?;
+            warnings
         } else {
             // STREAMING: Standard file write via BufWriter
             let out_path = files::get_output_path(p, suffix);
             let out_file = File::create(out_path)?;
             let mut writer = io::BufWriter::new(out_file);
-            trans.oqimni_oegir(content, &mut writer)?;
+            let warnings = trans.hujjatni_oegir(content, hujjat, &mut writer)?;
             writer.flush()?;
-        }
+            warnings
+        };
+        print_warnings(p, content, &warnings, trans);
         Ok(())
     })();
 
@@ -251,19 +459,40 @@ fn process_single_file(p: &PathBuf, trans: &Oegirgich, cli: &Cli, suffix: &str)
     }
 }
 
-fn validate_files(files: &BTreeSet<PathBuf>, trans: &Oegirgich, limit: usize) -> Result<bool> {
+/// Reports unbalanced shield markers left unprotected by a conversion.
+fn print_warnings(p: &Path, content: &str, warnings: &[TekshiruvHatosi], trans: &Oegirgich) {
+    if warnings.is_empty() {
+        return;
+    }
+    let label = trans.habar(HabarKaliti::OgohlantirishNomi);
+    for warning in warnings {
+        view::render_error(p, content, warning, &label);
+    }
+}
+
+fn validate_files(
+    files: &BTreeSet<PathBuf>,
+    trans: &Oegirgich,
+    profile: files::Profile,
+    limit: usize,
+) -> Result<bool> {
     use rayon::prelude::*;
 
     // Parallel processing for validation
     let global_failure = files
         .par_iter()
-        .map(|p| validate_single_file(p, trans, limit))
+        .map(|p| validate_single_file(p, trans, profile, limit))
         .any(|has_err| has_err);
 
     Ok(global_failure)
 }
 
-fn validate_single_file(p: &PathBuf, trans: &Oegirgich, limit: usize) -> bool {
+fn validate_single_file(
+    p: &PathBuf,
+    trans: &Oegirgich,
+    profile: files::Profile,
+    limit: usize,
+) -> bool {
     // Fail gracefully if file IO fails
     let Ok(file) = File::open(p) else {
         return false;
@@ -277,7 +506,8 @@ fn validate_single_file(p: &PathBuf, trans: &Oegirgich, limit: usize) -> bool {
     };
 
     // Zero-Copy: 'summary' holds references to 'content' (the mmap)
-    let summary = trans.tekshir(content, limit);
+    let hujjat = files::document_type(Some(p), content, profile, trans.sozlama.hujjat);
+    let summary = trans.hujjatni_tekshir(content, hujjat, limit);
 
     if !summary.hatolar.is_empty() {
         eprintln!(
@@ -305,12 +535,13 @@ fn validate_single_file(p: &PathBuf, trans: &Oegirgich, limit: usize) -> bool {
     false
 }
 
-fn validate_stdin(trans: &Oegirgich, limit: usize) -> Result<bool> {
+fn validate_stdin(trans: &Oegirgich, profile: files::Profile, limit: usize) -> Result<bool> {
     let mut buf = String::new();
     io::stdin().read_to_string(&mut buf)?;
 
     // Zero-Copy: 'summary' holds references to 'buf'
-    let summary = trans.tekshir(&buf, limit);
+    let hujjat = files::document_type(None, &buf, profile, trans.sozlama.hujjat);
+    let summary = trans.hujjatni_tekshir(&buf, hujjat, limit);
 
     if !summary.hatolar.is_empty() {
         eprintln!("\n{}: stdin", trans.habar(HabarKaliti::TekshiruvBoshi));
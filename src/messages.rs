@@ -4,6 +4,7 @@ use std::borrow::Cow;
 pub enum HabarKaliti {
     TekshiruvBoshi,
     TekshiruvHatosiNomi,
+    OgohlantirishNomi,
     TutuqUchunMaslahat,
     EskiQoeshHarf(String),
     QoeshHarfTutuqlaAjratilsin,
@@ -12,6 +13,8 @@ pub enum HabarKaliti {
     JarayonHatosi(String, String),
     JarayonMuvaffaqiyati(usize),
     FaylTopilmadi,
+    YopilmaganQalqon(String),
+    OchilmaganQalqon(String),
 }
 
 impl HabarKaliti {
@@ -20,6 +23,7 @@ impl HabarKaliti {
             Tartib::Joriy => match self {
                 Self::TekshiruvBoshi => Cow::Borrowed("[!] Imlo xatolari aniqlandi"),
                 Self::TekshiruvHatosiNomi => Cow::Borrowed("\x1b[1;31mxato\x1b[0m"),
+                Self::OgohlantirishNomi => Cow::Borrowed("\x1b[1;33mogohlantirish\x1b[0m"),
                 Self::TutuqUchunMaslahat => Cow::Borrowed(
                     "Maslahat: Avtomatik tuzatish uchun 'latinga' buyrugʻini ishlating.",
                 ),
@@ -39,10 +43,17 @@ impl HabarKaliti {
                     Cow::Owned(format!("Muvaffaqiyatli yakunlandi: {n} ta fayl"))
                 }
                 Self::FaylTopilmadi => Cow::Borrowed("Xatolik: Fayl topilmadi."),
+                Self::YopilmaganQalqon(m) => Cow::Owned(format!(
+                    "'{m}' qalqon belgisi yopilmagan, matn himoyalanmadi."
+                )),
+                Self::OchilmaganQalqon(m) => {
+                    Cow::Owned(format!("'{m}' qalqon belgisiga mos ochuvchi belgi yoʻq."))
+                }
             },
             Tartib::Kelgusi => match self {
                 Self::TekshiruvBoshi => Cow::Borrowed("[!] Imlo hatolari aniqlandi"),
                 Self::TekshiruvHatosiNomi => Cow::Borrowed("\x1b[1;31mhato\x1b[0m"),
+                Self::OgohlantirishNomi => Cow::Borrowed("\x1b[1;33mogohlantiriş\x1b[0m"),
                 Self::TutuqUchunMaslahat => {
                     Cow::Borrowed("Maslahat: Avtomatik tuzatiş uchun 'latinga' buyruğini işlating.")
                 }
@@ -62,6 +73,12 @@ impl HabarKaliti {
                     Cow::Owned(format!("Muvaffaqiyatli yakunlandi: {n} ta fayl"))
                 }
                 Self::FaylTopilmadi => Cow::Borrowed("Hatolik: Fayl topilmadi."),
+                Self::YopilmaganQalqon(m) => Cow::Owned(format!(
+                    "'{m}' qalqon belgisi yopilmagan, matn himoyalanmadi."
+                )),
+                Self::OchilmaganQalqon(m) => {
+                    Cow::Owned(format!("'{m}' qalqon belgisiga mos oçuvçi belgi yöq."))
+                }
             },
         }
     }
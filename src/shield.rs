@@ -1,12 +1,51 @@
-use crate::types::Chunk;
-use crate::{BARCHA_TUTUQ_TURLARI, Sozlama};
+mod data;
+mod directive;
+pub(crate) mod epub;
+pub(crate) mod fb2;
+mod front_matter;
+mod html;
+mod l10n;
+mod lang;
+mod latex;
+mod markdown;
+pub(crate) mod odf;
+pub(crate) mod office;
+mod po;
+pub(crate) mod rtf;
+mod social;
+mod source;
+pub(crate) mod sql;
+mod subtitle;
+pub(crate) mod table;
+mod technical;
+mod universal;
+
+use crate::types::{AttrQuote, Chunk, Rule};
+use crate::{BARCHA_TUTUQ_TURLARI, HujjatTuri, Qalqon, Sozlama};
+use crate::{HabarKaliti, TekshiruvHatosi};
+use html::{Markup, Tag};
+use lang::LangTracker;
+use latex::{Languages, ParsedArg};
 use regex::Regex;
+use std::borrow::Cow;
 use std::collections::VecDeque;
+use std::ops::Range;
 use std::sync::LazyLock;
 
 // --- 1. TOKEN-LEVEL REGEXES ---
+// Upper-case numerals where a numeral is plausible: before `.`, `)` or `-`,
+// or next to a chapter or century word (`XIX аср`, `bob IV`). Lower-case
+// ones only in brackets (`(iv)`, `f(x)`). Words such as "mix" or "DC" in
+// prose are still converted.
 static RE_ROMAN: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"(?i)\bM{0,4}(CM|D?C{0,3})(XC|XL|L?X{0,3})(IX|IV|V?I{0,3})\b").unwrap()
+    let numeral = r"M{0,4}(?:CM|CD|D?C{0,3})(?:XC|XL|L?X{0,3})(?:IX|IV|V?I{0,3})";
+    let after = r"asr|bob|qism|jild|sinf|band|аср|боб|қисм|жилд|синф|банд";
+    let before = r"bob|qism|jild|боб|қисм|жилд|chapter|part|volume";
+    let lower = numeral.to_lowercase();
+    let pattern = format!(
+        r"(?i:\b(?:{before})\s+)({numeral})\b|\b({numeral})(?:[.)-]|\s+(?i:{after}))|\(({lower})\)"
+    );
+    Regex::new(&pattern).unwrap()
 });
 static RE_CODE_BLOCK: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"(?ms)```.*?```|`[^`]+`").unwrap());
@@ -20,21 +59,7 @@ static RE_URL: LazyLock<Regex> = LazyLock::new(|| {
 static RE_HTML_ENTITY: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"&[a-zA-Z0-9#]+;").unwrap());
 static RE_KEY_VALUE: LazyLock<Regex> =
     LazyLock::new(|| Regex::new(r"[a-zA-Z0-9_-]+\s*=\s*[a-zA-Z0-9_\\\-]+").unwrap());
-static RE_ATTR_SCAN: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r#"(?i)([a-z0-9\-]+)\s*=\s*(["'][^"']*["'])"#).unwrap());
-
-const LATEX_STRUCTURAL_COMMANDS: &[&str] = &[
-    "label",
-    "cite",
-    "ref",
-    "include",
-    "input",
-    "includegraphics",
-    "usepackage",
-    "documentclass",
-    "begin",
-    "end",
-];
+
 const LATEX_VERBATIM_ENVIRONMENTS: &[&str] = &["verbatim", "lstlisting", "code", "minted"];
 
 // --- 2. THE PUBLIC API ---
@@ -42,50 +67,392 @@ const LATEX_VERBATIM_ENVIRONMENTS: &[&str] = &["verbatim", "lstlisting", "code",
 pub struct Shield;
 
 impl Shield {
-    pub fn tokenize<'a>(input: &'a str, config: &Sozlama) -> TokenIterator<'a> {
-        TokenIterator::new(input, config)
+    pub fn tokenize<'a, 'c>(
+        input: &'a str,
+        config: &'c Sozlama,
+        hujjat: HujjatTuri,
+    ) -> TokenIterator<'a, 'c> {
+        TokenIterator::new(input, config, hujjat, false)
     }
+
+    /// Like `tokenize`, but records which shield produced every shielded
+    /// byte (see `TokenIterator::labels`).
+    pub(crate) fn trace<'a, 'c>(
+        input: &'a str,
+        config: &'c Sozlama,
+        hujjat: HujjatTuri,
+    ) -> TokenIterator<'a, 'c> {
+        TokenIterator::new(input, config, hujjat, true)
+    }
+}
+
+/// The shield that claimed a span, for debug output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Source {
+    /// Not shielded.
+    #[default]
+    None,
+    Universal,
+    Latex,
+    Html,
+    ProtectedTag,
+    /// Content outside the Uzbek `lang` scopes.
+    Lang,
+    Directive,
+    FrontMatter,
+    /// Keys and structure of JSON, YAML and TOML documents.
+    Data,
+    /// Everything but the translations of a gettext catalog.
+    Catalog,
+    /// Keys, markup and untranslatable entries of localization resources.
+    Localization,
+    /// Cue numbers, timings, styling and header blocks of subtitles.
+    Subtitle,
+    /// Header row and unselected columns of CSV/TSV documents.
+    Table,
+    /// Markup of office document and e-book package parts outside their text.
+    Office,
+    Markdown,
+    /// Everything outside string literals in source files and SQL dumps.
+    Code,
+    CodeBlock,
+    Url,
+    Email,
+    Roman,
+    Entity,
+    KeyValue,
+    Emoji,
+    Domain,
+    Mention,
+    Command,
+    Hashtag,
+    Hex,
+    Version,
+    FileName,
+    Unit,
+    Formula,
+    Identifier,
+    /// A user pattern (`-q`), numbered from 1.
+    Pattern(usize),
+    /// A user word from the shield list.
+    WordList,
 }
 
-pub(crate) struct TokenIterator<'a> {
+impl Source {
+    pub(crate) fn label(self) -> Cow<'static, str> {
+        Cow::Borrowed(match self {
+            Self::None => "text",
+            Self::Universal => "universal",
+            Self::Latex => "latex",
+            Self::Html => "html",
+            Self::ProtectedTag => "protected-tag",
+            Self::Lang => "lang",
+            Self::Directive => "directive",
+            Self::FrontMatter => "front-matter",
+            Self::Data => "data",
+            Self::Catalog => "catalog",
+            Self::Localization => "l10n",
+            Self::Subtitle => "subtitle",
+            Self::Table => "table",
+            Self::Office => "office",
+            Self::Markdown => "markdown",
+            Self::Code => "code",
+            Self::CodeBlock => "code-block",
+            Self::Url => "url",
+            Self::Email => "email",
+            Self::Roman => "roman",
+            Self::Entity => "entity",
+            Self::KeyValue => "key-value",
+            Self::Emoji => "emoji",
+            Self::Domain => "domain",
+            Self::Mention => "mention",
+            Self::Command => "command",
+            Self::Hashtag => "hashtag",
+            Self::Hex => "hex",
+            Self::Version => "version",
+            Self::FileName => "file-name",
+            Self::Unit => "unit",
+            Self::Formula => "formula",
+            Self::Identifier => "identifier",
+            Self::Pattern(n) => return Cow::Owned(format!("pattern-{n}")),
+            Self::WordList => "word-list",
+        })
+    }
+}
+
+pub(crate) struct TokenIterator<'a, 'c> {
     input: &'a str,
+    config: &'c Sozlama,
     cursor: usize,
     mask: Vec<bool>,
-    buffer: VecDeque<Chunk<'a>>,
+    /// Shield of every masked byte, recorded only when tracing.
+    origins: Option<Vec<Source>>,
+    /// Scanner that produced the chunks being returned.
+    source: Source,
+    /// Queued chunks with their input ranges (`None` for fixed replacements).
+    buffer: VecDeque<(Chunk<'a>, Option<Range<usize>>)>,
+    /// Input range of the chunk last returned by `next`.
+    span: Option<Range<usize>>,
+    /// Start of the end tag closing the current raw text element
+    /// (`<textarea>`, `<title>`, ...). Before it, `<` is plain text.
+    raw_text_end: usize,
+    /// Scanners enabled for the document type (`Qalqon::Umumiy`,
+    /// `Qalqon::Latex` and `Qalqon::Html`).
+    universal: bool,
+    latex: bool,
+    html: bool,
+    /// Babel/polyglossia language switches seen so far.
+    latex_langs: Languages,
+    /// XML has no raw text elements; `<title>` is ordinary markup there.
+    xml: bool,
+    /// `lang` scopes of HTML and XML documents.
+    langs: Option<LangTracker>,
+    /// Single-quoted source literals (sorted) and the index of the next one.
+    literals: Vec<(Range<usize>, char)>,
+    literal_idx: usize,
+    /// Sorted masked spans emitted as their own chunks, and the index of
+    /// the next one. Empty spans insert text.
+    rewrites: Vec<(Range<usize>, Rewrite)>,
+    rewrite_idx: usize,
+    /// Unbalanced universal shield markers: (range, whether it is an opener).
+    marker_warnings: Vec<(Range<usize>, bool)>,
+    /// Validator rules turned off by inline directives.
+    suppressions: Suppressions,
+    /// Sorted, disjoint `ochiq_qoliplar` windows converted despite shields.
+    openings: Vec<Range<usize>>,
+}
+
+/// Validator rules suppressed by inline directives, by line.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Suppressions(Vec<directive::Suppression>);
+
+impl Suppressions {
+    /// Whether `rule` is suppressed on `line` (1-based).
+    pub(crate) fn contains(&self, line: usize, rule: Rule) -> bool {
+        self.0
+            .iter()
+            .any(|s| s.lines.contains(&line) && s.rules.contains(&rule))
+    }
+}
+
+/// A masked span handed to the engine as its own chunk kind.
+#[derive(Debug, Clone, Copy)]
+enum Rewrite {
+    /// `shakl` literal, written as the dictionary literal at this index.
+    Recase(usize),
+    /// `\uXXXX` escapes of a data value, converted after decoding.
+    Unescape,
+    /// Fixed replacement, e.g. a relabelled catalog header.
+    Replace(&'static str),
 }
 
-impl<'a> TokenIterator<'a> {
-    fn new(input: &'a str, config: &Sozlama) -> Self {
+/// A change to the source bytes of a tag.
+enum TagEdit {
+    /// Attribute value handed to the engine.
+    Convert(AttrQuote),
+    /// Fixed replacement, e.g. a relabelled language tag.
+    Replace(&'static str),
+}
+
+impl<'a, 'c> TokenIterator<'a, 'c> {
+    fn new(input: &'a str, config: &'c Sozlama, hujjat: HujjatTuri, trace: bool) -> Self {
         let mut mask = vec![false; input.len()];
-        Self::build_pre_mask(input, config, &mut mask);
+        let mut origins = trace.then(|| vec![Source::None; input.len()]);
+        let mut rewrites = Vec::new();
+        let literals = Self::build_pre_mask(
+            input,
+            config,
+            hujjat,
+            &mut mask,
+            &mut origins,
+            &mut rewrites,
+        );
+        shield_literals(input, config, &mut mask, &mut rewrites);
+        label_new(&mask, &mut origins, Source::WordList);
+        rewrites.sort_by_key(|(range, _)| range.start);
+
+        // `latinga: off` spans win over every window opened above
+        let directives = directive::scan(input, hujjat);
+        for range in &directives.shielded {
+            if let Some(slice) = mask.get_mut(range.clone()) {
+                slice.fill(true);
+            }
+        }
+        rewrites.retain(|(r, _)| {
+            !directives
+                .shielded
+                .iter()
+                .any(|d| d.start < r.end && r.start < d.end)
+        });
+        label_new(&mask, &mut origins, Source::Directive);
 
         Self {
             input,
+            config,
             cursor: 0,
             mask,
+            origins,
+            source: Source::None,
             buffer: VecDeque::new(),
+            span: None,
+            raw_text_end: 0,
+            universal: config.qalqon_yoqilgan(hujjat, Qalqon::Umumiy),
+            latex: config.qalqon_yoqilgan(hujjat, Qalqon::Latex),
+            html: config.qalqon_yoqilgan(hujjat, Qalqon::Html),
+            latex_langs: Languages::default(),
+            xml: hujjat == HujjatTuri::Xml,
+            langs: matches!(hujjat, HujjatTuri::Html | HujjatTuri::Xml)
+                .then(|| LangTracker::new(config.tilsizni_oegir, hujjat == HujjatTuri::Xml)),
+            literals,
+            literal_idx: 0,
+            rewrites,
+            rewrite_idx: 0,
+            marker_warnings: Vec::new(),
+            suppressions: Suppressions(directives.suppressions),
+            openings: openings(input, config),
         }
     }
 
-    fn build_pre_mask(input: &str, config: &Sozlama, mask: &mut [bool]) {
+    /// Returns the single-quoted literal windows of source files.
+    fn build_pre_mask(
+        input: &str,
+        config: &Sozlama,
+        hujjat: HujjatTuri,
+        mask: &mut [bool],
+        origins: &mut Option<Vec<Source>>,
+        rewrites: &mut Vec<(Range<usize>, Rewrite)>,
+    ) -> Vec<(Range<usize>, char)> {
+        let mut literals = Vec::new();
+        // Markdown code spans and fences are located by the CommonMark parser,
+        // which also understands tilde fences and indented code.
+        match hujjat {
+            HujjatTuri::Markdown => {
+                let body = front_matter::build_mask(input, &config.muqaddima_kalitlari, mask);
+                label_new(mask, origins, Source::FrontMatter);
+                markdown::build_mask(&input[body..], &mut mask[body..]);
+                label_new(mask, origins, Source::Markdown);
+            }
+            HujjatTuri::Kod(til) => {
+                literals = source::build_mask(input, til, config.izohlarni_oegir, mask);
+                label_new(mask, origins, Source::Code);
+            }
+            HujjatTuri::Json | HujjatTuri::Yaml | HujjatTuri::Toml => {
+                let format = match hujjat {
+                    HujjatTuri::Json => data::Format::Json,
+                    HujjatTuri::Yaml => data::Format::Yaml,
+                    _ => data::Format::Toml,
+                };
+                let encoded = data::build_mask(input, format, &config.kalit_yollari, mask);
+                rewrites.extend(encoded.into_iter().map(|range| (range, Rewrite::Unescape)));
+                label_new(mask, origins, Source::Data);
+            }
+            HujjatTuri::Tarjima(format) => {
+                literals = l10n::build_mask(input, format, mask, rewrites);
+                label_new(mask, origins, Source::Localization);
+            }
+            HujjatTuri::Po => {
+                let headers = po::build_mask(
+                    input,
+                    config.asl_matnni_oegir,
+                    config.noaniqlarni_oegir,
+                    mask,
+                );
+                rewrites.extend(
+                    headers
+                        .into_iter()
+                        .map(|(range, text)| (range, Rewrite::Replace(text))),
+                );
+                label_new(mask, origins, Source::Catalog);
+            }
+            HujjatTuri::Csv | HujjatTuri::Tsv => {
+                table::build_mask(input, hujjat, &config.ustunlar, mask);
+                label_new(mask, origins, Source::Table);
+            }
+            HujjatTuri::Ofis => {
+                office::build_mask(input, mask);
+                label_new(mask, origins, Source::Office);
+            }
+            HujjatTuri::Odf => {
+                odf::build_mask(input, mask);
+                label_new(mask, origins, Source::Office);
+            }
+            HujjatTuri::Epub => {
+                epub::build_mask(input, mask);
+                label_new(mask, origins, Source::Office);
+            }
+            HujjatTuri::Fb2 => {
+                fb2::build_mask(input, mask);
+                label_new(mask, origins, Source::Office);
+            }
+            HujjatTuri::Rtf => {
+                rtf::build_mask(input, mask);
+                label_new(mask, origins, Source::Office);
+            }
+            HujjatTuri::Sql => {
+                sql::build_mask(input, &config.jadvallar, &config.ustunlar, mask);
+                label_new(mask, origins, Source::Code);
+            }
+            HujjatTuri::Subtitr => {
+                subtitle::build_mask(input, mask);
+                label_new(mask, origins, Source::Subtitle);
+            }
+            HujjatTuri::Umumiy
+            | HujjatTuri::Matn
+            | HujjatTuri::Ijtimoiy
+            | HujjatTuri::Html
+            | HujjatTuri::Xml
+            | HujjatTuri::Latex => {}
+        }
+
         let token_regexes = [
-            &*RE_CODE_BLOCK,
-            &*RE_URL,
-            &*RE_EMAIL,
-            &*RE_ROMAN,
-            &*RE_HTML_ENTITY,
-            &*RE_KEY_VALUE,
+            (Qalqon::KodBloki, Source::CodeBlock, &*RE_CODE_BLOCK),
+            (Qalqon::Url, Source::Url, &*RE_URL),
+            (Qalqon::Email, Source::Email, &*RE_EMAIL),
+            (Qalqon::HtmlBelgisi, Source::Entity, &*RE_HTML_ENTITY),
+            (Qalqon::KalitQiymat, Source::KeyValue, &*RE_KEY_VALUE),
         ];
 
-        for re in token_regexes {
+        for (_, source, re) in token_regexes
+            .into_iter()
+            .filter(|&(qalqon, _, _)| config.qalqon_yoqilgan(hujjat, qalqon))
+        {
             for m in re.find_iter(input) {
                 if let Some(slice) = mask.get_mut(m.start()..m.end()) {
                     slice.fill(true);
                 }
             }
+            label_new(mask, origins, source);
         }
 
-        for re in &config.lughat.qalqons_re {
+        if config.qalqon_yoqilgan(hujjat, Qalqon::RimRaqami) {
+            for cap in RE_ROMAN.captures_iter(input) {
+                let numeral = cap.iter().skip(1).flatten().next().unwrap();
+                mask[numeral.range()].fill(true);
+            }
+            label_new(mask, origins, Source::Roman);
+        }
+
+        if config.qalqon_yoqilgan(hujjat, Qalqon::Ijtimoiy) {
+            for (source, re) in social::patterns(config.heshteglarni_oegir) {
+                for cap in re.captures_iter(input) {
+                    let range = cap.get(1).unwrap_or_else(|| cap.get(0).unwrap()).range();
+                    mask[range].fill(true);
+                }
+                label_new(mask, origins, source);
+            }
+        }
+
+        for (qalqon, source) in technical::KINDS {
+            if !config.qalqon_yoqilgan(hujjat, qalqon) {
+                continue;
+            }
+            for range in technical::find(qalqon, input) {
+                mask[range].fill(true);
+            }
+            label_new(mask, origins, source);
+        }
+
+        for (i, re) in config.lughat.qalqons_re.iter().enumerate() {
             for cap in re.captures_iter(input) {
                 let range = if let Some(inner) = cap.get(1) {
                     inner.range()
@@ -96,27 +463,15 @@ impl<'a> TokenIterator<'a> {
                     slice.fill(true);
                 }
             }
+            label_new(mask, origins, Source::Pattern(i + 1));
         }
 
-        if let Some(ref ac) = config.lughat.qalqons_ac {
-            for mat in ac.find_iter(input) {
-                let start = mat.start();
-                let end = mat.end();
-                let valid_start = start == 0 || !is_word_char(input, start - 1);
-                let valid_end = end == input.len() || !is_word_char(input, end);
-
-                if valid_start && valid_end {
-                    if let Some(slice) = mask.get_mut(start..end) {
-                        slice.fill(true);
-                    }
-                }
-            }
-        }
+        literals
     }
 
     // --- CORE ITERATION LOGIC ---
 
-    fn scan_next(&mut self) -> Option<Chunk<'a>> {
+    fn scan_next(&mut self) -> Option<(Chunk<'a>, Option<Range<usize>>)> {
         let len = self.input.len();
         if self.cursor >= len {
             return None;
@@ -126,137 +481,337 @@ impl<'a> TokenIterator<'a> {
 
         // 1. Check Pre-Mask
         if self.mask[self.cursor] {
-            while self.cursor < len && self.mask[self.cursor] {
+            while self
+                .rewrites
+                .get(self.rewrite_idx)
+                .is_some_and(|(r, _)| r.start < start)
+            {
+                self.rewrite_idx += 1;
+            }
+            if let Some((range, rewrite)) = self
+                .rewrites
+                .get(self.rewrite_idx)
+                .filter(|(r, _)| r.start == start)
+            {
+                let range = range.clone();
+                let text = &self.input[range.clone()];
+                self.cursor = range.end;
+                self.rewrite_idx += 1;
+                return Some(match *rewrite {
+                    Rewrite::Recase(idx) => {
+                        self.source = Source::WordList;
+                        (Chunk::Recased(text, idx), Some(range))
+                    }
+                    Rewrite::Unescape => {
+                        self.source = Source::None;
+                        (Chunk::Escaped(text), Some(range))
+                    }
+                    Rewrite::Replace(text) => {
+                        self.source = Source::Catalog;
+                        (Chunk::Shielded(text), None)
+                    }
+                });
+            }
+            let stop = self
+                .rewrites
+                .get(self.rewrite_idx)
+                .map_or(len, |(r, _)| r.start);
+            while self.cursor < stop && self.mask[self.cursor] {
                 self.cursor += 1;
             }
-            return Some(Chunk::Shielded(&self.input[start..self.cursor]));
+            self.source = Source::None;
+            return Some((
+                Chunk::Shielded(&self.input[start..self.cursor]),
+                Some(start..self.cursor),
+            ));
         }
 
         let c = self.peek(0)?;
 
         // Universal Shield {] ... [}
-        if c == '{' && self.peek(1) == Some(']') {
-            if let Some(end_idx) = self.scan_universal_shield() {
-                // inner_start skips the opening "{]"
-                let inner_start = start + 2;
-                // inner_end stops before the closing "[}"
-                // end_idx is the position AFTER the closing "}", so we subtract 2
-                let inner_end = end_idx.saturating_sub(2).max(inner_start);
-
-                self.cursor = end_idx;
-
-                if inner_start >= inner_end {
-                    // Empty shield case {][}, just recurse
-                    return self.scan_next();
+        if self.universal {
+            if let Some(idx) =
+                universal::opener_at(self.input, start, &self.config.qalqon_belgilari)
+            {
+                if let Some(region) =
+                    universal::region(self.input, start, idx, &self.config.qalqon_belgilari)
+                {
+                    self.cursor = region.end;
+                    self.source = Source::Universal;
+                    self.emit_universal_shield(start, region);
+                    return self.buffer.pop_front().or_else(|| self.scan_next());
                 }
-                return Some(Chunk::Shielded(&self.input[inner_start..inner_end]));
+                let len = self.config.qalqon_belgilari[idx].0.len();
+                self.marker_warnings.push((start..start + len, true));
+            } else if let Some(len) =
+                universal::closer_at(self.input, start, &self.config.qalqon_belgilari)
+            {
+                self.marker_warnings.push((start..start + len, false));
             }
         }
 
         // LaTeX Shield
-        if matches!(c, '\\' | '%' | '$') {
+        if self.latex && matches!(c, '\\' | '%' | '$') {
             if let Some(end_idx) = self.scan_latex() {
                 let chunk = Chunk::Shielded(&self.input[start..end_idx]);
                 self.cursor = end_idx;
-                return Some(chunk);
+                self.source = Source::Latex;
+                return Some((chunk, Some(start..end_idx)));
             }
         }
 
         // HTML Smart Shield
-        if c == '<' {
-            if let Some((end_idx, is_opaque)) = self.scan_html() {
-                if is_opaque {
-                    let chunk = Chunk::Shielded(&self.input[start..end_idx]);
-                    self.cursor = end_idx;
-                    return Some(chunk);
-                } else {
-                    self.emit_smart_tag(start, end_idx);
+        if self.html && c == '<' && self.cursor >= self.raw_text_end {
+            match html::tokenize(self.input, start) {
+                Markup::Text => {}
+                Markup::Opaque(end_idx) => {
                     self.cursor = end_idx;
+                    self.source = Source::Html;
+                    return Some((
+                        Chunk::Shielded(&self.input[start..end_idx]),
+                        Some(start..end_idx),
+                    ));
+                }
+                Markup::Tag(tag) => {
+                    if !tag.is_end && self.is_protected_tag(&tag.name) {
+                        let end_idx = html::find_end_tag(self.input, tag.end, &tag.name)
+                            .map_or(tag.end, |(_, end)| end);
+                        self.cursor = end_idx;
+                        self.source = Source::ProtectedTag;
+                        self.emit_protected_tag(start, &tag, end_idx);
+                        return self.buffer.pop_front();
+                    }
+                    if !self.xml && tag.has_raw_text() {
+                        self.raw_text_end = html::find_end_tag(self.input, tag.end, &tag.name)
+                            .map_or(len, |(close_start, _)| close_start);
+                    }
+                    self.emit_smart_tag(start, &tag);
+                    self.cursor = tag.end;
+                    self.source = Source::Html;
                     return self.buffer.pop_front();
                 }
             }
         }
 
         // 3. Neutral State (Consume 'Safe' chars)
+        // The byte at `start` was rejected by every scanner above, so it is text.
         let bytes = self.input.as_bytes();
+        self.cursor += 1;
         while self.cursor < len {
             if self.mask[self.cursor] {
                 break;
             }
             let curr = bytes[self.cursor] as char;
-            if matches!(curr, '\\' | '%' | '$' | '<') {
+            if self.html && curr == '<' && self.cursor >= self.raw_text_end {
+                break;
+            }
+            if self.latex && matches!(curr, '\\' | '%' | '$') {
                 break;
             }
-            if curr == '{' && self.peek(1) == Some(']') {
+            if self.universal && self.at_marker(self.cursor) {
                 break;
             }
             self.cursor += 1;
         }
 
-        if self.cursor > start {
-            Some(Chunk::Safe(&self.input[start..self.cursor]))
-        } else {
-            if self.cursor < len {
-                self.cursor += 1;
-                Some(Chunk::Safe(&self.input[start..self.cursor]))
-            } else {
-                None
-            }
+        let text = &self.input[start..self.cursor];
+        let foreign = match &self.langs {
+            Some(langs) => !langs.converting(),
+            None => self.latex && !self.latex_langs.converting(start),
+        };
+        if foreign {
+            self.source = Source::Lang;
+            return Some((Chunk::Shielded(text), Some(start..self.cursor)));
         }
+        self.source = Source::None;
+        if let Some(quote) = self.literal_quote(start) {
+            return Some((Chunk::Literal(text, quote), Some(start..self.cursor)));
+        }
+        Some((Chunk::Safe(text), Some(start..self.cursor)))
     }
 
-    fn emit_smart_tag(&mut self, start: usize, end: usize) {
-        let tag_content = &self.input[start..end];
-        let mut last_idx = 0;
+    /// Splits a tag into shielded markup and convertible attribute values.
+    fn emit_smart_tag(&mut self, start: usize, tag: &Tag) {
+        let convert = self.track_lang(tag);
+        let mut edits: Vec<(Range<usize>, TagEdit)> = Vec::new();
 
-        for cap in RE_ATTR_SCAN.captures_iter(tag_content) {
-            let name = cap.get(1).unwrap();
-            let val_match = cap.get(2).unwrap();
-            let attr_name = name.as_str().to_lowercase();
+        if !tag.is_end && convert {
+            for attr in &tag.attrs {
+                let Some((value, quote)) = &attr.value else {
+                    continue;
+                };
+                let name = &self.input[attr.name.clone()];
 
-            if crate::symbols::TRANSLITERABLE_ATTRIBUTES.contains(&attr_name.as_str()) {
-                let full_val_str = val_match.as_str();
+                let labels_language =
+                    is_lang_attribute(name) || is_content_language(self.input, tag, name);
+                if self.langs.is_some() && labels_language {
+                    edits.extend(self.latin_edits(value));
+                } else if self.is_convertible_attribute(&tag.name, name) {
+                    edits.push((value.clone(), TagEdit::Convert(*quote)));
+                }
+            }
+        }
 
-                let (quote_len, _) = if full_val_str.starts_with(['"', '\'']) {
-                    (1, 1)
-                } else {
-                    (0, 0)
-                };
+        let mut last_idx = start;
+        for (range, edit) in edits {
+            if range.start > last_idx {
+                self.push_shielded(last_idx..range.start);
+            }
+            match edit {
+                TagEdit::Replace(text) => self.buffer.push_back((Chunk::Shielded(text), None)),
+                TagEdit::Convert(quote) => self.emit_attribute_value(range.clone(), quote),
+            }
+            last_idx = range.end;
+        }
 
-                let abs_val_start = start + val_match.start() + quote_len;
-                let abs_val_end = start + val_match.end() - quote_len;
-                let relative_val_start = val_match.start() + quote_len;
+        if last_idx < tag.end {
+            self.push_shielded(last_idx..tag.end);
+        }
+    }
 
-                if relative_val_start > last_idx {
-                    self.buffer
-                        .push_back(Chunk::Shielded(&tag_content[last_idx..relative_val_start]));
+    /// Shields a protected element up to `end`, except for the attribute
+    /// values picked by `ochiq_atributlar` selectors.
+    fn emit_protected_tag(&mut self, start: usize, tag: &Tag, end: usize) {
+        let mut last_idx = start;
+        if !tag.is_end {
+            for attr in &tag.attrs {
+                let Some((value, quote)) = &attr.value else {
+                    continue;
+                };
+                if !self.is_selected_attribute(&tag.name, &self.input[attr.name.clone()]) {
+                    continue;
                 }
+                self.push_shielded(last_idx..value.start);
+                self.emit_attribute_value(value.clone(), *quote);
+                last_idx = value.end;
+            }
+        }
+        if last_idx < end {
+            self.push_shielded(last_idx..end);
+        }
+    }
 
-                let mut v_curr = abs_val_start;
-                while v_curr < abs_val_end {
-                    let v_chunk_start = v_curr;
-                    if self.mask[v_curr] {
-                        while v_curr < abs_val_end && self.mask[v_curr] {
-                            v_curr += 1;
-                        }
-                        self.buffer
-                            .push_back(Chunk::Shielded(&self.input[v_chunk_start..v_curr]));
-                    } else {
-                        while v_curr < abs_val_end && !self.mask[v_curr] {
-                            v_curr += 1;
-                        }
-                        self.buffer
-                            .push_back(Chunk::Safe(&self.input[v_chunk_start..v_curr]));
-                    }
-                }
-                last_idx = val_match.end() - quote_len;
+    /// Splits the shielded input `range` around the `ochiq_qoliplar` windows that
+    /// overlap it. Windows inside a tag's quotes become attribute values.
+    fn carve(&mut self, range: Range<usize>) -> Chunk<'a> {
+        let Range { start, end } = range;
+        let first = self.openings.partition_point(|w| w.end <= start);
+
+        let mut pieces = Vec::new();
+        let mut last = start;
+        for window in self.openings[first..].iter().take_while(|w| w.start < end) {
+            let (from, to) = (window.start.max(start), window.end.min(end));
+            if from > last {
+                pieces.push((Chunk::Shielded(&self.input[last..from]), last..from));
+            }
+            let quote = match (self.source, self.input.as_bytes().get(from.wrapping_sub(1))) {
+                (Source::Html, Some(b'"')) => Some(AttrQuote::Double),
+                (Source::Html, Some(b'\'')) => Some(AttrQuote::Single),
+                _ => None,
+            };
+            let window_text = &self.input[from..to];
+            let chunk = match quote {
+                Some(quote) => Chunk::Attribute(window_text, quote),
+                None => Chunk::Safe(window_text),
+            };
+            pieces.push((chunk, from..to));
+            last = to;
+        }
+        if pieces.is_empty() {
+            return Chunk::Shielded(&self.input[start..end]);
+        }
+        if last < end {
+            pieces.push((Chunk::Shielded(&self.input[last..end]), last..end));
+        }
+        for (chunk, range) in pieces.drain(1..).rev() {
+            self.buffer.push_front((chunk, Some(range)));
+        }
+        let (chunk, range) = pieces.remove(0);
+        self.span = Some(range);
+        chunk
+    }
+
+    /// Queues the shielded input `range`.
+    fn push_shielded(&mut self, range: Range<usize>) {
+        let text = &self.input[range.clone()];
+        self.buffer.push_back((Chunk::Shielded(text), Some(range)));
+    }
+
+    /// Splits an attribute value by the pre-mask.
+    fn emit_attribute_value(&mut self, value: Range<usize>, quote: AttrQuote) {
+        let mut v_curr = value.start;
+        while v_curr < value.end {
+            let v_chunk_start = v_curr;
+            let shielded = self.mask[v_curr];
+            while v_curr < value.end && self.mask[v_curr] == shielded {
+                v_curr += 1;
             }
+            let text = &self.input[v_chunk_start..v_curr];
+            let chunk = if shielded {
+                Chunk::Shielded(text)
+            } else {
+                Chunk::Attribute(text, quote)
+            };
+            self.buffer.push_back((chunk, Some(v_chunk_start..v_curr)));
         }
+    }
+
+    /// Updates the `lang` scopes and returns whether the tag's own
+    /// attributes are converted.
+    fn track_lang(&mut self, tag: &Tag) -> bool {
+        let Some(langs) = self.langs.as_mut() else {
+            return true;
+        };
+        if tag.is_end {
+            langs.leave(&tag.name);
+            return false;
+        }
+        let lang = tag.attr_value(self.input, LANG_ATTRIBUTES);
+        langs.enter(&tag.name, lang, tag.self_closing)
+    }
+
+    fn latin_edits(&self, value: &Range<usize>) -> impl Iterator<Item = (Range<usize>, TagEdit)> {
+        lang::latin_edits(&self.input[value.clone()], value.start)
+            .into_iter()
+            .map(|(range, text)| (range, TagEdit::Replace(text)))
+    }
 
-        if last_idx < tag_content.len() {
-            self.buffer
-                .push_back(Chunk::Shielded(&tag_content[last_idx..]));
+    /// Quote of the single-quoted literal containing `pos`, if any.
+    fn literal_quote(&mut self, pos: usize) -> Option<char> {
+        while self
+            .literals
+            .get(self.literal_idx)
+            .is_some_and(|(range, _)| range.end <= pos)
+        {
+            self.literal_idx += 1;
         }
+        self.literals
+            .get(self.literal_idx)
+            .filter(|(range, _)| range.contains(&pos))
+            .map(|&(_, quote)| quote)
+    }
+
+    fn is_protected_tag(&self, name: &str) -> bool {
+        self.config
+            .himoyali_tamghalar
+            .iter()
+            .any(|t| t.eq_ignore_ascii_case(name))
+    }
+
+    fn is_convertible_attribute(&self, tag: &str, name: &str) -> bool {
+        self.config
+            .atributlar
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(name))
+            || self.is_selected_attribute(tag, name)
+    }
+
+    /// Whether a `tag[attr]` selector picks this attribute.
+    fn is_selected_attribute(&self, tag: &str, name: &str) -> bool {
+        self.config
+            .ochiq_atributlar
+            .iter()
+            .any(|(t, a)| t.as_deref().is_none_or(|t| t == tag) && a.eq_ignore_ascii_case(name))
     }
 
     // --- HELPER SCANNERS ---
@@ -268,38 +823,104 @@ impl<'a> TokenIterator<'a> {
             .map(|&b| b as char)
     }
 
-    fn scan_universal_shield(&self) -> Option<usize> {
-        // Find closing "[}"
-        // The return value must be the index AFTER the closing brace
-        self.input[self.cursor..]
-            .find("[}")
-            .map(|idx| self.cursor + idx + 2)
+    fn at_marker(&self, pos: usize) -> bool {
+        let pairs = &self.config.qalqon_belgilari;
+        universal::opener_at(self.input, pos, pairs).is_some()
+            || universal::closer_at(self.input, pos, pairs).is_some()
     }
 
-    fn scan_html(&self) -> Option<(usize, bool)> {
-        let rest = &self.input[self.cursor..];
-        let tag_end = rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+    /// Queues a universal shield region, with or without its markers.
+    fn emit_universal_shield(&mut self, start: usize, region: universal::Region) {
+        if self.config.belgilarni_saqla {
+            self.push_shielded(start..region.end);
+            return;
+        }
+        let mut pos = start;
+        for marker in region.markers {
+            if pos < marker.start {
+                self.push_shielded(pos..marker.start);
+            }
+            pos = marker.end;
+        }
+    }
 
-        let tag_name = &rest[1..tag_end];
-        let tag_lower = tag_name.to_lowercase();
+    /// Splits a chunk returned by `next` into runs labelled with the shield
+    /// that produced them. Convertible chunks are a single `Source::None` run.
+    pub(crate) fn labels(&self, chunk: &Chunk<'a>) -> Vec<(&'a str, Source)> {
+        let text = match *chunk {
+            Chunk::Shielded(text) => text,
+            Chunk::Recased(text, _) => return vec![(text, Source::WordList)],
+            Chunk::Safe(text)
+            | Chunk::Attribute(text, _)
+            | Chunk::Literal(text, _)
+            | Chunk::Escaped(text) => {
+                return vec![(text, Source::None)];
+            }
+        };
+        let (Some(origins), Some(span)) = (self.origins.as_ref(), self.span.as_ref()) else {
+            // Fixed replacements (`uz-Latn`) have no input range
+            return vec![(text, self.source)];
+        };
+        let start = span.start;
 
-        // 1. Fully Protected Tags (script, style, etc.)
-        if crate::symbols::FULLY_PROTECTED_TAGS.contains(&tag_lower.as_str()) {
-            let close_target = format!("</{}>", tag_lower);
-            if let Some(close_pos) = find_case_insensitive(rest, &close_target) {
-                return Some((self.cursor + close_pos + close_target.len(), true));
+        let label = |i: usize| match origins[i] {
+            Source::None => self.source,
+            origin => origin,
+        };
+        let mut runs = Vec::new();
+        let mut run_start = 0;
+        for (i, _) in text.char_indices().skip(1) {
+            if label(start + i) != label(start + run_start) {
+                runs.push((&text[run_start..i], label(start + run_start)));
+                run_start = i;
             }
         }
+        runs.push((&text[run_start..], label(start + run_start)));
+        runs
+    }
 
-        // 2. Standard Tag
-        if let Some(end_idx) = rest.find('>') {
-            return Some((self.cursor + end_idx + 1, false));
-        }
-        None
+    /// Input range of the chunk last returned by `next`, `None` for fixed
+    /// replacements.
+    pub(crate) fn span(&self) -> Option<Range<usize>> {
+        self.span.clone()
+    }
+
+    /// Validator rules turned off by inline directives.
+    pub(crate) fn suppressions(&self) -> Suppressions {
+        self.suppressions.clone()
     }
 
-    fn scan_latex(&self) -> Option<usize> {
+    /// Unbalanced universal shield markers found so far, with their
+    /// line and column.
+    pub(crate) fn marker_warnings(&self) -> Vec<TekshiruvHatosi<'a>> {
+        self.marker_warnings
+            .iter()
+            .filter_map(|(range, opener)| {
+                let before = &self.input[..range.start];
+                let line = before.matches('\n').count() + 1;
+                if self.suppressions.contains(line, Rule::Marker) {
+                    return None;
+                }
+                let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+                let marker = self.input[range.clone()].to_string();
+                let key = if *opener {
+                    HabarKaliti::YopilmaganQalqon(marker)
+                } else {
+                    HabarKaliti::OchilmaganQalqon(marker)
+                };
+                Some(TekshiruvHatosi {
+                    qator: line,
+                    ustun: before[line_start..].chars().count() + 1,
+                    soez: Cow::Borrowed(&self.input[range.clone()]),
+                    habar: key.koersat(&self.config.tartib),
+                })
+            })
+            .collect()
+    }
+
+    fn scan_latex(&mut self) -> Option<usize> {
         let c = self.peek(0)?;
+        let start = self.cursor;
 
         if c == '%' {
             return self.input[self.cursor..]
@@ -309,43 +930,133 @@ impl<'a> TokenIterator<'a> {
         }
 
         if c == '$' {
-            let is_double = self.peek(1) == Some('$');
-            let offset = if is_double { 2 } else { 1 };
-            let pattern = if is_double { "$$" } else { "$" };
-            return self.input[self.cursor + offset..]
-                .find(pattern)
-                .map(|idx| self.cursor + offset + idx + pattern.len());
+            let closer = if self.peek(1) == Some('$') { "$$" } else { "$" };
+            return self.shield_math(start, start + closer.len(), closer);
         }
 
         if c == '\\' {
-            let bytes = self.input.as_bytes();
-            let mut i = 1;
-            while self.cursor + i < bytes.len() && (bytes[self.cursor + i] as char).is_alphabetic()
-            {
-                i += 1;
+            let name_end = latex::command_end(self.input, start + 1);
+            let cmd_name = &self.input[start + 1..name_end];
+
+            if cmd_name.is_empty() {
+                return match self.peek(1) {
+                    Some('(') => self.shield_math(start, start + 2, "\\)"),
+                    Some('[') => self.shield_math(start, start + 2, "\\]"),
+                    Some('\\') => Some(start + 2),
+                    _ => None,
+                }
+                .or(Some(start + 1));
             }
-            let cmd_name = &self.input[self.cursor + 1..self.cursor + i];
 
             if cmd_name == "begin" {
-                if let Some(env_name) = self.extract_braced_content(self.cursor + i) {
+                if let Some(env_name) = self.extract_braced_content(name_end) {
                     if LATEX_VERBATIM_ENVIRONMENTS.contains(&env_name) {
                         let closer = format!("\\end{{{}}}", env_name);
                         if let Some(close_pos) = self.input[self.cursor..].find(&closer) {
                             return Some(self.cursor + close_pos + closer.len());
                         }
                     }
+                    if latex::is_math_environment(env_name) {
+                        let closer = format!("\\end{{{}}}", env_name);
+                        let body = self.input[name_end..].find('}').map(|i| name_end + i + 1)?;
+                        if let Some(end_idx) = self.shield_math(start, body, &closer) {
+                            return Some(end_idx);
+                        }
+                    }
                 }
             }
 
-            if LATEX_STRUCTURAL_COMMANDS.contains(&cmd_name) {
-                return self.scan_balanced_braces(self.cursor + i);
-            }
+            let Some(spec) = self.config.latex_buyruqlari.get(cmd_name) else {
+                return Some(name_end);
+            };
+            let args = latex::parse_args(self.input, name_end, spec);
+            self.track_latex_language(cmd_name, &args);
 
-            return Some(self.cursor + i);
+            for arg in args.iter().filter(|arg| !arg.converted()) {
+                self.shield_latex(arg.outer.clone());
+            }
+            return Some(name_end);
         }
         None
     }
 
+    /// Masks a span found by the LaTeX scanner.
+    fn shield_latex(&mut self, range: Range<usize>) {
+        if let Some(origins) = &mut self.origins {
+            for (origin, &masked) in origins[range.clone()]
+                .iter_mut()
+                .zip(&self.mask[range.clone()])
+            {
+                if !masked {
+                    *origin = Source::Latex;
+                }
+            }
+        }
+        self.mask[range].fill(true);
+    }
+
+    /// Shields a math region from `start`, leaving the converted arguments
+    /// of text commands (`\text{...}`) open. Returns the region end.
+    fn shield_math(&mut self, start: usize, body: usize, closer: &str) -> Option<usize> {
+        let (end, windows) = latex::math(self.input, body, closer, &self.config.latex_buyruqlari)?;
+
+        let mut last = start;
+        for window in windows {
+            self.shield_latex(last..window.start);
+            last = window.end;
+        }
+        self.shield_latex(last..end);
+
+        // The region now starts with a shielded run
+        let mut run_end = start;
+        while run_end < end && self.mask[run_end] {
+            run_end += 1;
+        }
+        Some(run_end)
+    }
+
+    /// Follows babel and polyglossia language switches.
+    fn track_latex_language(&mut self, cmd_name: &str, args: &[ParsedArg]) {
+        let input = self.input;
+        let langs = &mut self.latex_langs;
+        match cmd_name {
+            "usepackage" => {
+                let loads_babel = latex::required(input, args, 0)
+                    .is_some_and(|pkgs| pkgs.split(',').any(|p| p.trim() == "babel"));
+                if loads_babel && let Some(options) = latex::optional(input, args) {
+                    langs.babel(options);
+                }
+            }
+            "selectlanguage" | "setmainlanguage" | "setdefaultlanguage" => {
+                if let Some(name) = latex::required(input, args, 0) {
+                    langs.select(name);
+                }
+            }
+            "foreignlanguage" => {
+                if let (Some(name), Some(text)) = (latex::required(input, args, 0), args.last()) {
+                    langs.scope(text.outer.end, name);
+                    // The closing brace ends the text chunk at the scope boundary
+                    self.shield_latex(text.outer.end - 1..text.outer.end);
+                }
+            }
+            "begin" => {
+                if let (Some(env), Some(name)) = (
+                    latex::required(input, args, 0),
+                    latex::required(input, args, 1),
+                ) && latex::is_language_environment(env)
+                {
+                    langs.begin(name);
+                }
+            }
+            "end"
+                if latex::required(input, args, 0).is_some_and(latex::is_language_environment) =>
+            {
+                langs.end();
+            }
+            _ => {}
+        }
+    }
+
     fn extract_braced_content(&self, start_search: usize) -> Option<&'a str> {
         let bytes = self.input.as_bytes();
         let mut curr = start_search;
@@ -372,85 +1083,113 @@ impl<'a> TokenIterator<'a> {
         }
         None
     }
+}
 
-    fn scan_balanced_braces(&self, start: usize) -> Option<usize> {
-        let bytes = self.input.as_bytes();
-        let mut curr = start;
+impl<'a> Iterator for TokenIterator<'a, '_> {
+    type Item = Chunk<'a>;
 
-        loop {
-            while curr < bytes.len() && (bytes[curr] as char).is_whitespace() {
-                curr += 1;
-            }
-            if curr >= bytes.len() {
-                break;
+    fn next(&mut self) -> Option<Self::Item> {
+        let (chunk, range) = match self.buffer.pop_front() {
+            Some(item) => item,
+            None => self.scan_next()?,
+        };
+        self.span.clone_from(&range);
+        match (chunk, range) {
+            (Chunk::Shielded(_), Some(range)) if !self.openings.is_empty() => {
+                Some(self.carve(range))
             }
+            (chunk, _) => Some(chunk),
+        }
+    }
+}
 
-            let next_char = bytes[curr] as char;
-            if next_char == '{' || next_char == '[' {
-                let closer = if next_char == '{' { '}' } else { ']' };
-                let mut depth = 1;
-                let mut j = 1;
+// --- UTILS ---
 
-                while curr + j < bytes.len() && depth > 0 {
-                    let cc = bytes[curr + j] as char;
-                    if cc == next_char {
-                        depth += 1;
-                    } else if cc == closer {
-                        depth -= 1;
-                    }
-                    j += 1;
-                }
-                curr += j;
-            } else {
-                break;
-            }
+/// Windows of the inverse shield patterns, merged and sorted.
+fn openings(input: &str, config: &Sozlama) -> Vec<Range<usize>> {
+    let mut windows: Vec<Range<usize>> = config
+        .ochiq_qoliplar
+        .iter()
+        .flat_map(|re| re.captures_iter(input))
+        .map(|cap| cap.get(1).unwrap_or_else(|| cap.get(0).unwrap()).range())
+        .filter(|w| !w.is_empty())
+        .collect();
+    windows.sort_by_key(|w| w.start);
+
+    let mut merged: Vec<Range<usize>> = Vec::with_capacity(windows.len());
+    for window in windows {
+        match merged.last_mut() {
+            Some(last) if window.start <= last.end => last.end = last.end.max(window.end),
+            _ => merged.push(window),
         }
-        Some(curr)
     }
+    merged
 }
 
-impl<'a> Iterator for TokenIterator<'a> {
-    type Item = Chunk<'a>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if let Some(chunk) = self.buffer.pop_front() {
-            return Some(chunk);
+/// Labels the masked bytes that no earlier stage has claimed.
+fn label_new(mask: &[bool], origins: &mut Option<Vec<Source>>, source: Source) {
+    let Some(origins) = origins else {
+        return;
+    };
+    for (origin, &masked) in origins.iter_mut().zip(mask) {
+        if masked && *origin == Source::None {
+            *origin = source;
         }
-        self.scan_next()
     }
 }
 
-// --- UTILS ---
+/// Attributes carrying an element's language, in order of precedence.
+const LANG_ATTRIBUTES: &[&str] = &["lang", "xml:lang"];
 
-fn is_word_char(text: &str, idx: usize) -> bool {
-    text.as_bytes().get(idx).map_or(false, |&b| {
-        let c = b as char;
-        c.is_alphanumeric() || c == '_'
-    })
+fn is_lang_attribute(name: &str) -> bool {
+    LANG_ATTRIBUTES.iter().any(|a| a.eq_ignore_ascii_case(name))
 }
 
-fn find_case_insensitive(haystack: &str, needle: &str) -> Option<usize> {
-    let haystack_bytes = haystack.as_bytes();
-    let needle_bytes = needle.as_bytes();
-    let needle_len = needle_bytes.len();
+/// `content` of `<meta http-equiv="content-language">`.
+fn is_content_language(input: &str, tag: &Tag, name: &str) -> bool {
+    tag.name == "meta"
+        && name.eq_ignore_ascii_case("content")
+        && tag
+            .attr_value(input, &["http-equiv"])
+            .is_some_and(|v| v.trim().eq_ignore_ascii_case("content-language"))
+}
 
-    if haystack_bytes.len() < needle_len {
-        return None;
+/// Marks the shield file literals and records the `shakl` matches.
+fn shield_literals(
+    input: &str,
+    config: &Sozlama,
+    mask: &mut [bool],
+    rewrites: &mut Vec<(Range<usize>, Rewrite)>,
+) {
+    for (re, flags) in &config.lughat.qalqons_lit {
+        for m in re.find_iter(input) {
+            let whole_word = !word_char_before(input, m.start()) && !word_char_at(input, m.end());
+            if !flags.substring && !whole_word {
+                continue;
+            }
+            // Text already shielded by another stage is left as written
+            let fresh = !mask[m.range()].contains(&true);
+            mask[m.range()].fill(true);
+            if fresh
+                && flags.recase
+                && let Some(idx) = config.lughat.recased_literal(m.as_str())
+            {
+                rewrites.push((m.range(), Rewrite::Recase(idx)));
+            }
+        }
     }
+}
 
-    // Use char_indices ONLY to ensure we start matching at a valid character boundary.
-    // However, we perform the match on BYTES to avoid panicking if the match
-    // extends into the middle of a multibyte character.
-    for (i, _) in haystack.char_indices() {
-        if i + needle_len > haystack_bytes.len() {
-            break;
-        }
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
 
-        // This slice is safe because we are slicing a &[u8], not a &str.
-        // It doesn't care about UTF-8 boundaries.
-        if haystack_bytes[i..i + needle_len].eq_ignore_ascii_case(needle_bytes) {
-            return Some(i);
-        }
-    }
-    None
+/// Whether the character ending just before byte `pos` is a word character.
+fn word_char_before(text: &str, pos: usize) -> bool {
+    text[..pos].chars().next_back().is_some_and(is_word_char)
+}
+
+/// Whether the character starting at byte `pos` is a word character.
+fn word_char_at(text: &str, pos: usize) -> bool {
+    text[pos..].chars().next().is_some_and(is_word_char)
 }
@@ -0,0 +1,659 @@
+use super::source::{RE_ESCAPE, RE_PLACEHOLDER};
+use crate::types::KeyPattern;
+use regex::Regex;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Template placeholders of i18n libraries: `{{name}}` and `${name}`.
+static RE_TEMPLATE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{[^{}]*\}\}|\$\{[^{}]*\}").unwrap());
+/// Consecutive `\uXXXX` / `\UXXXXXXXX` escapes.
+static RE_UNICODE_ESCAPES: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:\\u[0-9a-fA-F]{4}|\\U[0-9a-fA-F]{8})+").unwrap());
+
+/// Structured data formats converted value by value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// A string value and the key path leading to it. Array items are
+/// addressed by their index.
+pub(super) struct Value {
+    pub range: Range<usize>,
+    pub path: Vec<String>,
+    /// Whether backslash escapes are recognised inside the value.
+    pub escapes: bool,
+}
+
+/// Shields a data document except for its string values, or only the values
+/// whose key path matches `keys` when any are given. Escapes and
+/// placeholders stay shielded; runs of `\uXXXX` escapes are returned so
+/// they can be decoded, converted and escaped again.
+pub(super) fn build_mask(
+    input: &str,
+    format: Format,
+    keys: &[KeyPattern],
+    mask: &mut [bool],
+) -> Vec<Range<usize>> {
+    mask.fill(true);
+    let mut encoded = Vec::new();
+
+    let content = 0..input.len();
+    let values = match format {
+        Format::Json => JsonScanner::new(input).values(),
+        Format::Yaml => yaml_values(input, content),
+        Format::Toml => TomlScanner::new(input).values(content),
+    };
+    for value in values {
+        if value.range.is_empty()
+            || !keys.is_empty() && !keys.iter().any(|k| k.matches(&value.path))
+        {
+            continue;
+        }
+        encoded.extend(open_value(
+            input,
+            value.range,
+            value.escapes,
+            &[&RE_TEMPLATE],
+            mask,
+        ));
+    }
+    encoded
+}
+
+/// Opens a string value for conversion. Placeholders, matches of
+/// `shielded` and, when `escapes` is set, backslash escapes stay shielded.
+/// Returns the runs of `\uXXXX` escapes inside the value.
+pub(super) fn open_value(
+    input: &str,
+    range: Range<usize>,
+    escapes: bool,
+    shielded: &[&Regex],
+    mask: &mut [bool],
+) -> Vec<Range<usize>> {
+    let start = range.start;
+    let text = &input[range.clone()];
+    mask[range].fill(false);
+
+    let mut regexes = vec![&*RE_PLACEHOLDER];
+    regexes.extend_from_slice(shielded);
+    if escapes {
+        regexes.push(&RE_ESCAPE);
+    }
+    for re in regexes {
+        for m in re.find_iter(text) {
+            mask[start + m.start()..start + m.end()].fill(true);
+        }
+    }
+    if !escapes {
+        return Vec::new();
+    }
+    RE_UNICODE_ESCAPES
+        .find_iter(text)
+        .map(|m| start + m.start()..start + m.end())
+        .collect()
+}
+
+/// Iterates lines (without line terminators) together with their byte offsets.
+pub(super) fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, raw| {
+        let start = *offset;
+        *offset += raw.len();
+        let line = raw.trim_end_matches('\n').trim_end_matches('\r');
+        Some((start, line))
+    })
+}
+
+pub(super) fn find_closing_quote(text: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '"' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i);
+        }
+    }
+    None
+}
+
+fn unquote(key: &str) -> String {
+    key.trim().trim_matches(['"', '\'']).to_string()
+}
+
+// --- JSON ---
+
+pub(super) struct JsonScanner<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    path: Vec<String>,
+    values: Vec<Value>,
+}
+
+impl<'a> JsonScanner<'a> {
+    pub(super) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            bytes: input.as_bytes(),
+            path: Vec::new(),
+            values: Vec::new(),
+        }
+    }
+
+    /// Scans every top-level value, so JSON Lines files work too.
+    pub(super) fn values(mut self) -> Vec<Value> {
+        let mut pos = 0;
+        while pos < self.bytes.len() {
+            let start = self.skip_space(pos);
+            if start >= self.bytes.len() {
+                break;
+            }
+            pos = self.value(start).max(start + 1);
+        }
+        self.values
+    }
+
+    /// Parses the value at `pos` and returns the position after it.
+    fn value(&mut self, pos: usize) -> usize {
+        match self.bytes.get(pos) {
+            Some(b'{') => self.object(pos + 1),
+            Some(b'[') => self.array(pos + 1),
+            Some(b'"') => {
+                let end = self.string_end(pos + 1);
+                self.values.push(Value {
+                    range: pos + 1..end,
+                    path: self.path.clone(),
+                    escapes: true,
+                });
+                (end + 1).min(self.bytes.len())
+            }
+            // Numbers, booleans and null are never converted
+            _ => {
+                let rest = &self.bytes[pos..];
+                pos + rest
+                    .iter()
+                    .position(|b| matches!(b, b',' | b']' | b'}') || b.is_ascii_whitespace())
+                    .unwrap_or(rest.len())
+            }
+        }
+    }
+
+    fn object(&mut self, mut pos: usize) -> usize {
+        loop {
+            pos = self.skip_space(pos);
+            match self.bytes.get(pos) {
+                None => return pos,
+                Some(b'}') => return pos + 1,
+                Some(b',') => pos += 1,
+                Some(b'"') => {
+                    let end = self.string_end(pos + 1);
+                    let key = self.input[pos + 1..end].to_string();
+                    pos = self.skip_space((end + 1).min(self.bytes.len()));
+                    if self.bytes.get(pos) == Some(&b':') {
+                        pos = self.skip_space(pos + 1);
+                    }
+                    self.path.push(key);
+                    pos = self.value(pos);
+                    self.path.pop();
+                }
+                Some(_) => pos = self.value(pos).max(pos + 1),
+            }
+        }
+    }
+
+    fn array(&mut self, mut pos: usize) -> usize {
+        let mut index = 0;
+        loop {
+            pos = self.skip_space(pos);
+            match self.bytes.get(pos) {
+                None => return pos,
+                Some(b']') => return pos + 1,
+                Some(b',') => {
+                    index += 1;
+                    pos += 1;
+                }
+                Some(_) => {
+                    self.path.push(index.to_string());
+                    pos = self.value(pos).max(pos + 1);
+                    self.path.pop();
+                }
+            }
+        }
+    }
+
+    /// Position of the quote closing the string whose content starts at `pos`.
+    fn string_end(&self, mut pos: usize) -> usize {
+        while let Some(&b) = self.bytes.get(pos) {
+            match b {
+                b'\\' => pos += 2,
+                b'"' => return pos,
+                _ => pos += 1,
+            }
+        }
+        self.bytes.len()
+    }
+
+    /// Skips whitespace and the `//` and `/* */` comments of JSONC files.
+    fn skip_space(&self, mut pos: usize) -> usize {
+        loop {
+            let rest = &self.bytes[pos.min(self.bytes.len())..];
+            match rest {
+                [b, ..] if b.is_ascii_whitespace() => pos += 1,
+                [b'/', b'/', ..] => {
+                    pos += rest.iter().position(|&b| b == b'\n').unwrap_or(rest.len());
+                }
+                [b'/', b'*', ..] => {
+                    pos += self.input[pos..].find("*/").map_or(rest.len(), |i| i + 2);
+                }
+                _ => return pos,
+            }
+        }
+    }
+}
+
+// --- YAML ---
+
+/// An open mapping key or sequence item.
+struct Frame {
+    indent: usize,
+    segment: String,
+    /// Index of a sequence item.
+    item: Option<usize>,
+}
+
+/// String values of a block-style YAML document. Flow mappings and
+/// multi-line plain scalars are left shielded.
+pub(super) fn yaml_values(input: &str, content: Range<usize>) -> Vec<Value> {
+    let mut values = Vec::new();
+    let mut frames: Vec<Frame> = Vec::new();
+    // Indentation of the key owning a `|` or `>` block scalar
+    let mut block: Option<usize> = None;
+    let path = |frames: &[Frame]| frames.iter().map(|f| f.segment.clone()).collect::<Vec<_>>();
+
+    for (rel, line) in lines_with_offsets(&input[content.clone()]) {
+        let offset = content.start + rel;
+        let trimmed = line.trim_start();
+        let mut indent = line.len() - trimmed.len();
+
+        if let Some(owner) = block {
+            if trimmed.is_empty() {
+                continue;
+            }
+            if indent > owner {
+                values.push(Value {
+                    range: offset + indent..offset + line.trim_end().len(),
+                    path: path(&frames),
+                    escapes: false,
+                });
+                continue;
+            }
+            block = None;
+        }
+        if trimmed.is_empty()
+            || trimmed.starts_with('#')
+            || trimmed.starts_with('%')
+            || matches!(trimmed.trim_end(), "---" | "...")
+        {
+            continue;
+        }
+
+        // `- item`, `- key: value` and `- - nested`
+        let mut entry = trimmed;
+        let mut in_item = false;
+        while let Some(rest) = entry
+            .strip_prefix('-')
+            .filter(|r| r.is_empty() || r.starts_with(' '))
+        {
+            let index = close_item(&mut frames, indent);
+            frames.push(Frame {
+                indent,
+                segment: index.to_string(),
+                item: Some(index),
+            });
+            let content = rest.trim_start();
+            indent += entry.len() - content.len();
+            entry = content;
+            in_item = true;
+        }
+        if entry.is_empty() {
+            continue;
+        }
+        let entry_offset = offset + line.len() - entry.len();
+        let line_end = offset + line.len();
+
+        let value_start = match find_yaml_colon(entry) {
+            Some(colon) => {
+                while frames.last().is_some_and(|f| f.indent >= indent) {
+                    frames.pop();
+                }
+                frames.push(Frame {
+                    indent,
+                    segment: unquote(&entry[..colon]),
+                    item: None,
+                });
+                entry_offset + colon + 1
+            }
+            None if in_item => entry_offset,
+            // Continuation of a multi-line scalar
+            None => continue,
+        };
+
+        let value = input[value_start..line_end].trim_start();
+        let value_start = line_end - value.len();
+        if value.starts_with('|') || value.starts_with('>') {
+            // The block belongs to the key or item that was just opened
+            block = Some(frames.last().map_or(0, |f| f.indent));
+        } else if value.starts_with('[') {
+            for (i, range) in yaml_flow_sequence(input, value_start, line_end)
+                .into_iter()
+                .enumerate()
+            {
+                let mut item_path = path(&frames);
+                item_path.push(i.to_string());
+                values.push(yaml_value(input, range, item_path));
+            }
+        } else if let Some(range) = yaml_scalar(input, value_start, line_end) {
+            values.push(yaml_value(input, range, path(&frames)));
+        }
+    }
+    values
+}
+
+/// Closes the frames nested deeper than a `-` at `indent` and returns the
+/// index of the new item.
+fn close_item(frames: &mut Vec<Frame>, indent: usize) -> usize {
+    while let Some(top) = frames.last() {
+        if top.indent > indent {
+            frames.pop();
+        } else if top.indent == indent
+            && let Some(index) = top.item
+        {
+            frames.pop();
+            return index + 1;
+        } else {
+            break;
+        }
+    }
+    0
+}
+
+fn yaml_value(input: &str, range: Range<usize>, path: Vec<String>) -> Value {
+    let escapes = range.start > 0 && input.as_bytes()[range.start - 1] == b'"';
+    Value {
+        range,
+        path,
+        escapes,
+    }
+}
+
+/// Finds the `:` separating a mapping key from its value, skipping quoted keys.
+pub(super) fn find_yaml_colon(entry: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in entry.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') if i == 0 => quote = Some(c),
+            (None, ':') => {
+                let next = entry[i + 1..].chars().next();
+                if next.is_none_or(char::is_whitespace) {
+                    return Some(i);
+                }
+            }
+            (None, '#') => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Window of a single YAML scalar (plain, single- or double-quoted).
+/// Anchors and tags are skipped; aliases, numbers, booleans and nulls
+/// have no window.
+pub(super) fn yaml_scalar(input: &str, start: usize, end: usize) -> Option<Range<usize>> {
+    let mut text = input[start..end].trim_start();
+    while text.starts_with(['&', '!']) {
+        let property = text.find(char::is_whitespace)?;
+        text = text[property..].trim_start();
+    }
+    let start = end - text.len();
+
+    match text.chars().next()? {
+        quote @ ('"' | '\'') => {
+            let close = find_closing_quote(&text[1..], quote)?;
+            Some(start + 1..start + 1 + close)
+        }
+        '*' | '{' => None,
+        _ => {
+            let value = match text.find(" #") {
+                Some(comment) => &text[..comment],
+                None => text,
+            };
+            let value = value.trim_end();
+            (!value.is_empty() && !is_yaml_literal(value)).then(|| start..start + value.len())
+        }
+    }
+}
+
+fn is_yaml_literal(value: &str) -> bool {
+    matches!(
+        value.to_ascii_lowercase().as_str(),
+        "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~" | ".inf" | "-.inf" | ".nan"
+    ) || value.parse::<f64>().is_ok()
+}
+
+/// `[a, "b", c]` on a single line.
+pub(super) fn yaml_flow_sequence(input: &str, start: usize, end: usize) -> Vec<Range<usize>> {
+    let mut windows = Vec::new();
+    let text = &input[start..end];
+    let Some(close) = text.rfind(']') else {
+        return windows;
+    };
+
+    let mut item_start = start + 1;
+    let mut quote: Option<char> = None;
+    for (i, c) in text[1..close].char_indices() {
+        let abs = start + 1 + i;
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ',') => {
+                windows.extend(yaml_scalar(input, item_start, abs));
+                item_start = abs + 1;
+            }
+            _ => {}
+        }
+    }
+    windows.extend(yaml_scalar(input, item_start, start + close));
+    windows
+}
+
+// --- TOML ---
+
+pub(super) struct TomlScanner<'a> {
+    input: &'a str,
+    values: Vec<Value>,
+    /// Key path of the current value: table header plus key.
+    path: Vec<String>,
+    /// `[table]` or `[[array]]` header in effect.
+    table: Vec<String>,
+    /// Number of `[[array]]` tables seen so far, by name.
+    array_tables: HashMap<Vec<String>, usize>,
+}
+
+impl<'a> TomlScanner<'a> {
+    pub(super) fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            values: Vec::new(),
+            path: Vec::new(),
+            table: Vec::new(),
+            array_tables: HashMap::new(),
+        }
+    }
+
+    pub(super) fn values(mut self, content: Range<usize>) -> Vec<Value> {
+        let end = content.end;
+        let mut pos = content.start;
+
+        while pos < end {
+            pos = self.skip_whitespace(pos, end, true);
+            if pos >= end {
+                break;
+            }
+            match self.byte(pos) {
+                b'#' => pos = self.line_end(pos, end),
+                b'[' => pos = self.header(pos, end),
+                _ => pos = self.key_value(pos, end),
+            }
+        }
+        self.values
+    }
+
+    /// Parses `[table]` or `[[array]]` and returns the position after it.
+    fn header(&mut self, pos: usize, end: usize) -> usize {
+        let line_end = self.line_end(pos, end);
+        let line = self.input[pos..line_end].trim_end();
+        if let Some(name) = line.strip_prefix("[[").and_then(|l| l.split("]]").next()) {
+            let name = split_key(name);
+            let count = self.array_tables.entry(name.clone()).or_insert(0);
+            self.table = name;
+            self.table.push(count.to_string());
+            *count += 1;
+        } else if let Some(name) = line.strip_prefix('[').and_then(|l| l.split(']').next()) {
+            self.table = split_key(name);
+        }
+        line_end
+    }
+
+    /// Parses `key = value` and returns the position after it.
+    fn key_value(&mut self, pos: usize, end: usize) -> usize {
+        let line_end = self.line_end(pos, end);
+        let Some(eq) = self.input[pos..line_end].find('=') else {
+            return line_end;
+        };
+        self.path.clone_from(&self.table);
+        self.path.extend(split_key(&self.input[pos..pos + eq]));
+
+        let value_start = self.skip_whitespace(pos + eq + 1, end, false);
+        let after = self.value(value_start, end);
+        self.line_end(after, end)
+    }
+
+    /// Parses a TOML value starting at `pos`, recording its strings.
+    /// Returns the position after the value.
+    fn value(&mut self, pos: usize, end: usize) -> usize {
+        let rest = &self.input[pos..end];
+
+        for delimiter in ["\"\"\"", "'''"] {
+            if let Some(body) = rest.strip_prefix(delimiter) {
+                let close = body.find(delimiter).unwrap_or(body.len());
+                let start = pos + 3;
+                self.push(start..start + close, delimiter.starts_with('"'));
+                return (start + close + 3).min(end);
+            }
+        }
+
+        match rest.as_bytes().first() {
+            Some(&quote @ (b'"' | b'\'')) => {
+                let body = &rest[1..];
+                let close = find_closing_quote(body, quote as char).unwrap_or(body.len());
+                self.push(pos + 1..pos + 1 + close, quote == b'"');
+                (pos + 2 + close).min(end)
+            }
+            Some(b'[') => self.array(pos + 1, end),
+            Some(b'{') => self.inline_table(pos + 1, end),
+            _ => {
+                // Numbers, booleans and dates are never converted
+                let stop = rest.find([',', ']', '}', '\n', '#']).unwrap_or(rest.len());
+                pos + stop
+            }
+        }
+    }
+
+    fn push(&mut self, range: Range<usize>, escapes: bool) {
+        self.values.push(Value {
+            range,
+            path: self.path.clone(),
+            escapes,
+        });
+    }
+
+    fn array(&mut self, mut pos: usize, end: usize) -> usize {
+        let mut index = 0;
+        while pos < end {
+            pos = self.skip_whitespace(pos, end, true);
+            if pos >= end {
+                break;
+            }
+            match self.byte(pos) {
+                b']' => return pos + 1,
+                b',' => {
+                    index += 1;
+                    pos += 1;
+                }
+                b'#' => pos = self.line_end(pos, end),
+                _ => {
+                    self.path.push(index.to_string());
+                    pos = self.value(pos, end).max(pos + 1);
+                    self.path.pop();
+                }
+            }
+        }
+        pos
+    }
+
+    fn inline_table(&mut self, mut pos: usize, end: usize) -> usize {
+        while pos < end {
+            pos = self.skip_whitespace(pos, end, true);
+            if pos >= end {
+                break;
+            }
+            match self.byte(pos) {
+                b'}' => return pos + 1,
+                b',' => pos += 1,
+                _ => {
+                    let Some(eq) = self.input[pos..end].find('=') else {
+                        return end;
+                    };
+                    let depth = self.path.len();
+                    self.path.extend(split_key(&self.input[pos..pos + eq]));
+                    let value_start = self.skip_whitespace(pos + eq + 1, end, false);
+                    pos = self.value(value_start, end).max(pos + 1);
+                    self.path.truncate(depth);
+                }
+            }
+        }
+        pos
+    }
+
+    fn byte(&self, pos: usize) -> u8 {
+        self.input.as_bytes()[pos]
+    }
+
+    fn skip_whitespace(&self, mut pos: usize, end: usize, newlines: bool) -> usize {
+        while pos < end {
+            match self.byte(pos) {
+                b' ' | b'\t' | b'\r' => pos += 1,
+                b'\n' if newlines => pos += 1,
+                _ => break,
+            }
+        }
+        pos
+    }
+
+    fn line_end(&self, pos: usize, end: usize) -> usize {
+        self.input[pos..end].find('\n').map_or(end, |i| pos + i + 1)
+    }
+}
+
+/// Splits a dotted TOML key into its segments.
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.').map(unquote).collect()
+}
@@ -0,0 +1,182 @@
+use crate::types::Rule;
+use crate::{HujjatTuri, TarjimaFormati};
+use regex::Regex;
+use std::ops::{Range, RangeInclusive};
+use std::sync::LazyLock;
+
+/// Directive text shared by every comment syntax:
+/// `latinga: off|on`, `latinga-disable-line` and `latinga-disable-next-line`,
+/// optionally followed by the validator rules they apply to.
+const BODY: &str = r"latinga(?::[ \t]*(?P<state>off|on)|-(?P<line>disable-next-line|disable-line))(?P<rules>(?:[ \t,]+[a-z-]+)*)";
+
+static RE_HTML: LazyLock<Regex> = LazyLock::new(|| directive(r"<!--[ \t]*", r"[ \t]*-->"));
+static RE_LATEX: LazyLock<Regex> = LazyLock::new(|| directive(r"(?m)%[ \t]*", r"[ \t]*\r?$"));
+static RE_LINE_COMMENT: LazyLock<Regex> =
+    LazyLock::new(|| directive(r"(?m)(?://|#)[ \t]*", r"[ \t]*\r?$"));
+static RE_BLOCK_COMMENT: LazyLock<Regex> = LazyLock::new(|| directive(r"/\*[ \t]*", r"[ \t]*\*/"));
+
+fn directive(open: &str, close: &str) -> Regex {
+    Regex::new(&format!("{open}{BODY}{close}")).unwrap()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Off,
+    On,
+    Line,
+    NextLine,
+}
+
+/// Validator rules suppressed on a range of lines (1-based).
+#[derive(Debug, Clone)]
+pub(super) struct Suppression {
+    pub lines: RangeInclusive<usize>,
+    pub rules: Vec<Rule>,
+}
+
+/// Spans turned off by directives without rules, and rule-specific
+/// suppressions for the validator.
+#[derive(Debug, Default)]
+pub(super) struct Directives {
+    pub shielded: Vec<Range<usize>>,
+    pub suppressions: Vec<Suppression>,
+}
+
+/// Finds the directives written in the comment syntax of `hujjat`.
+/// The directive comments themselves are always shielded.
+pub(super) fn scan(input: &str, hujjat: HujjatTuri) -> Directives {
+    let regexes: &[&Regex] = match hujjat {
+        HujjatTuri::Umumiy => &[&RE_HTML, &RE_LATEX],
+        HujjatTuri::Markdown | HujjatTuri::Html | HujjatTuri::Xml => &[&RE_HTML],
+        HujjatTuri::Latex => &[&RE_LATEX],
+        HujjatTuri::Kod(crate::KodTili::Php) => &[&RE_HTML, &RE_LINE_COMMENT, &RE_BLOCK_COMMENT],
+        HujjatTuri::Kod(_) => &[&RE_LINE_COMMENT, &RE_BLOCK_COMMENT],
+        HujjatTuri::Yaml | HujjatTuri::Toml | HujjatTuri::Po => &[&RE_LINE_COMMENT],
+        HujjatTuri::Tarjima(TarjimaFormati::Strings) => &[&RE_LINE_COMMENT, &RE_BLOCK_COMMENT],
+        HujjatTuri::Tarjima(TarjimaFormati::Properties) => &[&RE_LINE_COMMENT],
+        HujjatTuri::Tarjima(TarjimaFormati::Arb) => &[],
+        HujjatTuri::Tarjima(_) => &[&RE_HTML],
+        HujjatTuri::Matn
+        | HujjatTuri::Ijtimoiy
+        | HujjatTuri::Json
+        | HujjatTuri::Subtitr
+        | HujjatTuri::Csv
+        | HujjatTuri::Tsv
+        | HujjatTuri::Ofis
+        | HujjatTuri::Odf
+        | HujjatTuri::Epub
+        | HujjatTuri::Fb2
+        | HujjatTuri::Rtf
+        | HujjatTuri::Sql => &[],
+    };
+
+    let mut found = Vec::new();
+    for re in regexes {
+        for cap in re.captures_iter(input) {
+            let kind = match (cap.name("state"), cap.name("line")) {
+                (Some(s), _) if s.as_str() == "off" => Kind::Off,
+                (Some(_), _) => Kind::On,
+                (None, Some(l)) if l.as_str() == "disable-line" => Kind::Line,
+                _ => Kind::NextLine,
+            };
+            let names = cap.name("rules").map_or("", |r| r.as_str());
+            // A directive naming an unknown rule is ignored rather than
+            // widened to every rule.
+            let Some(rules) = names
+                .split([' ', '\t', ','])
+                .filter(|n| !n.is_empty())
+                .map(Rule::parse)
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+            found.push((cap.get(0).unwrap().range(), kind, rules));
+        }
+    }
+    found.sort_by_key(|(range, _, _)| range.start);
+
+    let lines = LineIndex::new(input);
+    let mut directives = Directives::default();
+    let mut open: Vec<(usize, Vec<Rule>)> = Vec::new();
+
+    for (range, kind, rules) in found {
+        directives.shielded.push(range.clone());
+        match kind {
+            Kind::Off => {
+                if !open.iter().any(|(_, r)| *r == rules) {
+                    open.push((range.start, rules));
+                }
+            }
+            Kind::On => {
+                // A bare `on` ends every open span
+                let (closed, still_open) = open
+                    .into_iter()
+                    .partition(|(_, r)| rules.is_empty() || *r == rules);
+                open = still_open;
+                for (start, r) in closed {
+                    directives.add(&lines, start..range.end, r);
+                }
+            }
+            Kind::Line => {
+                let span = lines.line_start(range.start)..lines.line_end(range.start);
+                directives.add(&lines, span, rules);
+            }
+            Kind::NextLine => {
+                let next = lines.line_end(range.start) + 1;
+                if next < input.len() {
+                    directives.add(&lines, next..lines.line_end(next), rules);
+                }
+            }
+        }
+    }
+    for (start, rules) in open {
+        directives.add(&lines, start..input.len(), rules);
+    }
+    directives
+}
+
+impl Directives {
+    fn add(&mut self, lines: &LineIndex, span: Range<usize>, rules: Vec<Rule>) {
+        if rules.is_empty() {
+            self.shielded.push(span);
+        } else {
+            let last = span.end.saturating_sub(1).max(span.start);
+            self.suppressions.push(Suppression {
+                lines: lines.line_of(span.start)..=lines.line_of(last),
+                rules,
+            });
+        }
+    }
+}
+
+/// Offsets of the newlines of a document.
+struct LineIndex {
+    newlines: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    fn new(input: &str) -> Self {
+        Self {
+            newlines: input.match_indices('\n').map(|(i, _)| i).collect(),
+            len: input.len(),
+        }
+    }
+
+    /// 1-based line number of `pos`.
+    fn line_of(&self, pos: usize) -> usize {
+        self.newlines.partition_point(|&nl| nl < pos) + 1
+    }
+
+    fn line_start(&self, pos: usize) -> usize {
+        let line = self.newlines.partition_point(|&nl| nl < pos);
+        line.checked_sub(1)
+            .map_or(0, |prev| self.newlines[prev] + 1)
+    }
+
+    /// Offset of the newline ending the line of `pos` (or the input length).
+    fn line_end(&self, pos: usize) -> usize {
+        let line = self.newlines.partition_point(|&nl| nl < pos);
+        self.newlines.get(line).copied().unwrap_or(self.len)
+    }
+}
@@ -0,0 +1,73 @@
+use super::lang::latin_edits;
+use super::office::RE_TAG;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+static RE_XML_LANG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\sxml:lang="(?P<value>[^"]*)""#).unwrap());
+
+/// Elements whose text is converted: the title, authors, description and
+/// subjects of the package document, and the labels of an NCX table of
+/// contents (`<navLabel><text>`, `<docTitle><text>`, `<docAuthor><text>`).
+fn is_converted(name: &str) -> bool {
+    matches!(
+        name,
+        "dc:title" | "dc:creator" | "dc:description" | "dc:subject" | "text"
+    )
+}
+
+/// Text of the converted metadata and table-of-contents entries of an EPUB
+/// package document (OPF) or NCX, one entry per element.
+pub(crate) fn paragraphs(input: &str) -> Vec<Vec<Range<usize>>> {
+    element_contents(input, is_converted)
+        .into_iter()
+        .filter(|content| !content.is_empty())
+        .map(|content| vec![content])
+        .collect()
+}
+
+/// Edits that relabel Uzbek `dc:language` entries and `xml:lang`
+/// attributes as `uz-Latn`.
+pub(crate) fn language_edits(input: &str) -> Vec<(Range<usize>, &'static str)> {
+    let mut edits = Vec::new();
+    for language in element_contents(input, |name| name == "dc:language") {
+        edits.extend(latin_edits(&input[language.clone()], language.start));
+    }
+    for cap in RE_XML_LANG.captures_iter(input) {
+        let value = cap.name("value").unwrap();
+        edits.extend(latin_edits(value.as_str(), value.start()));
+    }
+    edits
+}
+
+/// Content ranges of the elements accepted by `wanted`. Elements nested
+/// in a wanted element are part of its content.
+fn element_contents(input: &str, wanted: impl Fn(&str) -> bool) -> Vec<Range<usize>> {
+    let mut contents = Vec::new();
+    let mut open: Option<(&str, usize)> = None;
+
+    for cap in RE_TAG.captures_iter(input) {
+        let tag = cap.get(0).unwrap();
+        let name = cap.name("name").unwrap().as_str();
+        match open {
+            Some((open_name, start)) if name == open_name && !cap["close"].is_empty() => {
+                contents.push(start..tag.start());
+                open = None;
+            }
+            None if cap["close"].is_empty() && cap["empty"].is_empty() && wanted(name) => {
+                open = Some((name, tag.end()));
+            }
+            _ => {}
+        }
+    }
+    contents
+}
+
+/// Shields an EPUB package document or NCX except for its converted entries.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    mask.fill(true);
+    for content in paragraphs(input).into_iter().flatten() {
+        mask[content].fill(false);
+    }
+}
@@ -0,0 +1,89 @@
+use super::lang::latin_edits;
+use super::office::{RE_TAG, TextElements, text_nodes};
+use std::ops::Range;
+
+/// Elements whose text is converted as one unit: paragraphs, verse lines,
+/// subtitles and table cells of the bodies, and the title and author names
+/// in `title-info`.
+fn is_paragraph(name: &str) -> bool {
+    matches!(
+        name,
+        "p" | "v"
+            | "subtitle"
+            | "text-author"
+            | "th"
+            | "td"
+            | "book-title"
+            | "first-name"
+            | "middle-name"
+            | "last-name"
+            | "nickname"
+            | "keywords"
+    )
+}
+
+/// Inline images end a word.
+fn is_spacing(name: &str) -> bool {
+    name == "image"
+}
+
+/// Elements never converted: code, embedded binaries and stylesheets, and
+/// the metadata of the FB2 file itself, of its publication and of the
+/// original of a translation.
+fn is_skipped(name: &str) -> bool {
+    matches!(
+        name,
+        "code"
+            | "binary"
+            | "stylesheet"
+            | "document-info"
+            | "publish-info"
+            | "custom-info"
+            | "src-title-info"
+    )
+}
+
+/// Text nodes of a FictionBook document grouped by paragraph, across
+/// `emphasis`, `strong`, `a` and other inline elements.
+pub(crate) fn paragraphs(input: &str) -> Vec<Vec<Range<usize>>> {
+    text_nodes(
+        input,
+        &TextElements {
+            paragraph: is_paragraph,
+            spacing: is_spacing,
+            skipped: is_skipped,
+        },
+    )
+}
+
+/// Edits that relabel the Uzbek book language (`<lang>` in `title-info`)
+/// as `uz-Latn`.
+pub(crate) fn language_edits(input: &str) -> Vec<(Range<usize>, &'static str)> {
+    let mut edits = Vec::new();
+    let mut title_info = false;
+    let mut lang_start = None;
+
+    for cap in RE_TAG.captures_iter(input) {
+        let tag = cap.get(0).unwrap();
+        let closing = !cap["close"].is_empty();
+        match &cap["name"] {
+            "title-info" => title_info = !closing,
+            "lang" if title_info && !closing => lang_start = Some(tag.end()),
+            "lang" => {
+                if let Some(start) = lang_start.take() {
+                    edits.extend(latin_edits(&input[start..tag.start()], start));
+                }
+            }
+            _ => {}
+        }
+    }
+    edits
+}
+
+/// Shields a FictionBook document except for the text of its paragraphs.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    mask.fill(true);
+    for run in paragraphs(input).into_iter().flatten() {
+        mask[run].fill(false);
+    }
+}
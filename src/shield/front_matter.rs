@@ -0,0 +1,73 @@
+use super::data::{TomlScanner, lines_with_offsets, yaml_values};
+use std::ops::Range;
+
+/// Front matter flavours used by Hugo, Jekyll and Zola.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Flavour {
+    /// `---` fenced YAML.
+    Yaml,
+    /// `+++` fenced TOML.
+    Toml,
+}
+
+/// Shields the front matter block at the start of `input`, reopening only
+/// the values of `keys` for conversion.
+/// Returns the byte offset where the document body starts (0 if there is
+/// no front matter).
+pub(super) fn build_mask(input: &str, keys: &[String], mask: &mut [bool]) -> usize {
+    let Some((flavour, content, body_start)) = detect(input) else {
+        return 0;
+    };
+
+    if let Some(slice) = mask.get_mut(..body_start) {
+        slice.fill(true);
+    }
+
+    let values = match flavour {
+        Flavour::Yaml => yaml_values(input, content),
+        Flavour::Toml => TomlScanner::new(input).values(content),
+    };
+    for value in values {
+        // Items of a list belong to the key holding the list
+        let key = value
+            .path
+            .iter()
+            .rev()
+            .find(|s| s.parse::<usize>().is_err());
+        if key.is_some_and(|key| keys.iter().any(|k| k.eq_ignore_ascii_case(key)))
+            && let Some(slice) = mask.get_mut(value.range)
+        {
+            slice.fill(false);
+        }
+    }
+    body_start
+}
+
+/// Finds the opening and closing fences.
+/// Returns the flavour, the byte range between the fences and the body offset.
+fn detect(input: &str) -> Option<(Flavour, Range<usize>, usize)> {
+    let mut lines = lines_with_offsets(input).peekable();
+
+    let (_, first) = lines.next()?;
+    let flavour = match first.trim_start_matches('\u{FEFF}').trim_end() {
+        "---" => Flavour::Yaml,
+        "+++" => Flavour::Toml,
+        _ => return None,
+    };
+    let content_start = lines.peek().map_or(input.len(), |(offset, _)| *offset);
+
+    for (offset, line) in lines {
+        let fence = line.trim_end();
+        let closes = match flavour {
+            Flavour::Yaml => fence == "---" || fence == "...",
+            Flavour::Toml => fence == "+++",
+        };
+        if closes {
+            let body_start = input[offset..]
+                .find('\n')
+                .map_or(input.len(), |i| offset + i + 1);
+            return Some((flavour, content_start..offset, body_start));
+        }
+    }
+    None
+}
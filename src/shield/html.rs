@@ -0,0 +1,233 @@
+use crate::types::AttrQuote;
+use std::ops::Range;
+
+/// Elements whose content is not tokenized as markup (HTML5 script data,
+/// RAWTEXT and RCDATA states). Their content runs to the matching end tag.
+const RAW_TEXT_ELEMENTS: &[&str] = &[
+    "script", "style", "xmp", "iframe", "noembed", "noframes", "textarea", "title",
+];
+
+/// What the tokenizer found at a `<`.
+#[derive(Debug)]
+pub(super) enum Markup {
+    /// The `<` does not start markup ("a < b") and is ordinary text.
+    Text,
+    /// Comment, doctype, CDATA section, processing instruction or bogus
+    /// comment ending at the given offset. Never converted.
+    Opaque(usize),
+    /// A start or end tag.
+    Tag(Tag),
+}
+
+#[derive(Debug)]
+pub(super) struct Tag {
+    /// ASCII-lowercased tag name.
+    pub name: String,
+    pub is_end: bool,
+    /// Start tag written as `<name ... />`.
+    pub self_closing: bool,
+    /// Offset just after the closing `>`.
+    pub end: usize,
+    pub attrs: Vec<Attribute>,
+}
+
+#[derive(Debug)]
+pub(super) struct Attribute {
+    pub name: Range<usize>,
+    /// Value range excluding quotes.
+    pub value: Option<(Range<usize>, AttrQuote)>,
+}
+
+impl Tag {
+    /// Whether the element's content must be skipped up to its end tag.
+    pub fn has_raw_text(&self) -> bool {
+        !self.is_end && RAW_TEXT_ELEMENTS.contains(&self.name.as_str())
+    }
+
+    /// Value of the first attribute with one of the given names.
+    pub fn attr_value<'a>(&self, input: &'a str, names: &[&str]) -> Option<&'a str> {
+        self.attrs
+            .iter()
+            .find(|attr| {
+                names
+                    .iter()
+                    .any(|n| input[attr.name.clone()].eq_ignore_ascii_case(n))
+            })
+            .map(|attr| {
+                attr.value
+                    .as_ref()
+                    .map_or("", |(value, _)| &input[value.clone()])
+            })
+    }
+}
+
+/// Tokenizes the markup starting at `pos`, which must point to a `<`.
+/// Follows the HTML5 tag-open, markup-declaration and attribute states.
+pub(super) fn tokenize(input: &str, pos: usize) -> Markup {
+    let bytes = input.as_bytes();
+    let rest = &input[pos..];
+
+    match bytes.get(pos + 1) {
+        Some(b'!') => Markup::Opaque(markup_declaration(input, pos)),
+        Some(b'?') => Markup::Opaque(bogus_comment(input, pos + 2)),
+        Some(b'/') => match bytes.get(pos + 2) {
+            Some(b) if b.is_ascii_alphabetic() => tag(input, pos + 2, true),
+            // `</>` is dropped by browsers
+            Some(b'>') => Markup::Opaque(pos + 3),
+            Some(_) if rest.len() > 2 => Markup::Opaque(bogus_comment(input, pos + 2)),
+            _ => Markup::Text,
+        },
+        Some(b) if b.is_ascii_alphabetic() => tag(input, pos + 1, false),
+        _ => Markup::Text,
+    }
+}
+
+/// Finds the end tag closing a raw text or fully protected element.
+/// Returns (start of `</name`, offset after its `>`).
+pub(super) fn find_end_tag(input: &str, from: usize, name: &str) -> Option<(usize, usize)> {
+    let bytes = input.as_bytes();
+    let mut search = from;
+
+    while let Some(rel) = input[search..].find("</") {
+        let start = search + rel;
+        let name_start = start + 2;
+        let name_end = name_start + name.len();
+
+        let name_matches = bytes
+            .get(name_start..name_end)
+            .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name.as_bytes()));
+        let terminated = matches!(
+            bytes.get(name_end),
+            Some(b'>' | b'/' | b' ' | b'\t' | b'\n' | b'\r' | b'\x0C') | None
+        );
+
+        if name_matches && terminated {
+            let end = input[name_end..]
+                .find('>')
+                .map_or(input.len(), |i| name_end + i + 1);
+            return Some((start, end));
+        }
+        search = start + 2;
+    }
+    None
+}
+
+fn markup_declaration(input: &str, pos: usize) -> usize {
+    let rest = &input[pos..];
+
+    if let Some(comment) = rest.strip_prefix("<!--") {
+        // `<!-->` and `<!--->` are complete (empty) comments
+        for short in ["<!-->", "<!--->"] {
+            if rest.starts_with(short) {
+                return pos + short.len();
+            }
+        }
+        return comment.find("-->").map_or(input.len(), |i| pos + 4 + i + 3);
+    }
+    if rest.starts_with("<![CDATA[") {
+        return rest.find("]]>").map_or(input.len(), |i| pos + i + 3);
+    }
+    // DOCTYPE and bogus comments both end at the first `>`
+    bogus_comment(input, pos + 2)
+}
+
+fn bogus_comment(input: &str, from: usize) -> usize {
+    input[from..]
+        .find('>')
+        .map_or(input.len(), |i| from + i + 1)
+}
+
+fn tag(input: &str, name_start: usize, is_end: bool) -> Markup {
+    let bytes = input.as_bytes();
+    let len = bytes.len();
+
+    let mut i = name_start;
+    while i < len && !is_tag_delimiter(bytes[i]) {
+        i += 1;
+    }
+    let name = input[name_start..i].to_ascii_lowercase();
+    let mut attrs = Vec::new();
+
+    loop {
+        // Before attribute name
+        let mut self_closing = false;
+        while i < len && (is_whitespace(bytes[i]) || bytes[i] == b'/') {
+            self_closing = bytes[i] == b'/';
+            i += 1;
+        }
+        if i >= len {
+            // EOF inside a tag: browsers drop it, we keep it as text
+            return Markup::Text;
+        }
+        if bytes[i] == b'>' {
+            return Markup::Tag(Tag {
+                name,
+                is_end,
+                self_closing,
+                end: i + 1,
+                attrs,
+            });
+        }
+
+        // Attribute name (a leading `=` belongs to the name)
+        let attr_start = i;
+        i += 1;
+        while i < len && !is_tag_delimiter(bytes[i]) && bytes[i] != b'=' {
+            i += 1;
+        }
+        let attr_name = attr_start..i;
+
+        // After attribute name
+        while i < len && is_whitespace(bytes[i]) {
+            i += 1;
+        }
+        if i >= len || bytes[i] != b'=' {
+            attrs.push(Attribute {
+                name: attr_name,
+                value: None,
+            });
+            continue;
+        }
+        i += 1;
+
+        // Before attribute value
+        while i < len && is_whitespace(bytes[i]) {
+            i += 1;
+        }
+        let value = match bytes.get(i) {
+            Some(&q @ (b'"' | b'\'')) => {
+                let value_start = i + 1;
+                let Some(close) = input[value_start..].find(q as char) else {
+                    return Markup::Text;
+                };
+                i = value_start + close + 1;
+                let quote = if q == b'"' {
+                    AttrQuote::Double
+                } else {
+                    AttrQuote::Single
+                };
+                Some((value_start..value_start + close, quote))
+            }
+            Some(b'>') | None => None,
+            Some(_) => {
+                let value_start = i;
+                while i < len && !is_whitespace(bytes[i]) && bytes[i] != b'>' {
+                    i += 1;
+                }
+                Some((value_start..i, AttrQuote::Unquoted))
+            }
+        };
+        attrs.push(Attribute {
+            name: attr_name,
+            value,
+        });
+    }
+}
+
+fn is_whitespace(b: u8) -> bool {
+    matches!(b, b' ' | b'\t' | b'\n' | b'\r' | b'\x0C')
+}
+
+fn is_tag_delimiter(b: u8) -> bool {
+    is_whitespace(b) || b == b'/' || b == b'>'
+}
@@ -0,0 +1,330 @@
+use super::Rewrite;
+use super::data::{JsonScanner, find_closing_quote, lines_with_offsets, open_value};
+use super::lang;
+use crate::TarjimaFormati;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Tags, comments and CDATA markers inside XML values.
+static RE_MARKUP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->|<!\[CDATA\[|\]\]>|</?[A-Za-z][^>]*>").unwrap());
+static RE_COMMENT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+/// Android placeholders wrapped in `<xliff:g>` keep their content as written.
+static RE_XLIFF_G: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<xliff:g\b[^>]*>.*?</xliff:g>").unwrap());
+/// `%@`, `%1$@` and the `%#@files@` variables of `.stringsdict`.
+static RE_APPLE_PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"%(?:\d+\$)?(?:#@\w+@|@)").unwrap());
+/// Native code of XLIFF 1.2 inline elements.
+static RE_XLIFF_CODE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<ph\b[^>]*>.*?</ph>|<bpt\b[^>]*>.*?</bpt>|<ept\b[^>]*>.*?</ept>|<it\b[^>]*>.*?</it>|<mrk\b[^>]*translate=.no.[^>]*>.*?</mrk>")
+        .unwrap()
+});
+
+static RE_ANDROID_ELEMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(string-array|plurals|string)\b([^>]*)>").unwrap());
+static RE_ANDROID_ITEM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<item\b[^>]*>(.*?)</item>").unwrap());
+static RE_PLIST_PAIR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<key>(.*?)</key>\s*<string>(.*?)</string>").unwrap());
+static RE_XLIFF_TARGET: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<target\b([^>]*)>").unwrap());
+static RE_XLIFF_UNIT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<(?:trans-unit|unit)\b([^>]*)>").unwrap());
+/// Attributes naming the language of XLIFF translations.
+static RE_XLIFF_LANG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"<(?:file|xliff|target)\b[^>]*?\b(?:target-language|trgLang|xml:lang)\s*=\s*"([^"]*)""#,
+    )
+    .unwrap()
+});
+static RE_NOT_TRANSLATABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\b(?:translatable\s*=\s*"false"|translate\s*=\s*"no")"#).unwrap()
+});
+
+/// `.stringsdict` keys whose values are format metadata, not text.
+const PLIST_METADATA_KEYS: &[&str] = &["NSStringFormatSpecTypeKey", "NSStringFormatValueTypeKey"];
+
+/// Shields a localization resource except for its translatable values.
+/// Placeholders, escapes and inline markup stay shielded; `\uXXXX` escapes
+/// and relabelled XLIFF languages are added to `rewrites`.
+/// Returns the Android and `.strings` values whose converted text must
+/// escape their quote.
+pub(super) fn build_mask(
+    input: &str,
+    format: TarjimaFormati,
+    mask: &mut [bool],
+    rewrites: &mut Vec<(Range<usize>, Rewrite)>,
+) -> Vec<(Range<usize>, char)> {
+    mask.fill(true);
+    let mut values = Vec::new();
+    let mut literals = Vec::new();
+
+    match format {
+        TarjimaFormati::Android => {
+            for range in android_values(input) {
+                literals.push((range.clone(), '\''));
+                values.push(range);
+            }
+        }
+        TarjimaFormati::Strings => {
+            for range in strings_values(input) {
+                literals.push((range.clone(), '"'));
+                values.push(range);
+            }
+        }
+        TarjimaFormati::Stringsdict => {
+            for cap in RE_PLIST_PAIR.captures_iter(input) {
+                let (key, value) = (cap.get(1).unwrap(), cap.get(2).unwrap());
+                if !PLIST_METADATA_KEYS.contains(&key.as_str().trim()) {
+                    values.push(value.range());
+                }
+            }
+        }
+        TarjimaFormati::Arb => {
+            // `@key` entries hold descriptions and placeholder metadata
+            for value in JsonScanner::new(input).values() {
+                if value.path.len() == 1 && !value.path[0].starts_with('@') {
+                    let start = value.range.start;
+                    let text = &input[value.range.clone()];
+                    rewrites.extend(unicode(open_value(input, value.range, true, &[], mask)));
+                    mask_icu_syntax(text, start, mask);
+                }
+            }
+        }
+        TarjimaFormati::Properties => {
+            values = properties_values(input);
+        }
+        TarjimaFormati::Xliff => {
+            values = xliff_targets(input);
+            for cap in RE_XLIFF_LANG.captures_iter(input) {
+                let value = cap.get(1).unwrap();
+                rewrites.extend(
+                    lang::latin_edits(value.as_str(), value.start())
+                        .into_iter()
+                        .map(|(range, text)| (range, Rewrite::Replace(text))),
+                );
+            }
+        }
+    }
+
+    let shielded: Vec<&Regex> = match format {
+        TarjimaFormati::Android => vec![&RE_XLIFF_G, &RE_MARKUP],
+        TarjimaFormati::Strings => vec![&RE_APPLE_PLACEHOLDER],
+        TarjimaFormati::Stringsdict => vec![&RE_APPLE_PLACEHOLDER, &RE_MARKUP],
+        TarjimaFormati::Xliff => vec![&RE_XLIFF_CODE, &RE_MARKUP],
+        TarjimaFormati::Arb | TarjimaFormati::Properties => Vec::new(),
+    };
+    // XML values have no backslash escapes of their own, except Android's
+    let escapes = !matches!(format, TarjimaFormati::Stringsdict | TarjimaFormati::Xliff);
+    let comments: Vec<_> = RE_COMMENT.find_iter(input).map(|m| m.range()).collect();
+    for range in values {
+        if !comments.iter().any(|c| c.contains(&range.start)) {
+            rewrites.extend(unicode(open_value(input, range, escapes, &shielded, mask)));
+        }
+    }
+    literals.retain(|(range, _)| !comments.iter().any(|c| c.contains(&range.start)));
+    literals
+}
+
+fn unicode(encoded: Vec<Range<usize>>) -> impl Iterator<Item = (Range<usize>, Rewrite)> {
+    encoded.into_iter().map(|range| (range, Rewrite::Unescape))
+}
+
+/// Contents of translatable `string` elements and `plurals`/`string-array` items.
+fn android_values(input: &str) -> Vec<Range<usize>> {
+    let mut values = Vec::new();
+    for cap in RE_ANDROID_ELEMENT.captures_iter(input) {
+        let (name, attrs) = (cap.get(1).unwrap().as_str(), cap.get(2).unwrap().as_str());
+        if attrs.ends_with('/') || RE_NOT_TRANSLATABLE.is_match(attrs) {
+            continue;
+        }
+        let start = cap.get(0).unwrap().end();
+        let Some(len) = input[start..].find(&format!("</{name}>")) else {
+            continue;
+        };
+        if name == "string" {
+            values.push(start..start + len);
+        } else {
+            values.extend(
+                RE_ANDROID_ITEM
+                    .captures_iter(&input[start..start + len])
+                    .map(|item| {
+                        let content = item.get(1).unwrap();
+                        start + content.start()..start + content.end()
+                    }),
+            );
+        }
+    }
+    values
+}
+
+/// Values of `"key" = "value";` pairs.
+fn strings_values(input: &str) -> Vec<Range<usize>> {
+    let bytes = input.as_bytes();
+    let mut values = Vec::new();
+    let mut expect_value = false;
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        match &bytes[pos..] {
+            [b'/', b'/', ..] => {
+                pos = input[pos..].find('\n').map_or(bytes.len(), |i| pos + i + 1);
+            }
+            [b'/', b'*', ..] => {
+                pos = input[pos..].find("*/").map_or(bytes.len(), |i| pos + i + 2);
+            }
+            [b'"', ..] => {
+                let close =
+                    find_closing_quote(&input[pos + 1..], '"').map_or(bytes.len(), |i| pos + 1 + i);
+                if expect_value {
+                    values.push(pos + 1..close);
+                }
+                pos = close + 1;
+            }
+            [b'=', ..] => {
+                expect_value = true;
+                pos += 1;
+            }
+            [b';', ..] => {
+                expect_value = false;
+                pos += 1;
+            }
+            _ => pos += 1,
+        }
+    }
+    values
+}
+
+/// Values of `key=value`, `key: value` and `key value` lines, including
+/// their `\` continuation lines.
+fn properties_values(input: &str) -> Vec<Range<usize>> {
+    let mut values = Vec::new();
+    let mut continued: Option<usize> = None;
+
+    for (offset, line) in lines_with_offsets(input) {
+        let line_end = offset + line.len();
+        let continues = (line.len() - line.trim_end_matches('\\').len()) % 2 == 1;
+        if let Some(start) = continued {
+            continued = continues.then_some(start);
+            if !continues {
+                values.push(start..line_end);
+            }
+            continue;
+        }
+
+        let trimmed = line.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with(['#', '!']) {
+            continue;
+        }
+        let key_start = offset + line.len() - trimmed.len();
+        let key_len = key_length(trimmed);
+        let rest = &trimmed[key_len..];
+        let separator = rest.trim_start();
+        let value = separator
+            .strip_prefix(['=', ':'])
+            .unwrap_or(separator)
+            .trim_start();
+        let value_start = key_start + key_len + (rest.len() - value.len());
+
+        if continues {
+            continued = Some(value_start);
+        } else {
+            values.push(value_start..line_end);
+        }
+    }
+    if let Some(start) = continued {
+        values.push(start..input.len());
+    }
+    values
+}
+
+/// Length of a `.properties` key: up to the first unescaped separator.
+fn key_length(line: &str) -> usize {
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if matches!(c, '=' | ':') || c.is_whitespace() {
+            return i;
+        }
+    }
+    line.len()
+}
+
+/// Contents of the `target` elements of translatable units.
+fn xliff_targets(input: &str) -> Vec<Range<usize>> {
+    let units: Vec<_> = RE_XLIFF_UNIT
+        .captures_iter(input)
+        .map(|cap| {
+            (
+                cap.get(0).unwrap().start(),
+                RE_NOT_TRANSLATABLE.is_match(&cap[1]),
+            )
+        })
+        .collect();
+
+    let mut targets = Vec::new();
+    for cap in RE_XLIFF_TARGET.captures_iter(input) {
+        let attrs = cap.get(1).unwrap().as_str();
+        let tag = cap.get(0).unwrap();
+        if attrs.ends_with('/') || RE_NOT_TRANSLATABLE.is_match(attrs) {
+            continue;
+        }
+        let unit = units.partition_point(|(start, _)| *start < tag.start());
+        if unit > 0 && units[unit - 1].1 {
+            continue;
+        }
+        if let Some(len) = input[tag.end()..].find("</target>") {
+            targets.push(tag.end()..tag.end() + len);
+        }
+    }
+    targets
+}
+
+/// Shields the syntax of ICU messages: `{name}` arguments, the
+/// `{count, plural, ...}` headers, branch selectors, braces and `#`.
+/// The text of the branches stays convertible.
+fn mask_icu_syntax(text: &str, start: usize, mask: &mut [bool]) {
+    static RE_ARGUMENT: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"^\{\s*\w+\s*(?:\}|,\s*(?:plural|select|selectordinal)\s*,)").unwrap()
+    });
+
+    // Whether each open brace is an argument (between branches) or a branch
+    let mut open: Vec<bool> = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let c = rest.chars().next().unwrap();
+        let in_argument = open.last() == Some(&true);
+
+        let shield = if in_argument {
+            match c {
+                '{' => open.push(false),
+                '}' => {
+                    open.pop();
+                }
+                _ => {}
+            }
+            c.len_utf8()
+        } else if let Some(m) = RE_ARGUMENT.find(rest) {
+            if !m.as_str().ends_with('}') {
+                open.push(true);
+            }
+            m.end()
+        } else if c == '}' && !open.is_empty() {
+            open.pop();
+            1
+        } else if c == '#' && !open.is_empty() {
+            1
+        } else {
+            pos += c.len_utf8();
+            continue;
+        };
+        mask[start + pos..start + pos + shield].fill(true);
+        pos += shield;
+    }
+}
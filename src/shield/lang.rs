@@ -0,0 +1,133 @@
+use std::ops::Range;
+
+/// HTML elements that never have content or an end tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Script subtag written for converted text.
+const LATIN_SCRIPT: &str = "Latn";
+
+/// How an element's BCP 47 language tag relates to conversion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Lang {
+    /// `uz`, `uz-UZ`, `uz-Cyrl`, `uz-Cyrl-UZ`, ... — converted and relabelled.
+    Uzbek,
+    /// `uz-Latn` — already Latin, converted but left as is.
+    UzbekLatin,
+    /// Any other language, or Uzbek in another script (`uz-Arab`) — never
+    /// converted.
+    Other,
+}
+
+impl Lang {
+    /// Classifies a language tag. Returns `None` for an empty (unknown) tag.
+    pub(super) fn classify(tag: &str) -> Option<Self> {
+        let mut subtags = tag.trim().split(['-', '_']);
+        let primary = subtags.next().filter(|p| !p.is_empty())?;
+        if !primary.eq_ignore_ascii_case("uz") {
+            return Some(Self::Other);
+        }
+        match subtags.next() {
+            Some(script) if script.eq_ignore_ascii_case(LATIN_SCRIPT) => Some(Self::UzbekLatin),
+            Some(script) if script.eq_ignore_ascii_case("Cyrl") => Some(Self::Uzbek),
+            Some(script) if is_script(script) => Some(Self::Other),
+            _ => Some(Self::Uzbek),
+        }
+    }
+}
+
+/// Edits that relabel the Uzbek tags in a language tag list
+/// (`uz-Cyrl, ru`) as `uz-Latn`, relative to `offset`.
+/// Each edit replaces a (possibly empty) range with a fixed string.
+pub(super) fn latin_edits(value: &str, offset: usize) -> Vec<(Range<usize>, &'static str)> {
+    let mut edits = Vec::new();
+    let mut item_start = 0;
+
+    for item in value.split(',') {
+        let lead = item.len() - item.trim_start().len();
+        let tag = item.trim();
+        let tag_start = offset + item_start + lead;
+        item_start += item.len() + 1;
+
+        if Lang::classify(tag) != Some(Lang::Uzbek) {
+            continue;
+        }
+        // `uz` is two bytes; a script subtag, if any, follows the separator
+        let script = tag.get(3..).and_then(|rest| rest.split(['-', '_']).next());
+        match script {
+            Some(s) if is_script(s) => {
+                edits.push((tag_start + 3..tag_start + 7, LATIN_SCRIPT));
+            }
+            _ => edits.push((tag_start + 2..tag_start + 2, "-Latn")),
+        }
+    }
+    edits
+}
+
+/// Whether a subtag is a four-letter script subtag.
+fn is_script(subtag: &str) -> bool {
+    subtag.len() == 4 && subtag.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// One open element and whether its content is converted.
+struct Scope {
+    name: String,
+    convert: bool,
+}
+
+/// Tracks the `lang`/`xml:lang` inheritance tree of an HTML or XML document.
+pub(super) struct LangTracker {
+    stack: Vec<Scope>,
+    /// Whether elements without a known language are converted.
+    untagged: bool,
+    xml: bool,
+}
+
+impl LangTracker {
+    pub(super) fn new(untagged: bool, xml: bool) -> Self {
+        Self {
+            stack: Vec::new(),
+            untagged,
+            xml,
+        }
+    }
+
+    /// Whether text at the current position is converted.
+    pub(super) fn converting(&self) -> bool {
+        self.stack
+            .last()
+            .map_or(self.untagged, |scope| scope.convert)
+    }
+
+    /// Opens an element with the given language tag (if any) and returns
+    /// whether the element itself is converted.
+    pub(super) fn enter(&mut self, name: &str, lang: Option<&str>, self_closing: bool) -> bool {
+        let convert = match lang.map(Lang::classify) {
+            // Without a tag the language is inherited from the parent
+            None => self.converting(),
+            Some(Some(Lang::Uzbek | Lang::UzbekLatin)) => true,
+            Some(Some(Lang::Other)) => false,
+            // `lang=""` explicitly marks the language as unknown
+            Some(None) => self.untagged,
+        };
+
+        let empty = self_closing || (!self.xml && VOID_ELEMENTS.contains(&name));
+        if !empty {
+            self.stack.push(Scope {
+                name: name.to_string(),
+                convert,
+            });
+        }
+        convert
+    }
+
+    /// Closes the innermost open element with this name, along with any
+    /// elements left unclosed inside it. Stray end tags are ignored.
+    pub(super) fn leave(&mut self, name: &str) {
+        if let Some(pos) = self.stack.iter().rposition(|scope| scope.name == name) {
+            self.stack.truncate(pos);
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use super::lang::Lang;
+use crate::types::LatexArg;
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Environments whose whole body is math.
+const MATH_ENVIRONMENTS: &[&str] = &[
+    "equation",
+    "equation*",
+    "align",
+    "align*",
+    "alignat",
+    "alignat*",
+    "flalign",
+    "flalign*",
+    "gather",
+    "gather*",
+    "multline",
+    "multline*",
+    "eqnarray",
+    "eqnarray*",
+    "math",
+    "displaymath",
+];
+
+/// Environments switching the language of their body (`{lang}` argument).
+const LANGUAGE_ENVIRONMENTS: &[&str] = &["otherlanguage", "otherlanguage*"];
+
+/// An argument found after a command name.
+#[derive(Debug)]
+pub(super) struct ParsedArg {
+    pub kind: LatexArg,
+    /// Range including the delimiters.
+    pub outer: Range<usize>,
+    /// Range of the content.
+    pub inner: Range<usize>,
+}
+
+impl ParsedArg {
+    pub(super) fn converted(&self) -> bool {
+        matches!(
+            self.kind,
+            LatexArg::Required(true) | LatexArg::Optional(true)
+        )
+    }
+}
+
+/// Content of the `n`-th required argument.
+pub(super) fn required<'a>(input: &'a str, args: &[ParsedArg], n: usize) -> Option<&'a str> {
+    args.iter()
+        .filter(|a| matches!(a.kind, LatexArg::Required(_) | LatexArg::Rest))
+        .nth(n)
+        .map(|a| &input[a.inner.clone()])
+}
+
+/// Content of the first optional argument.
+pub(super) fn optional<'a>(input: &'a str, args: &[ParsedArg]) -> Option<&'a str> {
+    args.iter()
+        .find(|a| matches!(a.kind, LatexArg::Optional(_)))
+        .map(|a| &input[a.inner.clone()])
+}
+
+/// End of the command name starting at `pos` (just after the backslash).
+pub(super) fn command_end(input: &str, pos: usize) -> usize {
+    let bytes = input.as_bytes();
+    let mut end = pos;
+    while end < bytes.len() && bytes[end].is_ascii_alphabetic() {
+        end += 1;
+    }
+    end
+}
+
+/// Matches the arguments described by `spec` after a command name.
+/// Missing optional arguments are skipped; a missing required argument
+/// ends the match.
+pub(super) fn parse_args(input: &str, pos: usize, spec: &[LatexArg]) -> Vec<ParsedArg> {
+    let mut args = Vec::new();
+    let mut curr = pos;
+
+    for &kind in spec {
+        match kind {
+            LatexArg::Rest => {
+                while let Some(arg) = group_at(input, skip_whitespace(input, curr), None) {
+                    curr = arg.outer.end;
+                    args.push(arg);
+                }
+            }
+            LatexArg::Required(_) | LatexArg::Optional(_) => {
+                let open = if matches!(kind, LatexArg::Required(_)) {
+                    b'{'
+                } else {
+                    b'['
+                };
+                match group_at(input, skip_whitespace(input, curr), Some(open)) {
+                    Some(mut arg) => {
+                        arg.kind = kind;
+                        curr = arg.outer.end;
+                        args.push(arg);
+                    }
+                    None if open == b'[' => {}
+                    None => break,
+                }
+            }
+        }
+    }
+    args
+}
+
+fn skip_whitespace(input: &str, mut pos: usize) -> usize {
+    let bytes = input.as_bytes();
+    while pos < bytes.len() && bytes[pos].is_ascii_whitespace() {
+        pos += 1;
+    }
+    pos
+}
+
+/// Parses a balanced `{...}` or `[...]` group at `pos`.
+/// Brackets only close at brace depth zero (`[title={a]b}]`).
+fn group_at(input: &str, pos: usize, open: Option<u8>) -> Option<ParsedArg> {
+    let bytes = input.as_bytes();
+    let first = *bytes.get(pos)?;
+    if !matches!(first, b'{' | b'[') || open.is_some_and(|o| o != first) {
+        return None;
+    }
+
+    let mut braces = 0usize;
+    let mut i = pos + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'{' => braces += 1,
+            b'}' if braces == 0 && first == b'{' => break,
+            b'}' => braces = braces.saturating_sub(1),
+            b']' if braces == 0 && first == b'[' => break,
+            _ => {}
+        }
+        i += 1;
+    }
+    if i >= bytes.len() {
+        return None;
+    }
+
+    Some(ParsedArg {
+        kind: LatexArg::Rest,
+        outer: pos..i + 1,
+        inner: pos + 1..i,
+    })
+}
+
+/// Finds the end of a math region whose content starts at `pos` and the
+/// converted arguments (`\text{...}`) inside it.
+/// Returns the offset after `closer` and the text windows.
+pub(super) fn math(
+    input: &str,
+    pos: usize,
+    closer: &str,
+    commands: &HashMap<String, Vec<LatexArg>>,
+) -> Option<(usize, Vec<Range<usize>>)> {
+    let bytes = input.as_bytes();
+    let mut windows = Vec::new();
+    let mut i = pos;
+
+    while i < bytes.len() {
+        if bytes[i..].starts_with(closer.as_bytes()) {
+            return Some((i + closer.len(), windows));
+        }
+        if bytes[i] != b'\\' {
+            i += 1;
+            continue;
+        }
+
+        let name_end = command_end(input, i + 1);
+        if name_end == i + 1 {
+            // Escaped character such as `\$` or `\\`
+            i += 2;
+            continue;
+        }
+        let name = &input[i + 1..name_end];
+        i = name_end;
+
+        if let Some(spec) = commands.get(name)
+            && spec
+                .iter()
+                .any(|a| matches!(a, LatexArg::Required(true) | LatexArg::Optional(true)))
+        {
+            let args = parse_args(input, name_end, spec);
+            windows.extend(
+                args.iter()
+                    .filter(|a| a.converted())
+                    .map(|a| a.inner.clone()),
+            );
+            if let Some(last) = args.last() {
+                i = last.outer.end;
+            }
+        }
+    }
+    None
+}
+
+pub(super) fn is_math_environment(name: &str) -> bool {
+    MATH_ENVIRONMENTS.contains(&name)
+}
+
+pub(super) fn is_language_environment(name: &str) -> bool {
+    LANGUAGE_ENVIRONMENTS.contains(&name)
+}
+
+/// Babel and polyglossia language names that mean Uzbek.
+fn is_uzbek(name: &str) -> bool {
+    let name = name.trim();
+    name.eq_ignore_ascii_case("uzbek")
+        || matches!(Lang::classify(name), Some(Lang::Uzbek | Lang::UzbekLatin))
+}
+
+/// Language switches of a LaTeX document.
+pub(super) struct Languages {
+    /// Whether the main language is converted.
+    main: bool,
+    /// Scoped switches: (end offset, converted). `otherlanguage`
+    /// environments run until their `\end`, marked by `usize::MAX`.
+    scopes: Vec<(usize, bool)>,
+}
+
+impl Default for Languages {
+    fn default() -> Self {
+        Self {
+            main: true,
+            scopes: Vec::new(),
+        }
+    }
+}
+
+impl Languages {
+    /// Whether text at `pos` is converted.
+    pub(super) fn converting(&mut self, pos: usize) -> bool {
+        while self.scopes.last().is_some_and(|&(end, _)| end <= pos) {
+            self.scopes.pop();
+        }
+        self.scopes
+            .last()
+            .map_or(self.main, |&(_, convert)| convert)
+    }
+
+    /// `\usepackage[...]{babel}`: the last option (or `main=`) is the main
+    /// language. Documents that load babel without Uzbek (e.g. for Russian
+    /// hyphenation) are left alone.
+    pub(super) fn babel(&mut self, options: &str) {
+        let declared: Vec<&str> = options
+            .split(',')
+            .map(str::trim)
+            .filter(|o| !o.is_empty() && (!o.contains('=') || o.starts_with("main=")))
+            .collect();
+
+        if !declared
+            .iter()
+            .any(|o| is_uzbek(o.trim_start_matches("main=")))
+        {
+            return;
+        }
+        let main = declared
+            .iter()
+            .find_map(|o| o.strip_prefix("main="))
+            .or(declared.last().copied());
+        if let Some(main) = main {
+            self.main = is_uzbek(main);
+        }
+    }
+
+    /// `\selectlanguage{...}` and polyglossia's `\setmainlanguage{...}`.
+    pub(super) fn select(&mut self, name: &str) {
+        self.main = is_uzbek(name);
+    }
+
+    /// `\foreignlanguage{lang}{...}` up to `end`.
+    pub(super) fn scope(&mut self, end: usize, name: &str) {
+        self.scopes.push((end, is_uzbek(name)));
+    }
+
+    /// `\begin{otherlanguage}{lang}`.
+    pub(super) fn begin(&mut self, name: &str) {
+        self.scopes.push((usize::MAX, is_uzbek(name)));
+    }
+
+    /// `\end{otherlanguage}`.
+    pub(super) fn end(&mut self) {
+        if let Some(pos) = self.scopes.iter().rposition(|&(end, _)| end == usize::MAX) {
+            self.scopes.truncate(pos);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use pulldown_cmark::{Event, LinkType, Options, Parser, Tag, TagEnd};
+use std::collections::HashSet;
+use std::ops::Range;
+
+/// Builds the Markdown part of the pre-mask using a CommonMark parser.
+///
+/// Ranges are applied in two passes: first every structural span (links,
+/// reference definitions, code, math, anchors) is shielded, then the
+/// human-readable windows inside them (link text, alt text, titles) are
+/// reopened for conversion.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    let mut shielded: Vec<Range<usize>> = Vec::new();
+    let mut windows: Vec<Range<usize>> = Vec::new();
+    let mut shortcut_labels: HashSet<String> = HashSet::new();
+
+    // Depth of containers whose text must stay untouched (code blocks,
+    // autolinks, metadata blocks).
+    let mut opaque_depth = 0usize;
+    // One entry per open link or image: whether it raised `opaque_depth`.
+    let mut link_stack: Vec<bool> = Vec::new();
+
+    let mut parser = Parser::new_ext(input, options()).into_offset_iter();
+
+    for (event, range) in parser.by_ref() {
+        match event {
+            Event::Start(Tag::CodeBlock(_)) | Event::Start(Tag::MetadataBlock(_)) => {
+                shielded.push(range);
+                opaque_depth += 1;
+            }
+            Event::End(TagEnd::CodeBlock) | Event::End(TagEnd::MetadataBlock(_)) => {
+                opaque_depth = opaque_depth.saturating_sub(1);
+            }
+            Event::Start(Tag::Link {
+                link_type,
+                title,
+                id,
+                ..
+            })
+            | Event::Start(Tag::Image {
+                link_type,
+                title,
+                id,
+                ..
+            }) => {
+                shielded.push(range.clone());
+                let is_autolink = matches!(link_type, LinkType::Autolink | LinkType::Email);
+                link_stack.push(is_autolink);
+                if is_autolink {
+                    opaque_depth += 1;
+                } else {
+                    if let Some(window) = find_title(input, &range, &title) {
+                        windows.push(window);
+                    }
+                    // The link text doubles as the reference label, so the
+                    // definition label must be converted the same way.
+                    if matches!(link_type, LinkType::Shortcut | LinkType::Collapsed) {
+                        shortcut_labels.insert(id.to_lowercase());
+                    }
+                }
+            }
+            Event::End(TagEnd::Link) | Event::End(TagEnd::Image) => {
+                // The text of an autolink is the URL itself, so it never opens a window.
+                let was_autolink = link_stack.pop().unwrap_or(false);
+                opaque_depth = opaque_depth.saturating_sub(usize::from(was_autolink));
+            }
+            Event::Start(Tag::Heading {
+                id, classes, attrs, ..
+            }) if id.is_some() || !classes.is_empty() || !attrs.is_empty() => {
+                if let Some(anchor) = find_heading_attributes(input, &range) {
+                    shielded.push(anchor);
+                }
+            }
+            Event::Start(Tag::FootnoteDefinition(_)) => {
+                if let Some(label_end) = input[range.clone()].find("]:") {
+                    shielded.push(range.start..range.start + label_end + 2);
+                }
+            }
+            Event::Text(_) if opaque_depth == 0 => windows.push(range),
+            Event::Code(_)
+            | Event::InlineMath(_)
+            | Event::DisplayMath(_)
+            | Event::FootnoteReference(_)
+            | Event::TaskListMarker(_) => shielded.push(range),
+            _ => {}
+        }
+    }
+
+    for (label, def) in parser.reference_definitions().iter() {
+        let span = def.span.clone();
+        shielded.push(span.clone());
+
+        if shortcut_labels.contains(&label.to_lowercase()) {
+            let slice = &input[span.clone()];
+            if let (Some(open), Some(close)) = (slice.find('['), slice.find("]:"))
+                && open < close
+            {
+                windows.push(span.start + open + 1..span.start + close);
+            }
+        }
+        if let Some(title) = &def.title
+            && let Some(window) = find_title(input, &span, title)
+        {
+            windows.push(window);
+        }
+    }
+
+    for range in shielded {
+        fill(mask, range, true);
+    }
+    for range in windows {
+        fill(mask, range, false);
+    }
+}
+
+fn options() -> Options {
+    Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS
+        | Options::ENABLE_HEADING_ATTRIBUTES
+        | Options::ENABLE_MATH
+}
+
+fn fill(mask: &mut [bool], range: Range<usize>, value: bool) {
+    if let Some(slice) = mask.get_mut(range) {
+        slice.fill(value);
+    }
+}
+
+/// Locates a link title inside the source span of its link or definition.
+/// Titles containing escapes or entities are not found verbatim and stay shielded.
+fn find_title(input: &str, span: &Range<usize>, title: &str) -> Option<Range<usize>> {
+    if title.is_empty() {
+        return None;
+    }
+    let slice = &input[span.clone()];
+    let pos = slice.rfind(title)?;
+    let before = slice[..pos].chars().next_back()?;
+    if !matches!(before, '"' | '\'' | '(') {
+        return None;
+    }
+    Some(span.start + pos..span.start + pos + title.len())
+}
+
+/// Finds the trailing `{#id .class key=value}` block of a heading line.
+fn find_heading_attributes(input: &str, range: &Range<usize>) -> Option<Range<usize>> {
+    let slice = &input[range.clone()];
+    let line = slice.lines().next()?;
+    let trimmed = line.trim_end().trim_end_matches('#').trim_end();
+    if !trimmed.ends_with('}') {
+        return None;
+    }
+    let open = trimmed.rfind('{')?;
+    Some(range.start + open..range.start + trimmed.len())
+}
@@ -0,0 +1,112 @@
+use super::lang::latin_edits;
+use super::office::{RE_TAG, TextElements, text_nodes};
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+static RE_UZBEK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\sfo:language="uz""#).unwrap());
+
+/// Script attributes of Uzbek text properties.
+static RE_SCRIPT_ATTR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\s(?P<name>fo:script|style:script-type)="(?P<value>[^"]*)""#).unwrap()
+});
+
+static RE_LANGUAGE_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"\sstyle:rfc-language-tag="(?P<value>[^"]*)""#).unwrap());
+
+/// Elements whose text is converted as one unit: paragraphs and headings,
+/// and the title, subject, description and keywords in `meta.xml`.
+fn is_paragraph(name: &str) -> bool {
+    matches!(
+        name,
+        "text:p" | "text:h" | "dc:title" | "dc:subject" | "dc:description" | "meta:keyword"
+    )
+}
+
+/// Spacing elements that end a word: text on either side is converted apart.
+fn is_break(name: &str) -> bool {
+    matches!(
+        name,
+        "text:s" | "text:tab" | "text:line-break" | "text:soft-page-break"
+    )
+}
+
+/// Elements whose text is never converted: deleted text kept for change
+/// tracking, note numbers and annotation authors and dates.
+fn is_skipped(name: &str) -> bool {
+    matches!(
+        name,
+        "text:tracked-changes" | "text:note-citation" | "dc:creator" | "dc:date"
+    )
+}
+
+/// Text nodes of an OpenDocument part grouped by paragraph, across
+/// `text:span`, `text:a` and other inline elements.
+pub(crate) fn paragraphs(input: &str) -> Vec<Vec<Range<usize>>> {
+    text_nodes(
+        input,
+        &TextElements {
+            paragraph: is_paragraph,
+            spacing: is_break,
+            skipped: is_skipped,
+        },
+    )
+}
+
+/// Edits that mark Uzbek text properties as written in the Latin script,
+/// adding `fo:script` where it is missing, and relabel `uz-Cyrl` language tags in styles and `dc:language`.
+pub(crate) fn language_edits(input: &str) -> Vec<(Range<usize>, &'static str)> {
+    let mut edits = Vec::new();
+    let mut language_start = None;
+
+    for cap in RE_TAG.captures_iter(input) {
+        let tag = cap.get(0).unwrap();
+        if &cap["name"] == "dc:language" {
+            match (cap["close"].is_empty(), language_start.take()) {
+                (true, _) => language_start = Some(tag.end()),
+                (false, Some(start)) => {
+                    edits.extend(latin_edits(&input[start..tag.start()], start));
+                }
+                (false, None) => {}
+            }
+            continue;
+        }
+
+        let attrs = cap.name("attrs").unwrap();
+        for attr in RE_LANGUAGE_TAG.captures_iter(attrs.as_str()) {
+            let value = attr.name("value").unwrap();
+            edits.extend(latin_edits(value.as_str(), attrs.start() + value.start()));
+        }
+        // Uzbek in the Arabic script is a complex-script language
+        let Some(uzbek) = RE_UZBEK.find(attrs.as_str()) else {
+            continue;
+        };
+        if attrs.as_str().contains(r#"fo:script="Arab""#) {
+            continue;
+        }
+        if !attrs.as_str().contains("fo:script=") {
+            let end = attrs.start() + uzbek.end();
+            edits.push((end..end, r#" fo:script="Latn""#));
+        }
+        for attr in RE_SCRIPT_ATTR.captures_iter(attrs.as_str()) {
+            let value = attr.name("value").unwrap();
+            let latin = match &attr["name"] {
+                "fo:script" => "Latn",
+                _ => "latin",
+            };
+            if value.as_str() != latin {
+                let start = attrs.start() + value.start();
+                edits.push((start..start + value.len(), latin));
+            }
+        }
+    }
+    edits
+}
+
+/// Shields an OpenDocument part except for the text of its paragraphs.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    mask.fill(true);
+    for run in paragraphs(input).into_iter().flatten() {
+        mask[run].fill(false);
+    }
+}
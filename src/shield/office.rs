@@ -0,0 +1,161 @@
+use super::lang::latin_edits;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Start and end tags of an XML part; comments and declarations are not matched.
+pub(super) static RE_TAG: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"<(?P<close>/?)(?P<name>[A-Za-z_][\w.:-]*)(?P<attrs>[^>]*?)(?P<empty>/?)>").unwrap()
+});
+
+/// Language attributes of run properties: `<w:lang w:val="uz-Cyrl-UZ"/>`
+/// in WordprocessingML, `<a:rPr lang="uz-Cyrl-UZ">` in DrawingML.
+static RE_LANG_ATTR: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"\s(?:w:val|w:eastAsia|w:bidi|lang|altLang)="(?P<value>[^"]*)""#).unwrap()
+});
+
+/// Elements that hold the text of one paragraph: Word and DrawingML
+/// paragraphs, shared and inline spreadsheet strings.
+fn is_paragraph(name: &str) -> bool {
+    matches!(name, "w:p" | "a:p" | "si" | "is" | "x:si" | "x:is")
+}
+
+/// Elements whose content is run text. Deleted text (`w:delText`), field
+/// codes (`w:instrText`) and math (`m:t`) are not.
+fn is_text(name: &str) -> bool {
+    matches!(name, "w:t" | "a:t" | "t" | "x:t")
+}
+
+/// Elements carrying a language in their attributes.
+fn has_lang(name: &str) -> bool {
+    matches!(name, "w:lang" | "a:rPr" | "a:endParaRPr" | "a:defRPr")
+}
+
+/// Text runs of an Office Open XML part grouped by paragraph, so that a
+/// word split across differently formatted runs is converted as one.
+/// Phonetic guides (`rPh`) of spreadsheet strings are skipped.
+pub(crate) fn paragraphs(input: &str) -> Vec<Vec<Range<usize>>> {
+    let mut done = Vec::new();
+    let mut open: Vec<Vec<Range<usize>>> = Vec::new();
+    let mut text_start = None;
+    let mut phonetic = 0usize;
+
+    for cap in RE_TAG.captures_iter(input) {
+        let tag = cap.get(0).unwrap();
+        let name = &cap["name"];
+        let closing = !cap["close"].is_empty();
+        let empty = !cap["empty"].is_empty();
+
+        if matches!(name, "rPh" | "x:rPh") && !empty {
+            if closing {
+                phonetic = phonetic.saturating_sub(1);
+            } else {
+                phonetic += 1;
+            }
+        } else if is_paragraph(name) && !empty {
+            if closing {
+                done.extend(open.pop().filter(|runs| !runs.is_empty()));
+            } else {
+                open.push(Vec::new());
+            }
+        } else if is_text(name) && phonetic == 0 {
+            if closing {
+                if let Some(start) = text_start.take() {
+                    let run = start..tag.start();
+                    // A run outside any paragraph stands on its own
+                    match open.last_mut() {
+                        Some(runs) => runs.push(run),
+                        None => done.push(vec![run]),
+                    }
+                }
+            } else if !empty {
+                text_start = Some(tag.end());
+            }
+        }
+    }
+    done
+}
+
+/// Which elements of an XML vocabulary hold text that is converted.
+pub(super) struct TextElements {
+    /// Elements whose text nodes are converted as one unit.
+    pub paragraph: fn(&str) -> bool,
+    /// Empty elements that stand for spacing and so end a word.
+    pub spacing: fn(&str) -> bool,
+    /// Elements whose content is never converted. A word is not joined
+    /// across them either.
+    pub skipped: fn(&str) -> bool,
+}
+
+/// Text nodes of the paragraphs of a document whose text is element
+/// content (OpenDocument, FictionBook), grouped so that a word split by
+/// inline formatting elements is converted as one.
+pub(super) fn text_nodes(input: &str, elements: &TextElements) -> Vec<Vec<Range<usize>>> {
+    let mut done = Vec::new();
+    let mut open: Vec<Vec<Range<usize>>> = Vec::new();
+    let mut skipped = 0usize;
+    let mut text_start = 0;
+
+    for cap in RE_TAG.captures_iter(input) {
+        let tag = cap.get(0).unwrap();
+        if skipped == 0
+            && let Some(runs) = open.last_mut()
+            && text_start < tag.start()
+        {
+            runs.push(text_start..tag.start());
+        }
+        text_start = tag.end();
+
+        let name = &cap["name"];
+        let closing = !cap["close"].is_empty();
+        if !cap["empty"].is_empty() {
+            if (elements.spacing)(name)
+                && let Some(runs) = open.last_mut()
+            {
+                done.push(std::mem::take(runs));
+            }
+        } else if (elements.skipped)(name) {
+            if let Some(runs) = open.last_mut() {
+                done.push(std::mem::take(runs));
+            }
+            skipped = if closing {
+                skipped.saturating_sub(1)
+            } else {
+                skipped + 1
+            };
+        } else if (elements.paragraph)(name) {
+            if closing {
+                done.extend(open.pop());
+            } else {
+                open.push(Vec::new());
+            }
+        }
+    }
+    done.retain(|runs| !runs.is_empty());
+    done
+}
+
+/// Edits that relabel the Uzbek proofing and display languages of runs
+/// as `uz-Latn`.
+pub(crate) fn language_edits(input: &str) -> Vec<(Range<usize>, &'static str)> {
+    let mut edits = Vec::new();
+    for cap in RE_TAG.captures_iter(input) {
+        if !cap["close"].is_empty() || !has_lang(&cap["name"]) {
+            continue;
+        }
+        let attrs = cap.name("attrs").unwrap();
+        for attr in RE_LANG_ATTR.captures_iter(attrs.as_str()) {
+            let value = attr.name("value").unwrap();
+            edits.extend(latin_edits(value.as_str(), attrs.start() + value.start()));
+        }
+    }
+    edits
+}
+
+/// Shields an Office Open XML part except for the text of its runs.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    mask.fill(true);
+    for run in paragraphs(input).into_iter().flatten() {
+        mask[run].fill(false);
+    }
+}
@@ -0,0 +1,185 @@
+use super::data::{find_closing_quote, lines_with_offsets};
+use super::source::{RE_ESCAPE, RE_PLACEHOLDER};
+use std::ops::Range;
+
+/// Plural-Forms of a catalog created from a template that was never filled in.
+const TEMPLATE_PLURAL_FORMS: &str = "nplurals=INTEGER; plural=EXPRESSION;";
+/// CLDR plural rule for Uzbek: one and other.
+const UZBEK_PLURAL_FORMS: &str = "nplurals=2; plural=(n != 1);";
+
+/// Keyword of a field of a catalog entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    IdPlural,
+    Str,
+}
+
+#[derive(Default)]
+struct Entry {
+    fuzzy: bool,
+    /// Content ranges of the string lines of each field.
+    fields: Vec<(Field, Vec<Range<usize>>)>,
+}
+
+impl Entry {
+    fn has(&self, field: Field) -> bool {
+        self.fields.iter().any(|(f, _)| *f == field)
+    }
+
+    /// The entry with an empty `msgid` and no `msgctxt` holds the catalog headers.
+    fn is_header(&self) -> bool {
+        !self.has(Field::Context)
+            && self
+                .fields
+                .iter()
+                .any(|(f, lines)| *f == Field::Id && lines.iter().all(Range::is_empty))
+    }
+}
+
+/// Shields a PO/POT catalog except for its translations (`msgstr`,
+/// `msgstr[n]`), and the source strings too when `msgid` is set. Fuzzy
+/// entries are left alone unless `fuzzy` is set. Escapes and c-format and
+/// python-format placeholders stay shielded.
+/// Returns the header values rewritten for a Latin catalog.
+pub(super) fn build_mask(
+    input: &str,
+    msgid: bool,
+    fuzzy: bool,
+    mask: &mut [bool],
+) -> Vec<(Range<usize>, &'static str)> {
+    mask.fill(true);
+    let mut replacements = Vec::new();
+    let mut entry = Entry::default();
+
+    let mut flush = |entry: &mut Entry| {
+        let entry = std::mem::take(entry);
+        if entry.is_header() {
+            replacements.extend(header_edits(input, &entry));
+            return;
+        }
+        if entry.fuzzy && !fuzzy {
+            return;
+        }
+        for (field, lines) in &entry.fields {
+            let convert = match field {
+                Field::Str => true,
+                Field::Id | Field::IdPlural => msgid,
+                Field::Context => false,
+            };
+            if convert {
+                for range in lines {
+                    unmask_string(input, range.clone(), mask);
+                }
+            }
+        }
+    };
+
+    for (offset, line) in lines_with_offsets(input) {
+        let trimmed = line.trim_start();
+        let indent = offset + line.len() - trimmed.len();
+
+        if trimmed.is_empty() {
+            flush(&mut entry);
+            continue;
+        }
+        if let Some(comment) = trimmed.strip_prefix('#') {
+            // A comment after the translation starts the next entry
+            if entry.has(Field::Str) {
+                flush(&mut entry);
+            }
+            if let Some(flags) = comment.strip_prefix(',') {
+                entry.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            }
+            continue;
+        }
+        if trimmed.starts_with('"') {
+            if let Some((_, lines)) = entry.fields.last_mut() {
+                lines.extend(string_content(trimmed, indent));
+            }
+            continue;
+        }
+
+        let (keyword, rest) = trimmed.split_at(trimmed.find([' ', '\t']).unwrap_or(trimmed.len()));
+        let field = match keyword {
+            "msgctxt" => Field::Context,
+            "msgid" => Field::Id,
+            "msgid_plural" => Field::IdPlural,
+            k if k.starts_with("msgstr") => Field::Str,
+            _ => continue,
+        };
+        if matches!(field, Field::Context | Field::Id) && entry.has(Field::Str) {
+            flush(&mut entry);
+        }
+        let value = rest.trim_start();
+        let value_offset = offset + line.len() - value.len();
+        entry.fields.push((
+            field,
+            string_content(value, value_offset).into_iter().collect(),
+        ));
+    }
+    flush(&mut entry);
+    replacements
+}
+
+/// Content range of the quoted string at the start of `text`.
+fn string_content(text: &str, offset: usize) -> Option<Range<usize>> {
+    let body = text.strip_prefix('"')?;
+    let close = find_closing_quote(body, '"')?;
+    Some(offset + 1..offset + 1 + close)
+}
+
+fn unmask_string(input: &str, range: Range<usize>, mask: &mut [bool]) {
+    let start = range.start;
+    let text = &input[range.clone()];
+    mask[range].fill(false);
+    for re in [&*RE_PLACEHOLDER, &*RE_ESCAPE] {
+        for m in re.find_iter(text) {
+            mask[start + m.start()..start + m.end()].fill(true);
+        }
+    }
+}
+
+/// `Language:` is relabelled with the Latin locale and the template
+/// `Plural-Forms` placeholder is filled in with the Uzbek rule.
+fn header_edits(input: &str, header: &Entry) -> Vec<(Range<usize>, &'static str)> {
+    let mut edits = Vec::new();
+    let lines = header
+        .fields
+        .iter()
+        .filter(|(f, _)| *f == Field::Str)
+        .flat_map(|(_, lines)| lines);
+    for range in lines {
+        let text = &input[range.clone()];
+        let Some((name, value)) = text.split_once(':') else {
+            continue;
+        };
+        let value_text = value.strip_suffix("\\n").unwrap_or(value).trim();
+        let value_start = range.start + name.len() + 1 + (value.len() - value.trim_start().len());
+        let value_range = value_start..value_start + value_text.len();
+
+        let edit = match name.trim() {
+            "Language" => latin_language(value_text),
+            "Plural-Forms" if value_text == TEMPLATE_PLURAL_FORMS => Some(UZBEK_PLURAL_FORMS),
+            _ => None,
+        };
+        if let Some(edit) = edit {
+            edits.push((value_range, edit));
+        }
+    }
+    edits
+}
+
+/// Latin locale matching a Cyrillic (or unset) Uzbek catalog language.
+fn latin_language(language: &str) -> Option<&'static str> {
+    let latin = match language {
+        "" | "uz" | "uz@cyrillic" => "uz@latin",
+        "uz_UZ" | "uz_UZ@cyrillic" => "uz_UZ@latin",
+        "uz_Cyrl" => "uz_Latn",
+        "uz-Cyrl" => "uz-Latn",
+        "uz_Cyrl_UZ" => "uz_Latn_UZ",
+        _ => return None,
+    };
+    Some(latin)
+}
@@ -0,0 +1,492 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// A run of RTF text: literal characters, `\'hh` code-page escapes and
+/// `\uN` escapes with their fallback characters.
+#[derive(Debug, Clone)]
+pub(crate) struct Segment {
+    /// Source bytes of the run.
+    pub range: Range<usize>,
+    /// Decoded text.
+    pub text: String,
+    /// Fallback characters written after each `\uN` (`\ucN`).
+    pub uc: usize,
+    /// Whether the run follows a control word without a space delimiter,
+    /// so text starting with a letter or digit needs one.
+    pub after_control_word: bool,
+}
+
+/// Destinations whose text is never converted: tables, metadata, pictures,
+/// embedded objects and field instructions. `{\*\...}` groups are skipped too.
+const SKIPPED_DESTINATIONS: &[&str] = &[
+    "fonttbl",
+    "colortbl",
+    "stylesheet",
+    "listtable",
+    "listoverridetable",
+    "revtbl",
+    "rsidtbl",
+    "info",
+    "pict",
+    "object",
+    "fldinst",
+    "datafield",
+    "themedata",
+    "colorschememapping",
+    "latentstyles",
+    "generator",
+    "xmlnstbl",
+];
+
+/// Destinations whose text belongs to paragraphs of their own.
+const NESTED_DESTINATIONS: &[&str] = &[
+    "footnote",
+    "header",
+    "headerl",
+    "headerr",
+    "headerf",
+    "footer",
+    "footerl",
+    "footerr",
+    "footerf",
+    "annotation",
+    "shptxt",
+];
+
+/// Control words that end a paragraph or a word.
+const BREAKS: &[&str] = &[
+    "par", "line", "sect", "page", "cell", "row", "nestcell", "tab", "column",
+];
+
+/// Control words that stand for a character.
+fn symbol(word: &str) -> Option<char> {
+    let c = match word {
+        "emdash" => '\u{2014}',
+        "endash" => '\u{2013}',
+        "lquote" => '\u{2018}',
+        "rquote" => '\u{2019}',
+        "ldblquote" => '\u{201C}',
+        "rdblquote" => '\u{201D}',
+        "bullet" => '\u{2022}',
+        _ => return None,
+    };
+    Some(c)
+}
+
+/// Code page of a font charset (`\fcharsetN`); `None` for the default
+/// charset, which follows `\ansicpg`. The symbol charset maps to a code
+/// page that is never decoded.
+fn charset_codepage(charset: u32) -> Option<u32> {
+    let codepage = match charset {
+        0 => 1252,
+        161 => 1253,
+        162 => 1254,
+        177 => 1255,
+        178 => 1256,
+        186 => 1257,
+        204 => 1251,
+        238 => 1250,
+        2 => 42,
+        _ => return None,
+    };
+    Some(codepage)
+}
+
+/// Characters 0x80-0xBF of Windows-1251; 0xC0-0xFF are `А`-`я`.
+const CP1251_HIGH: [u16; 64] = [
+    0x0402, 0x0403, 0x201A, 0x0453, 0x201E, 0x2026, 0x2020, 0x2021, 0x20AC, 0x2030, 0x0409, 0x2039,
+    0x040A, 0x040C, 0x040B, 0x040F, 0x0452, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x0000, 0x2122, 0x0459, 0x203A, 0x045A, 0x045C, 0x045B, 0x045F, 0x00A0, 0x040E, 0x045E, 0x0408,
+    0x00A4, 0x0490, 0x00A6, 0x00A7, 0x0401, 0x00A9, 0x0404, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x0407,
+    0x00B0, 0x00B1, 0x0406, 0x0456, 0x0491, 0x00B5, 0x00B6, 0x00B7, 0x0451, 0x2116, 0x0454, 0x00BB,
+    0x0458, 0x0405, 0x0455, 0x0457,
+];
+
+/// Characters 0x80-0x9F of Windows-1252; 0xA0-0xFF are Latin-1.
+const CP1252_HIGH: [u16; 32] = [
+    0x20AC, 0x0000, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, 0x02C6, 0x2030, 0x0160, 0x2039,
+    0x0152, 0x0000, 0x017D, 0x0000, 0x0000, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014,
+    0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x0000, 0x017E, 0x0178,
+];
+
+/// Decodes a `\'hh` byte. Code pages other than Windows-1251 and -1252
+/// are not decoded, and text written in them is left as it is.
+fn decode_byte(byte: u8, codepage: u32) -> Option<char> {
+    let code = match (codepage, byte) {
+        (_, 0..=0x7F) => u16::from(byte),
+        (1251, 0x80..=0xBF) => CP1251_HIGH[usize::from(byte - 0x80)],
+        (1251, _) => 0x0410 + u16::from(byte - 0xC0),
+        (1252, 0x80..=0x9F) => CP1252_HIGH[usize::from(byte - 0x80)],
+        (1252, _) => u16::from(byte),
+        _ => return None,
+    };
+    char::from_u32(u32::from(code)).filter(|&c| c != '\0')
+}
+
+#[derive(Debug, Clone)]
+struct Group {
+    uc: usize,
+    codepage: u32,
+    skipped: bool,
+    nested: bool,
+    /// Font table entry being read, in `\fonttbl`.
+    font: Option<u32>,
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    ansi: u32,
+    default_font: Option<u32>,
+    fonts: HashMap<u32, u32>,
+    /// Depth of the `\fonttbl` group while it is read.
+    font_table: Option<usize>,
+    groups: Vec<Group>,
+    group: Group,
+    /// Whether the next control word opens the group.
+    group_start: bool,
+    done: Vec<Vec<Segment>>,
+    open: Vec<Vec<Segment>>,
+    segment: Option<Segment>,
+    after_control_word: bool,
+}
+
+/// Text segments of an RTF document grouped by paragraph, so that a word
+/// split by formatting control words is converted as one.
+pub(crate) fn paragraphs(input: &str) -> Vec<Vec<Segment>> {
+    let mut parser = Parser {
+        input,
+        bytes: input.as_bytes(),
+        pos: 0,
+        ansi: 1252,
+        default_font: None,
+        fonts: HashMap::new(),
+        font_table: None,
+        groups: Vec::new(),
+        group: Group {
+            uc: 1,
+            codepage: 1252,
+            skipped: false,
+            nested: false,
+            font: None,
+        },
+        group_start: false,
+        done: Vec::new(),
+        open: vec![Vec::new()],
+        segment: None,
+        after_control_word: false,
+    };
+    parser.run();
+    parser.done.retain(|segments| !segments.is_empty());
+    parser.done
+}
+
+impl Parser<'_> {
+    fn run(&mut self) {
+        while let Some(&b) = self.bytes.get(self.pos) {
+            match b {
+                b'{' => {
+                    self.end_segment();
+                    self.pos += 1;
+                    self.groups.push(self.group.clone());
+                    self.group.nested = false;
+                    self.group_start = true;
+                    self.after_control_word = false;
+                }
+                b'}' => {
+                    self.end_segment();
+                    self.pos += 1;
+                    if self.group.nested {
+                        self.break_paragraph();
+                        self.done.extend(self.open.pop());
+                    }
+                    if let Some(group) = self.groups.pop() {
+                        self.group = group;
+                    }
+                    if self
+                        .font_table
+                        .is_some_and(|depth| self.groups.len() < depth)
+                    {
+                        self.font_table = None;
+                    }
+                    self.group_start = false;
+                    self.after_control_word = false;
+                }
+                b'\\' => self.control(),
+                b'\r' | b'\n' => {
+                    // Line breaks are ignored, but they delimit control words
+                    self.end_segment();
+                    self.pos += 1;
+                    self.after_control_word = false;
+                }
+                _ => {
+                    let c = self.input[self.pos..].chars().next().unwrap();
+                    let start = self.pos;
+                    self.pos += c.len_utf8();
+                    self.text(start, c);
+                }
+            }
+        }
+        self.end_segment();
+        self.break_paragraph();
+        self.done.append(&mut self.open);
+    }
+
+    fn control(&mut self) {
+        let start = self.pos;
+        let Some(&next) = self.bytes.get(self.pos + 1) else {
+            self.pos += 1;
+            return;
+        };
+        if !next.is_ascii_alphabetic() {
+            let symbol = self.input[self.pos + 1..].chars().next().unwrap();
+            self.pos += 1 + symbol.len_utf8();
+            let group_start = std::mem::take(&mut self.group_start);
+            match next {
+                b'\'' => {
+                    let byte = self
+                        .input
+                        .get(self.pos..self.pos + 2)
+                        .and_then(|h| u8::from_str_radix(h, 16).ok());
+                    let Some(byte) = byte else {
+                        self.end_segment();
+                        return;
+                    };
+                    self.pos += 2;
+                    match decode_byte(byte, self.group.codepage) {
+                        Some(c) => self.text(start, c),
+                        None => self.end_segment(),
+                    }
+                }
+                b'\\' | b'{' | b'}' => self.text(start, char::from(next)),
+                b'~' => self.text(start, '\u{00A0}'),
+                b'*' if group_start => {
+                    self.end_segment();
+                    self.group.skipped = true;
+                }
+                b'\r' | b'\n' => {
+                    self.end_segment();
+                    self.break_paragraph();
+                }
+                _ => self.end_segment(),
+            }
+            self.after_control_word = false;
+            return;
+        }
+
+        let mut end = self.pos + 1;
+        while self.bytes.get(end).is_some_and(u8::is_ascii_alphabetic) {
+            end += 1;
+        }
+        let word = &self.input[self.pos + 1..end];
+        let number_start = end;
+        if self.bytes.get(end) == Some(&b'-') {
+            end += 1;
+        }
+        while self.bytes.get(end).is_some_and(u8::is_ascii_digit) {
+            end += 1;
+        }
+        let param: Option<i32> = self.input[number_start..end].parse().ok();
+        let delimited = self.bytes.get(end) == Some(&b' ');
+        if delimited {
+            end += 1;
+        }
+        self.pos = end;
+        let group_start = std::mem::take(&mut self.group_start);
+
+        if word == "u"
+            && let Some(n) = param
+        {
+            self.unicode(start, n);
+            return;
+        }
+        if let Some(c) = symbol(word) {
+            self.text(start, c);
+            return;
+        }
+        self.end_segment();
+        self.after_control_word = !delimited;
+        self.word(word, param, group_start);
+    }
+
+    fn word(&mut self, word: &str, param: Option<i32>, group_start: bool) {
+        let param_u32 = param.and_then(|p| u32::try_from(p).ok());
+        match word {
+            "ansicpg" => {
+                if let Some(cp) = param_u32 {
+                    self.ansi = cp;
+                    self.group.codepage = cp;
+                }
+            }
+            "deff" => self.default_font = param_u32,
+            "uc" => self.group.uc = param_u32.map_or(1, |n| n as usize),
+            "fonttbl" => {
+                self.group.skipped = true;
+                self.font_table = Some(self.groups.len());
+            }
+            "f" if self.font_table.is_some() => self.group.font = param_u32,
+            "f" => self.group.codepage = self.font_codepage(param_u32),
+            "plain" => self.group.codepage = self.font_codepage(self.default_font),
+            "fcharset" if self.font_table.is_some() => {
+                if let (Some(font), Some(charset)) = (self.group.font, param_u32) {
+                    let codepage = charset_codepage(charset).unwrap_or(self.ansi);
+                    self.fonts.insert(font, codepage);
+                }
+            }
+            "cpg" if self.font_table.is_some() => {
+                if let (Some(font), Some(cp)) = (self.group.font, param_u32) {
+                    self.fonts.insert(font, cp);
+                }
+            }
+            "bin" => {
+                // Binary data of the given length follows
+                let len = param_u32.map_or(0, |n| n as usize);
+                self.skip_bytes(len);
+            }
+            w if SKIPPED_DESTINATIONS.contains(&w) => self.group.skipped = true,
+            w if group_start && NESTED_DESTINATIONS.contains(&w) => {
+                self.group.nested = true;
+                self.open.push(Vec::new());
+            }
+            w if BREAKS.contains(&w) => self.break_paragraph(),
+            _ => {}
+        }
+    }
+
+    /// Skips `len` bytes, and the rest of a character they end inside.
+    fn skip_bytes(&mut self, len: usize) {
+        self.pos = (self.pos + len).min(self.bytes.len());
+        while !self.input.is_char_boundary(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn font_codepage(&self, font: Option<u32>) -> u32 {
+        font.and_then(|f| self.fonts.get(&f).copied())
+            .unwrap_or(self.ansi)
+    }
+
+    /// `\uN` followed by `\ucN` fallback characters, which are skipped.
+    fn unicode(&mut self, start: usize, n: i32) {
+        // Values above 32767 are written as negative numbers
+        let unit = if n < 0 { n + 65536 } else { n } as u32;
+        for _ in 0..self.group.uc {
+            match self.bytes.get(self.pos) {
+                Some(b'\\') if self.bytes.get(self.pos + 1) == Some(&b'\'') => {
+                    let hex = self.bytes.get(self.pos + 2..self.pos + 4);
+                    let escape = hex.is_some_and(|h| h.iter().all(u8::is_ascii_hexdigit));
+                    self.skip_bytes(if escape { 4 } else { 2 });
+                }
+                Some(b'\\' | b'{' | b'}' | b'\r' | b'\n') | None => break,
+                Some(_) => {
+                    let c = self.input[self.pos..].chars().next().unwrap();
+                    self.pos += c.len_utf8();
+                }
+            }
+        }
+
+        // The low half of a surrogate pair completes the previous escape
+        if (0xDC00..0xE000).contains(&unit) {
+            // High halves waiting for this one, in the private use plane
+            let pending = 0xF0000 + 0xD800..0xF0000 + 0xDC00;
+            if let Some(segment) = self.segment.as_mut()
+                && let Some(high) = segment.text.pop()
+            {
+                if pending.contains(&u32::from(high)) {
+                    let high = u32::from(high) - 0xF0000;
+                    let c = char::from_u32(0x10000 + ((high - 0xD800) << 10) + (unit - 0xDC00));
+                    segment.text.extend(c);
+                    segment.range.end = self.pos;
+                    return;
+                }
+                segment.text.push(high);
+            }
+            // A lone low surrogate is left as written
+            self.end_segment();
+            return;
+        }
+        // High surrogates are kept in a private use plane until completed
+        let c = if (0xD800..0xDC00).contains(&unit) {
+            char::from_u32(0xF0000 + unit)
+        } else {
+            char::from_u32(unit)
+        };
+        match c {
+            Some(c) => self.text(start, c),
+            None => self.end_segment(),
+        }
+    }
+
+    fn text(&mut self, start: usize, c: char) {
+        if self.group.skipped {
+            return;
+        }
+        let after_control_word = std::mem::take(&mut self.after_control_word);
+        let segment = self.segment.get_or_insert_with(|| Segment {
+            range: start..start,
+            text: String::new(),
+            uc: self.group.uc,
+            after_control_word,
+        });
+        segment.text.push(c);
+        segment.range.end = self.pos;
+    }
+
+    fn end_segment(&mut self) {
+        if let Some(segment) = self.segment.take() {
+            // An unpaired high surrogate cannot be converted
+            if segment.text.chars().any(|c| c >= '\u{F0000}') {
+                return;
+            }
+            if let Some(paragraph) = self.open.last_mut() {
+                paragraph.push(segment);
+            }
+        }
+    }
+
+    fn break_paragraph(&mut self) {
+        self.end_segment();
+        if let Some(paragraph) = self.open.last_mut() {
+            self.done.push(std::mem::take(paragraph));
+        }
+    }
+}
+
+/// Writes converted text as RTF: ASCII as it is with `\`, `{` and `}`
+/// escaped, everything else as `\uN` escapes followed by `uc` `?` fallbacks.
+pub(crate) fn encode(text: &str, segment: &Segment) -> String {
+    let mut encoded = String::with_capacity(text.len() * 2);
+    if segment.after_control_word && text.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        encoded.push(' ');
+    }
+    for c in text.chars() {
+        match c {
+            '\\' | '{' | '}' => {
+                encoded.push('\\');
+                encoded.push(c);
+            }
+            ' '..='~' => encoded.push(c),
+            _ => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    // RTF parameters are signed 16-bit numbers
+                    encoded.push_str(&format!("\\u{}", *unit as i16));
+                    if segment.uc == 0 {
+                        // Without fallbacks a following digit would extend the number
+                        encoded.push(' ');
+                    }
+                    encoded.push_str(&"?".repeat(segment.uc));
+                }
+            }
+        }
+    }
+    encoded
+}
+
+/// Shields an RTF document except for its text runs.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    mask.fill(true);
+    for segment in paragraphs(input).into_iter().flatten() {
+        mask[segment.range].fill(false);
+    }
+}
@@ -0,0 +1,48 @@
+use super::Source;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Domains without a scheme (`www.site.uz`, `t.me/kanal`), with their path.
+/// A trailing full stop or comma belongs to the sentence.
+pub(super) static RE_DOMAIN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b(?:[a-z0-9](?:[a-z0-9-]*[a-z0-9])?\.)+(?:uz|com|ru|org|net|info|io|me|app|dev|edu|gov|kz|tj|kg|tm)\b(?:/(?:[^\s<>"]*[^\s<>".,!?;:)])?)?"#,
+    )
+    .unwrap()
+});
+/// `@username`, not preceded by a word character (so e-mails are left alone).
+pub(super) static RE_MENTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w@])(@[A-Za-z0-9_]{3,32})\b").unwrap());
+/// Bot commands such as `/start` or `/help@bot`, at the start of a word.
+pub(super) static RE_COMMAND: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)(?:^|\s)(/[A-Za-z][A-Za-z0-9_]{0,31}(?:@[A-Za-z0-9_]+)?)\b").unwrap()
+});
+/// `#hashtag`, not part of a character reference or a `##` run.
+pub(super) static RE_HASHTAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|[^\w&#])(#\w+)").unwrap());
+/// An emoji with its variation selectors, skin tones and keycap marks, and
+/// every emoji joined to it with ZWJ. Regional indicator pairs (flags) and
+/// tag sequences are covered by the same ranges.
+pub(super) static RE_EMOJI: LazyLock<Regex> = LazyLock::new(|| {
+    let emoji = r"[\x{00A9}\x{00AE}\x{203C}\x{2049}\x{2122}\x{2139}\x{2194}-\x{21AA}\x{231A}-\x{23FF}\x{24C2}\x{25AA}-\x{27BF}\x{2934}\x{2935}\x{2B05}-\x{2B55}\x{3030}\x{303D}\x{3297}\x{3299}\x{1F000}-\x{1FAFF}]";
+    let modifier = r"[\x{FE0E}\x{FE0F}\x{20E3}\x{1F3FB}-\x{1F3FF}\x{E0020}-\x{E007F}]";
+    Regex::new(&format!(
+        r"(?:{emoji}|[0-9#*]\x{{FE0F}}?\x{{20E3}}){modifier}*(?:\x{{200D}}{emoji}{modifier}*)*"
+    ))
+    .unwrap()
+});
+
+/// The social shields in the order they are applied. Each pattern shields
+/// its first capture group, or the whole match if it has none.
+pub(super) fn patterns(convert_hashtags: bool) -> Vec<(Source, &'static Regex)> {
+    let mut patterns = vec![
+        (Source::Emoji, &*RE_EMOJI),
+        (Source::Domain, &*RE_DOMAIN),
+        (Source::Mention, &*RE_MENTION),
+        (Source::Command, &*RE_COMMAND),
+    ];
+    if !convert_hashtags {
+        patterns.push((Source::Hashtag, &*RE_HASHTAG));
+    }
+    patterns
+}
@@ -0,0 +1,590 @@
+use crate::KodTili;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// `{name}`, `{0}`, `{:>10}`, `{{`, `%s`, `%(name)s`, `%1$d`, `%-5.2f`, `%%`.
+pub(super) static RE_PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\{\{|\}\}|\{[\x21-\x7E&&[^{}]]*\}",
+        r"|%(?:\([A-Za-z_][A-Za-z0-9_]*\)|\d+\$)?[-+#0']*(?:\d+|\*)?(?:\.(?:\d+|\*))?",
+        r"(?:hh|h|ll|l|L|q|j|z|t)?[diouxXeEfFgGcrsaAbvqpTt%]",
+    ))
+    .unwrap()
+});
+/// Backslash escapes, including `\u{...}`, `\uXXXX`, `\xXX` and `\N{...}`.
+pub(super) static RE_ESCAPE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)\\(?:u\{[0-9a-fA-F]*\}|u[0-9a-fA-F]{4}|U[0-9a-fA-F]{8}|x[0-9a-fA-F]{1,2}|N\{[^}]*\}|[0-7]{1,3}|.)")
+        .unwrap()
+});
+/// PHP variables interpolated into double-quoted strings and heredocs.
+static RE_PHP_VARIABLE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\{\$[^}]*\}|\$[A-Za-z_][A-Za-z0-9_]*(?:->[A-Za-z_][A-Za-z0-9_]*|\[[^\]]*\])*")
+        .unwrap()
+});
+
+/// Keywords after which `/` starts a JavaScript regex rather than a division.
+const JS_REGEX_KEYWORDS: &[&str] = &[
+    "return", "typeof", "case", "do", "else", "in", "of", "new", "delete", "void", "throw",
+    "yield", "await",
+];
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    /// Inline HTML around PHP tags, handed over as is.
+    Markup,
+    Comment,
+    /// String literal content.
+    Text {
+        escapes: bool,
+        variables: bool,
+    },
+}
+
+struct Window {
+    range: Range<usize>,
+    kind: Kind,
+    /// Delimiter that converted text must escape (single-quoted literals).
+    quote: Option<char>,
+}
+
+/// Shields a source file except for string literal contents (and comments
+/// when `comments` is set). Escapes, placeholders and interpolations inside
+/// literals stay shielded.
+/// Returns the single-quoted literal windows, whose converted text must
+/// escape the quote.
+pub(super) fn build_mask(
+    input: &str,
+    til: KodTili,
+    comments: bool,
+    mask: &mut [bool],
+) -> Vec<(Range<usize>, char)> {
+    mask.fill(true);
+    let mut literals = Vec::new();
+
+    for window in Lexer::new(input, til, comments).run() {
+        let range = window.range;
+        if range.is_empty() {
+            continue;
+        }
+        mask[range.clone()].fill(false);
+
+        if let Kind::Text { escapes, variables } = window.kind {
+            let text = &input[range.clone()];
+            let regexes = [
+                Some(&*RE_PLACEHOLDER),
+                escapes.then_some(&*RE_ESCAPE),
+                variables.then_some(&*RE_PHP_VARIABLE),
+            ];
+            for re in regexes.into_iter().flatten() {
+                for m in re.find_iter(text) {
+                    mask[range.start + m.start()..range.start + m.end()].fill(true);
+                }
+            }
+        }
+        if let Some(quote) = window.quote {
+            literals.push((range, quote));
+        }
+    }
+    literals
+}
+
+struct Lexer<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    til: KodTili,
+    comments: bool,
+    windows: Vec<Window>,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(input: &'a str, til: KodTili, comments: bool) -> Self {
+        Self {
+            input,
+            bytes: input.as_bytes(),
+            til,
+            comments,
+            windows: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Window> {
+        if self.til == KodTili::Php {
+            self.php_document();
+        } else {
+            self.code(0, false);
+        }
+        self.windows
+    }
+
+    fn starts_with(&self, pos: usize, s: &str) -> bool {
+        self.bytes[pos..].starts_with(s.as_bytes())
+    }
+
+    fn push(&mut self, range: Range<usize>, kind: Kind, quote: Option<char>) {
+        self.windows.push(Window { range, kind, quote });
+    }
+
+    /// Text outside `<?php ... ?>` is markup; everything inside is code.
+    fn php_document(&mut self) {
+        let len = self.bytes.len();
+        let mut pos = 0;
+
+        while pos < len {
+            let open = self.input[pos..]
+                .match_indices("<?")
+                .map(|(i, _)| pos + i)
+                .find(|&i| !self.input[i..].starts_with("<?xml"));
+            let Some(open) = open else {
+                self.push(pos..len, Kind::Markup, None);
+                return;
+            };
+            self.push(pos..open, Kind::Markup, None);
+
+            let code_start = if self.starts_with(open, "<?php") {
+                open + 5
+            } else if self.starts_with(open, "<?=") {
+                open + 3
+            } else {
+                open + 2
+            };
+            let end = self.code(code_start, false);
+            pos = (end + 2).min(len);
+        }
+    }
+
+    /// Lexes code from `pos`. Inside an interpolation, stops after the `}`
+    /// closing it. Returns the offset where lexing stopped.
+    fn code(&mut self, mut pos: usize, interpolation: bool) -> usize {
+        let len = self.bytes.len();
+        let mut depth = 0usize;
+        // Start of input behaves like an open parenthesis for regex detection
+        let mut last_significant = b'(';
+
+        while pos < len {
+            if self.til == KodTili::Php && self.starts_with(pos, "?>") && !interpolation {
+                return pos;
+            }
+            if let Some(end) = self.comment(pos) {
+                pos = end;
+                continue;
+            }
+            if let Some(end) = self.string(pos, last_significant) {
+                pos = end;
+                last_significant = b'"';
+                continue;
+            }
+
+            let b = self.bytes[pos];
+            if is_ident(b) {
+                while pos < len && is_ident(self.bytes[pos]) {
+                    pos += 1;
+                }
+                last_significant = b'a';
+                continue;
+            }
+            match b {
+                b'{' => depth += 1,
+                b'}' if interpolation && depth == 0 => return pos + 1,
+                b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            if !b.is_ascii_whitespace() {
+                last_significant = b;
+            }
+            pos += 1;
+        }
+        pos
+    }
+
+    /// Skips a comment at `pos`, recording its text when comments are converted.
+    fn comment(&mut self, pos: usize) -> Option<usize> {
+        let til = self.til;
+        let c_style = til != KodTili::Python;
+
+        let line = (c_style && self.starts_with(pos, "//"))
+            || (til == KodTili::Python && self.bytes[pos] == b'#')
+            || (til == KodTili::Php && self.bytes[pos] == b'#' && !self.starts_with(pos, "#["));
+        if line {
+            let marker = if self.bytes[pos] == b'#' { 1 } else { 2 };
+            let mut end = self.input[pos..]
+                .find('\n')
+                .map_or(self.bytes.len(), |i| pos + i);
+            if til == KodTili::Php
+                && let Some(close) = self.input[pos..end].find("?>")
+            {
+                end = pos + close;
+            }
+            if self.comments {
+                self.push(pos + marker..end, Kind::Comment, None);
+            }
+            return Some(end);
+        }
+
+        if c_style && self.starts_with(pos, "/*") {
+            let nested = til == KodTili::Rust;
+            let mut depth = 1usize;
+            let mut i = pos + 2;
+            while i < self.bytes.len() && depth > 0 {
+                if self.starts_with(i, "*/") {
+                    depth -= 1;
+                    i += 2;
+                } else if nested && self.starts_with(i, "/*") {
+                    depth += 1;
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            if self.comments {
+                let content_end = if depth == 0 { i - 2 } else { i };
+                self.push(pos + 2..content_end, Kind::Comment, None);
+            }
+            return Some(i);
+        }
+        None
+    }
+
+    /// Skips a string, character or regex literal at `pos`.
+    fn string(&mut self, pos: usize, last_significant: u8) -> Option<usize> {
+        let b = self.bytes[pos];
+        let at_boundary = pos == 0 || !is_ident(self.bytes[pos - 1]);
+
+        match self.til {
+            KodTili::Rust => {
+                if at_boundary && let Some(end) = self.rust_prefixed(pos) {
+                    return Some(end);
+                }
+                match b {
+                    b'"' => Some(self.literal(pos + 1, "\"", true, false, None)),
+                    b'\'' => Some(self.rust_quote(pos)),
+                    _ => None,
+                }
+            }
+            KodTili::Python => {
+                if at_boundary && let Some(end) = self.python_string(pos) {
+                    return Some(end);
+                }
+                None
+            }
+            KodTili::JavaScript => match b {
+                b'"' => Some(self.literal(pos + 1, "\"", true, true, None)),
+                b'\'' => Some(self.literal(pos + 1, "'", true, true, Some('\''))),
+                b'`' => Some(self.interpolated(pos + 1, "`", "${", true, false, None)),
+                b'/' if self.regex_allowed(pos, last_significant) => Some(self.js_regex(pos)),
+                _ => None,
+            },
+            KodTili::Java => match b {
+                b'"' if self.starts_with(pos, "\"\"\"") => {
+                    Some(self.literal(pos + 3, "\"\"\"", true, false, None))
+                }
+                b'"' => Some(self.literal(pos + 1, "\"", true, true, None)),
+                b'\'' => Some(self.skip_quoted(pos + 1, b'\'')),
+                _ => None,
+            },
+            KodTili::Go => match b {
+                b'"' => Some(self.literal(pos + 1, "\"", true, true, None)),
+                b'`' => Some(self.literal(pos + 1, "`", false, false, None)),
+                b'\'' => Some(self.skip_quoted(pos + 1, b'\'')),
+                _ => None,
+            },
+            KodTili::Php => match b {
+                b'\'' => Some(self.literal(pos + 1, "'", true, false, Some('\''))),
+                b'"' => Some(self.php_double(pos + 1, "\"")),
+                b'<' if self.starts_with(pos, "<<<") => self.php_heredoc(pos),
+                _ => None,
+            },
+        }
+    }
+
+    /// A plain literal whose content ends at `close`.
+    fn literal(
+        &mut self,
+        start: usize,
+        close: &str,
+        escapes: bool,
+        single_line: bool,
+        quote: Option<char>,
+    ) -> usize {
+        let (content_end, end) = self.find_close(start, close, escapes, single_line);
+        self.push(
+            start..content_end,
+            Kind::Text {
+                escapes,
+                variables: false,
+            },
+            quote,
+        );
+        end
+    }
+
+    /// Finds the closing delimiter. Returns (content end, offset after it).
+    /// Unterminated single-line literals stop at the end of the line.
+    fn find_close(
+        &self,
+        start: usize,
+        close: &str,
+        escapes: bool,
+        single_line: bool,
+    ) -> (usize, usize) {
+        let len = self.bytes.len();
+        let mut i = start;
+        while i < len {
+            if escapes && self.bytes[i] == b'\\' {
+                i += 2;
+                continue;
+            }
+            if self.starts_with(i, close) {
+                return (i, i + close.len());
+            }
+            if single_line && self.bytes[i] == b'\n' {
+                return (i, i);
+            }
+            i += 1;
+        }
+        (len, len)
+    }
+
+    /// Character, rune and regex literals are never converted.
+    fn skip_quoted(&self, start: usize, close: u8) -> usize {
+        let mut buf = [0u8; 4];
+        let close = (close as char).encode_utf8(&mut buf);
+        self.find_close(start, close, true, true).1
+    }
+
+    /// A literal with `${...}` (JavaScript) or `{...}` (Python) interpolations,
+    /// whose expressions are lexed as code. The text between them is treated
+    /// like `literal` text.
+    fn interpolated(
+        &mut self,
+        start: usize,
+        close: &str,
+        opener: &str,
+        escapes: bool,
+        single_line: bool,
+        quote: Option<char>,
+    ) -> usize {
+        let len = self.bytes.len();
+        let kind = Kind::Text {
+            escapes,
+            variables: false,
+        };
+        let mut segment = start;
+        let mut i = start;
+
+        while i < len {
+            if escapes && self.bytes[i] == b'\\' {
+                i += 2;
+                continue;
+            }
+            if self.starts_with(i, close) {
+                self.push(segment..i, kind, quote);
+                return i + close.len();
+            }
+            if single_line && self.bytes[i] == b'\n' {
+                self.push(segment..i, kind, quote);
+                return i;
+            }
+            // `{{` is a literal brace in Python f-strings
+            if opener == "{" && self.starts_with(i, "{{") {
+                i += 2;
+                continue;
+            }
+            if self.starts_with(i, opener) {
+                self.push(segment..i, kind, quote);
+                i = self.code(i + opener.len(), true);
+                segment = i;
+                continue;
+            }
+            i += 1;
+        }
+        self.push(segment..len.min(i), kind, quote);
+        len
+    }
+
+    // --- Rust ---
+
+    /// `b"..."`, `c"..."`, `r#"..."#`, `br"..."`, `b'x'`.
+    fn rust_prefixed(&mut self, pos: usize) -> Option<usize> {
+        let mut i = pos;
+        let bytes_only = matches!(self.bytes[i], b'b' | b'c');
+        if bytes_only {
+            i += 1;
+        }
+        let raw = self.bytes.get(i) == Some(&b'r');
+        if raw {
+            i += 1;
+        }
+        if !bytes_only && !raw {
+            return None;
+        }
+
+        if bytes_only && !raw && self.bytes.get(i) == Some(&b'\'') {
+            return Some(self.skip_quoted(i + 1, b'\''));
+        }
+
+        let hashes_start = i;
+        while raw && self.bytes.get(i) == Some(&b'#') {
+            i += 1;
+        }
+        if self.bytes.get(i) != Some(&b'"') {
+            return None;
+        }
+        let close = format!("\"{}", "#".repeat(i - hashes_start));
+        let escapes = !raw;
+
+        if bytes_only {
+            // Byte and C strings cannot hold converted text
+            return Some(self.find_close(i + 1, &close, escapes, false).1);
+        }
+        Some(self.literal(i + 1, &close, escapes, false, None))
+    }
+
+    /// A character literal or a lifetime.
+    fn rust_quote(&self, pos: usize) -> usize {
+        if self.bytes.get(pos + 1) == Some(&b'\\') {
+            return self.skip_quoted(pos + 1, b'\'');
+        }
+        let char_len = self.input[pos + 1..]
+            .chars()
+            .next()
+            .map_or(1, char::len_utf8);
+        if self.bytes.get(pos + 1 + char_len) == Some(&b'\'') {
+            return pos + 2 + char_len;
+        }
+        // Lifetime or label: `'a`
+        pos + 1
+    }
+
+    // --- Python ---
+
+    fn python_string(&mut self, pos: usize) -> Option<usize> {
+        let mut i = pos;
+        while i < self.bytes.len()
+            && i - pos < 2
+            && matches!(
+                self.bytes[i].to_ascii_lowercase(),
+                b'r' | b'b' | b'u' | b'f'
+            )
+        {
+            i += 1;
+        }
+        let quote = *self.bytes.get(i)?;
+        if !matches!(quote, b'\'' | b'"') {
+            return None;
+        }
+
+        let prefix = self.input[pos..i].to_ascii_lowercase();
+        let raw = prefix.contains('r');
+        let triple = self.bytes[i..].starts_with(&[quote; 3]);
+        let close = if triple {
+            (quote as char).to_string().repeat(3)
+        } else {
+            (quote as char).to_string()
+        };
+        let start = i + close.len();
+
+        if prefix.contains('b') {
+            return Some(self.find_close(start, &close, !raw, !triple).1);
+        }
+        let escape_quote = (quote == b'\'' && !triple).then_some('\'');
+        if prefix.contains('f') {
+            return Some(self.interpolated(start, &close, "{", !raw, !triple, escape_quote));
+        }
+        Some(self.literal(start, &close, !raw, !triple, escape_quote))
+    }
+
+    // --- JavaScript ---
+
+    fn regex_allowed(&self, pos: usize, last_significant: u8) -> bool {
+        if matches!(self.bytes.get(pos + 1), Some(b'/' | b'*')) {
+            return false;
+        }
+        if last_significant == b'a' {
+            let word_end = self.input[..pos].trim_end().len();
+            let word_start = self.input[..word_end]
+                .rfind(|c: char| c.is_ascii() && !is_ident(c as u8))
+                .map_or(0, |i| i + 1);
+            return JS_REGEX_KEYWORDS.contains(&&self.input[word_start..word_end]);
+        }
+        b"(,=:[!&|?{};+-*%<>~^".contains(&last_significant)
+    }
+
+    fn js_regex(&self, pos: usize) -> usize {
+        let len = self.bytes.len();
+        let mut i = pos + 1;
+        let mut in_class = false;
+        while i < len {
+            match self.bytes[i] {
+                b'\\' => i += 1,
+                b'[' => in_class = true,
+                b']' => in_class = false,
+                b'/' if !in_class => break,
+                b'\n' => return i,
+                _ => {}
+            }
+            i += 1;
+        }
+        i += 1;
+        while i < len && self.bytes[i].is_ascii_alphabetic() {
+            i += 1;
+        }
+        i.min(len)
+    }
+
+    // --- PHP ---
+
+    fn php_double(&mut self, start: usize, close: &str) -> usize {
+        let (content_end, end) = self.find_close(start, close, true, false);
+        self.push(
+            start..content_end,
+            Kind::Text {
+                escapes: true,
+                variables: true,
+            },
+            None,
+        );
+        end
+    }
+
+    /// `<<<ID` heredoc and `<<<'ID'` nowdoc.
+    fn php_heredoc(&mut self, pos: usize) -> Option<usize> {
+        let header_end = self.input[pos..].find('\n').map(|i| pos + i)?;
+        let header = self.input[pos + 3..header_end].trim();
+        let nowdoc = header.starts_with('\'');
+        let id = header.trim_matches(|c| c == '\'' || c == '"');
+        if id.is_empty() || !id.bytes().all(is_ident) {
+            return None;
+        }
+
+        let body = header_end + 1;
+        let mut line_start = body;
+        while line_start < self.bytes.len() {
+            let line_end = self.input[line_start..]
+                .find('\n')
+                .map_or(self.bytes.len(), |i| line_start + i);
+            let line = self.input[line_start..line_end].trim_start();
+            let closes = line
+                .strip_prefix(id)
+                .is_some_and(|rest| !rest.bytes().next().is_some_and(is_ident));
+            if closes {
+                self.push(
+                    body..line_start,
+                    Kind::Text {
+                        escapes: !nowdoc,
+                        variables: !nowdoc,
+                    },
+                    None,
+                );
+                let id_start = line_end - line.len();
+                return Some(id_start + id.len());
+            }
+            line_start = line_end + 1;
+        }
+        None
+    }
+}
+
+fn is_ident(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$' || b >= 0x80
+}
@@ -0,0 +1,698 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Words between `INSERT`, `CREATE TABLE` or `COPY` and the table name.
+const TABLE_MODIFIERS: &[&str] = &[
+    "INTO",
+    "IGNORE",
+    "LOW_PRIORITY",
+    "DELAYED",
+    "HIGH_PRIORITY",
+    "IF",
+    "NOT",
+    "EXISTS",
+    "ONLY",
+];
+
+/// Words between `CREATE` and `TABLE`.
+const CREATE_MODIFIERS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "TEMPORARY",
+    "TEMP",
+    "UNLOGGED",
+    "GLOBAL",
+    "LOCAL",
+];
+
+/// Table definition entries that do not define a column.
+const CONSTRAINTS: &[&str] = &[
+    "PRIMARY",
+    "KEY",
+    "UNIQUE",
+    "CONSTRAINT",
+    "INDEX",
+    "FOREIGN",
+    "CHECK",
+    "FULLTEXT",
+    "SPATIAL",
+    "EXCLUDE",
+    "LIKE",
+    "PERIOD",
+];
+
+/// How the text of a string literal is escaped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Escapes {
+    /// Standard SQL: quotes are doubled.
+    Standard,
+    /// MySQL strings and PostgreSQL `E'…'` strings: backslash escapes as
+    /// well as doubled quotes.
+    Backslash,
+    /// A field of a `COPY … FROM stdin` data block: backslash escapes only.
+    Copy,
+}
+
+/// A string literal or `COPY` data field of an SQL dump.
+#[derive(Debug)]
+pub(crate) struct Literal {
+    /// Text between the quotes, escapes included.
+    pub range: Range<usize>,
+    pub escapes: Escapes,
+    /// Whether the literal belongs to the selected tables and columns.
+    pub selected: bool,
+}
+
+impl Literal {
+    /// Ranges of the literal text between its backslash escapes. Doubled
+    /// quotes are left inside the ranges.
+    pub(crate) fn runs(&self, input: &str) -> Vec<Range<usize>> {
+        if self.escapes == Escapes::Standard {
+            return vec![self.range.clone()];
+        }
+        let bytes = input.as_bytes();
+        let mut runs = Vec::new();
+        let mut start = self.range.start;
+        let mut i = start;
+        while i < self.range.end {
+            if bytes[i] != b'\\' {
+                i += 1;
+                continue;
+            }
+            if i > start {
+                runs.push(start..i);
+            }
+            i = (i + escape_len(&input[i + 1..self.range.end]) + 1).min(self.range.end);
+            start = i;
+        }
+        if start < self.range.end {
+            runs.push(start..self.range.end);
+        }
+        runs
+    }
+}
+
+/// Length of the escape after a backslash: `\xHH`, `\uXXXX`, `\UXXXXXXXX`,
+/// up to three octal digits, or a single character.
+fn escape_len(rest: &str) -> usize {
+    let hex = |max: usize| {
+        rest[1..]
+            .bytes()
+            .take(max)
+            .take_while(u8::is_ascii_hexdigit)
+            .count()
+    };
+    match rest.as_bytes().first() {
+        None => 0,
+        Some(b'x') => 1 + hex(2),
+        Some(b'u') if hex(4) == 4 => 5,
+        Some(b'U') if hex(8) == 8 => 9,
+        Some(b'0'..=b'7') => rest
+            .bytes()
+            .take(3)
+            .take_while(|b| (b'0'..=b'7').contains(b))
+            .count(),
+        Some(_) => rest.chars().next().map_or(1, char::len_utf8),
+    }
+}
+
+#[derive(Debug)]
+enum Token {
+    /// A keyword, unquoted identifier or number.
+    Word(Range<usize>),
+    /// The content of a quoted identifier.
+    Quoted(Range<usize>),
+    Punct(u8),
+    Str(Range<usize>, Escapes),
+    /// A value that is never converted: a dollar-quoted string, or a bit,
+    /// hex or Unicode-escape string.
+    Opaque,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Insert,
+    Create,
+    Copy,
+    #[default]
+    Other,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    /// Start of a statement.
+    #[default]
+    Start,
+    /// After `CREATE`, before `TABLE`.
+    Create,
+    /// Before the table name.
+    Table,
+    /// After a `.` of a qualified table name.
+    Part,
+    /// After a table name part.
+    Name,
+    /// The column list of an `INSERT` or `COPY`.
+    Columns,
+    /// Values, column definitions or options.
+    Body,
+    /// A statement whose literals are converted only without a selection.
+    Other,
+}
+
+/// Which values of a row are converted.
+#[derive(Debug, Default)]
+enum Selection {
+    All,
+    #[default]
+    None,
+    Columns(Vec<bool>),
+}
+
+impl Selection {
+    fn get(&self, column: usize) -> bool {
+        match self {
+            Self::All => true,
+            Self::None => false,
+            Self::Columns(selected) => selected.get(column).copied().unwrap_or(false),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Statement {
+    kind: Kind,
+    phase: Phase,
+    /// Table name, its parts joined with `.` and unquoted.
+    table: String,
+    /// Names of the column list, or of the columns a table defines.
+    columns: Vec<String>,
+    /// Parenthesis depth in the body.
+    depth: usize,
+    /// Index of the current value of an `INSERT` row.
+    column: usize,
+    /// `VALUES` has been read.
+    values: bool,
+    /// `FROM stdin` has been read.
+    stdin: bool,
+    /// The next body token starts a column definition.
+    definition: bool,
+    selection: Selection,
+}
+
+/// A `COPY … FROM stdin` data block being read.
+struct CopyBlock {
+    column: usize,
+    selection: Selection,
+}
+
+/// Streams the string literals and `COPY` data fields of a MySQL or
+/// PostgreSQL dump. Comments, quoted identifiers and dollar-quoted strings
+/// are skipped. Literals are selected when `tables` and `columns` are both
+/// empty, or when they are values of an `INSERT` row or `COPY` block whose
+/// table is named in `tables` (any table when empty) and whose column is
+/// named or numbered in `columns` (any column when empty). Columns of
+/// `INSERT`s without a column list are named after the `CREATE TABLE`
+/// seen earlier in the dump.
+pub(crate) struct Literals<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    /// Backslashes escape in every string and `#` starts a comment.
+    mysql: bool,
+    tables: &'a [String],
+    columns: &'a [String],
+    /// Column names of the tables created so far, by lowercase name.
+    schemas: HashMap<String, Vec<String>>,
+    statement: Statement,
+    copy: Option<CopyBlock>,
+}
+
+impl<'a> Literals<'a> {
+    pub(crate) fn new(input: &'a str, tables: &'a [String], columns: &'a [String]) -> Self {
+        Self {
+            input,
+            bytes: input.as_bytes(),
+            pos: 0,
+            mysql: is_mysql(input),
+            tables,
+            columns,
+            schemas: HashMap::new(),
+            statement: Statement::default(),
+            copy: None,
+        }
+    }
+
+    fn token(&mut self) -> Option<Token> {
+        loop {
+            let start = self.pos;
+            let b = *self.bytes.get(start)?;
+            let next = self.bytes.get(start + 1).copied();
+            match b {
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                b'-' if next == Some(b'-') => self.skip_line(),
+                b'#' if self.mysql => self.skip_line(),
+                b'/' if next == Some(b'*') => self.skip_comment(),
+                b'\'' => return Some(self.string(start + 1, self.literal_escapes())),
+                b'"' | b'`' => {
+                    let end = self.quoted(start + 1, b, false);
+                    return Some(Token::Quoted(start + 1..end));
+                }
+                b'$' if self.dollar_quoted(start) => return Some(Token::Opaque),
+                b if is_word_start(b) => return Some(self.word(start)),
+                _ => {
+                    self.pos += 1;
+                    return Some(Token::Punct(b));
+                }
+            }
+        }
+    }
+
+    /// A word, or the prefix of an `E'…'`, `N'…'`, `_charset'…'`, `X'…'`,
+    /// `B'…'` or `U&'…'` string.
+    fn word(&mut self, start: usize) -> Token {
+        self.pos = start + 1;
+        while self.bytes.get(self.pos).is_some_and(|&b| is_word_byte(b)) {
+            self.pos += 1;
+        }
+        let word = &self.input[start..self.pos];
+        let rest = &self.bytes[self.pos..];
+        if rest.starts_with(b"'") {
+            let open = self.pos + 1;
+            return match word {
+                "E" | "e" => self.string(open, Escapes::Backslash),
+                "N" | "n" => self.string(open, self.literal_escapes()),
+                _ if word.starts_with('_') => self.string(open, self.literal_escapes()),
+                "X" | "x" | "B" | "b" => {
+                    self.quoted(open, b'\'', false);
+                    Token::Opaque
+                }
+                _ => Token::Word(start..self.pos),
+            };
+        }
+        if matches!(word, "U" | "u") && rest.starts_with(b"&'") {
+            self.quoted(self.pos + 2, b'\'', false);
+            return Token::Opaque;
+        }
+        Token::Word(start..self.pos)
+    }
+
+    /// Escapes of an ordinary string in this dump.
+    fn literal_escapes(&self) -> Escapes {
+        if self.mysql {
+            Escapes::Backslash
+        } else {
+            Escapes::Standard
+        }
+    }
+
+    fn string(&mut self, start: usize, escapes: Escapes) -> Token {
+        let end = self.quoted(start, b'\'', escapes == Escapes::Backslash);
+        Token::Str(start..end, escapes)
+    }
+
+    /// Skips to the closing `quote`, past doubled quotes (and backslash
+    /// escapes). Returns the end of the content.
+    fn quoted(&mut self, start: usize, quote: u8, backslash: bool) -> usize {
+        let len = self.bytes.len();
+        let mut i = start;
+        while i < len {
+            let b = self.bytes[i];
+            if (backslash && b == b'\\') || (b == quote && self.bytes.get(i + 1) == Some(&quote)) {
+                i += 2;
+            } else if b == quote {
+                self.pos = i + 1;
+                return i;
+            } else {
+                i += 1;
+            }
+        }
+        self.pos = len;
+        len
+    }
+
+    /// Skips a `$tag$…$tag$` string starting at `start`, if there is one.
+    fn dollar_quoted(&mut self, start: usize) -> bool {
+        let mut i = start + 1;
+        if self.bytes.get(i).is_some_and(u8::is_ascii_digit) {
+            return false;
+        }
+        while self
+            .bytes
+            .get(i)
+            .is_some_and(|&b| is_word_byte(b) && b != b'$')
+        {
+            i += 1;
+        }
+        if self.bytes.get(i) != Some(&b'$') {
+            return false;
+        }
+        let tag = &self.input[start..=i];
+        self.pos = self.input[i + 1..]
+            .find(tag)
+            .map_or(self.bytes.len(), |close| i + 1 + close + tag.len());
+        true
+    }
+
+    fn skip_line(&mut self) {
+        self.pos = self.input[self.pos..]
+            .find('\n')
+            .map_or(self.bytes.len(), |nl| self.pos + nl + 1);
+    }
+
+    /// Skips a block comment, nested ones included in PostgreSQL. MySQL
+    /// `/*!…*/` version comments are skipped too.
+    fn skip_comment(&mut self) {
+        let len = self.bytes.len();
+        let mut depth = 0;
+        let mut i = self.pos;
+        while i < len {
+            match &self.bytes[i..(i + 2).min(len)] {
+                b"/*" if depth == 0 || !self.mysql => {
+                    depth += 1;
+                    i += 2;
+                }
+                b"*/" => {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => i += 1,
+            }
+        }
+        self.pos = i.min(len);
+    }
+
+    /// Reads `COPY` data fields up to the next selected, non-null one, and
+    /// leaves the block at its `\.` line.
+    fn copy_field(&mut self) -> Option<Literal> {
+        let len = self.bytes.len();
+        while let Some(copy) = &mut self.copy {
+            let at_line_start = copy.column == 0;
+            if self.pos >= len {
+                self.copy = None;
+                break;
+            }
+            if at_line_start
+                && self.bytes[self.pos..].starts_with(b"\\.")
+                && matches!(self.bytes.get(self.pos + 2), None | Some(b'\r' | b'\n'))
+            {
+                self.pos += 2;
+                self.copy = None;
+                break;
+            }
+            let start = self.pos;
+            while let Some(&b) = self.bytes.get(self.pos) {
+                match b {
+                    b'\t' | b'\n' => break,
+                    b'\\' => self.pos += 2,
+                    _ => self.pos += 1,
+                }
+            }
+            self.pos = self.pos.min(len);
+            let end = self.pos;
+            let column = copy.column;
+            let selected = copy.selection.get(column);
+            match self.bytes.get(self.pos) {
+                Some(b'\t') => copy.column += 1,
+                Some(_) => copy.column = 0,
+                None => {}
+            }
+            self.pos = (self.pos + 1).min(len);
+
+            let field = &self.input[start..end];
+            let field = field.strip_suffix('\r').unwrap_or(field);
+            if field.is_empty() || field == "\\N" {
+                continue;
+            }
+            return Some(Literal {
+                range: start..start + field.len(),
+                escapes: Escapes::Copy,
+                selected,
+            });
+        }
+        None
+    }
+
+    fn advance(&mut self, token: &Token) {
+        let input = self.input;
+        let word = match token {
+            Token::Word(range) => &input[range.clone()],
+            _ => "",
+        };
+        let is = |w: &str| word.eq_ignore_ascii_case(w);
+        let any = |words: &[&str]| words.iter().any(|w| is(w));
+        if matches!(token, Token::Punct(b';')) {
+            self.end_statement();
+            return;
+        }
+
+        let st = &mut self.statement;
+        match st.phase {
+            Phase::Start => {
+                (st.kind, st.phase) = if any(&["INSERT", "REPLACE"]) {
+                    (Kind::Insert, Phase::Table)
+                } else if is("CREATE") {
+                    (Kind::Create, Phase::Create)
+                } else if is("COPY") {
+                    (Kind::Copy, Phase::Table)
+                } else {
+                    (Kind::Other, Phase::Other)
+                };
+            }
+            Phase::Create if is("TABLE") => st.phase = Phase::Table,
+            Phase::Create if !any(CREATE_MODIFIERS) => st.phase = Phase::Other,
+            Phase::Create => {}
+            Phase::Table if any(TABLE_MODIFIERS) => {}
+            Phase::Table | Phase::Part => match name(input, token) {
+                Some(part) => {
+                    if st.phase == Phase::Part {
+                        st.table.push('.');
+                    }
+                    st.table.push_str(part);
+                    st.phase = Phase::Name;
+                }
+                None => st.phase = Phase::Other,
+            },
+            Phase::Name => match token {
+                Token::Punct(b'.') => st.phase = Phase::Part,
+                Token::Punct(b'(') if st.kind == Kind::Create => {
+                    st.phase = Phase::Body;
+                    st.depth = 1;
+                    st.definition = true;
+                }
+                Token::Punct(b'(') => st.phase = Phase::Columns,
+                _ => {
+                    self.enter_body();
+                    self.body(token);
+                }
+            },
+            Phase::Columns => match token {
+                Token::Punct(b')') => self.enter_body(),
+                _ => st.columns.extend(name(input, token).map(str::to_string)),
+            },
+            Phase::Body => self.body(token),
+            Phase::Other => {}
+        }
+    }
+
+    fn enter_body(&mut self) {
+        self.statement.phase = Phase::Body;
+        if matches!(self.statement.kind, Kind::Insert | Kind::Copy) {
+            self.statement.selection = self.selection();
+        }
+    }
+
+    fn body(&mut self, token: &Token) {
+        let input = self.input;
+        let st = &mut self.statement;
+        match token {
+            Token::Punct(b'(') => {
+                st.depth += 1;
+                if st.depth == 1 {
+                    st.column = 0;
+                    st.definition = true;
+                }
+            }
+            Token::Punct(b')') => st.depth = st.depth.saturating_sub(1),
+            Token::Punct(b',') if st.depth == 1 => {
+                st.column += 1;
+                st.definition = true;
+            }
+            Token::Word(range) if st.depth == 0 => {
+                let word = &input[range.clone()];
+                match st.kind {
+                    Kind::Insert if word.eq_ignore_ascii_case("VALUES") => st.values = true,
+                    Kind::Insert if word.eq_ignore_ascii_case("VALUE") => st.values = true,
+                    Kind::Copy if word.eq_ignore_ascii_case("STDIN") => st.stdin = true,
+                    _ => {}
+                }
+            }
+            _ if st.depth == 1 && st.definition => {
+                st.definition = false;
+                if st.kind == Kind::Create
+                    && let Some(column) = name(input, token)
+                    && !(matches!(token, Token::Word(_))
+                        && CONSTRAINTS.iter().any(|c| c.eq_ignore_ascii_case(column)))
+                {
+                    st.columns.push(column.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn end_statement(&mut self) {
+        let statement = std::mem::take(&mut self.statement);
+        match statement.kind {
+            Kind::Create if !statement.columns.is_empty() => {
+                self.schemas
+                    .insert(table_key(&statement.table), statement.columns);
+            }
+            Kind::Copy if statement.stdin => {
+                // Data starts on the line after the statement
+                self.skip_line();
+                self.copy = Some(CopyBlock {
+                    column: 0,
+                    selection: statement.selection,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Which values of the current `INSERT` or `COPY` rows are selected.
+    fn selection(&self) -> Selection {
+        if self.tables.is_empty() && self.columns.is_empty() {
+            return Selection::All;
+        }
+        let st = &self.statement;
+        let table = st.table.to_lowercase();
+        let key = table_key(&table);
+        if !self.tables.is_empty()
+            && !self.tables.iter().any(|t| {
+                let t = t.to_lowercase();
+                t == table || t == key
+            })
+        {
+            return Selection::None;
+        }
+        if self.columns.is_empty() {
+            return Selection::All;
+        }
+        let names = if st.columns.is_empty() {
+            self.schemas.get(&key).map_or(&[][..], Vec::as_slice)
+        } else {
+            &st.columns
+        };
+        let numbered = self
+            .columns
+            .iter()
+            .filter_map(|c| c.parse::<usize>().ok())
+            .max()
+            .unwrap_or(0);
+        Selection::Columns(
+            (0..names.len().max(numbered))
+                .map(|i| {
+                    self.columns.iter().any(|c| {
+                        c.parse::<usize>().map_or_else(
+                            |_| {
+                                names
+                                    .get(i)
+                                    .is_some_and(|n| n.to_lowercase() == c.to_lowercase())
+                            },
+                            |n| n == i + 1,
+                        )
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// Whether a literal read now is selected.
+    fn selected(&self) -> bool {
+        if self.tables.is_empty() && self.columns.is_empty() {
+            return true;
+        }
+        let st = &self.statement;
+        st.kind == Kind::Insert
+            && st.phase == Phase::Body
+            && st.values
+            && st.depth > 0
+            && st.selection.get(st.column)
+    }
+}
+
+impl Iterator for Literals<'_> {
+    type Item = Literal;
+
+    fn next(&mut self) -> Option<Literal> {
+        loop {
+            if self.copy.is_some()
+                && let Some(field) = self.copy_field()
+            {
+                return Some(field);
+            }
+            match self.token()? {
+                Token::Str(range, escapes) => {
+                    let selected = self.selected();
+                    self.advance(&Token::Opaque);
+                    return Some(Literal {
+                        range,
+                        escapes,
+                        selected,
+                    });
+                }
+                token => self.advance(&token),
+            }
+        }
+    }
+}
+
+fn is_word_start(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+fn is_word_byte(b: u8) -> bool {
+    is_word_start(b) || b == b'$'
+}
+
+/// The unquoted text of an identifier token.
+fn name<'a>(input: &'a str, token: &Token) -> Option<&'a str> {
+    match token {
+        Token::Word(range) | Token::Quoted(range) => Some(&input[range.clone()]),
+        _ => None,
+    }
+}
+
+/// Lowercase unqualified name of a table.
+fn table_key(table: &str) -> String {
+    table.rsplit('.').next().unwrap_or(table).to_lowercase()
+}
+
+/// Whether the dump comes from MySQL or MariaDB, whose strings take
+/// backslash escapes: by its header, a `/*!` version comment or a
+/// backquoted identifier near the start.
+fn is_mysql(input: &str) -> bool {
+    let head = &input.as_bytes()[..input.len().min(8192)];
+    [&b"MySQL dump"[..], b"MariaDB dump", b"/*!", b"`"]
+        .iter()
+        .any(|marker| head.windows(marker.len()).any(|w| w == *marker))
+}
+
+/// Shields an SQL dump except for the text of its selected string literals
+/// and `COPY` fields. Escapes and doubled quotes stay shielded.
+pub(super) fn build_mask(input: &str, tables: &[String], columns: &[String], mask: &mut [bool]) {
+    mask.fill(true);
+    for literal in Literals::new(input, tables, columns).filter(|l| l.selected) {
+        for run in literal.runs(input) {
+            mask[run.clone()].fill(false);
+            if literal.escapes != Escapes::Copy {
+                for (i, _) in input[run.clone()].match_indices("''") {
+                    mask[run.start + i..run.start + i + 2].fill(true);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use super::data::lines_with_offsets;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Styling inside cue text: `<i>`, `<v Speaker>`, `<c.yellow>`, `<00:01.000>`
+/// timestamps and ASS-style `{\an8}` overrides.
+static RE_CUE_MARKUP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<[^<>\n]*>|\{\\[^{}\n]*\}").unwrap());
+
+/// Shields an SRT or WebVTT file except for the text of its cues.
+/// Cue numbers and identifiers, timing lines with their settings, the
+/// `WEBVTT` header and `NOTE`, `STYLE` and `REGION` blocks stay as written.
+pub(super) fn build_mask(input: &str, mask: &mut [bool]) {
+    mask.fill(true);
+    let mut block: Vec<Range<usize>> = Vec::new();
+
+    for (offset, line) in lines_with_offsets(input) {
+        if line.trim().is_empty() {
+            open_cue(input, &block, mask);
+            block.clear();
+        } else {
+            block.push(offset..offset + line.len());
+        }
+    }
+    open_cue(input, &block, mask);
+}
+
+/// Opens the text lines of a block that has a timing line; blocks without
+/// one are headers or notes.
+fn open_cue(input: &str, block: &[Range<usize>], mask: &mut [bool]) {
+    let Some(timing) = block
+        .iter()
+        .position(|line| input[line.clone()].contains("-->"))
+    else {
+        return;
+    };
+    for line in &block[timing + 1..] {
+        mask[line.clone()].fill(false);
+        for m in RE_CUE_MARKUP.find_iter(&input[line.clone()]) {
+            mask[line.start + m.start()..line.start + m.end()].fill(true);
+        }
+    }
+}
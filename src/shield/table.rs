@@ -0,0 +1,173 @@
+use crate::HujjatTuri;
+use std::ops::Range;
+
+/// A field of a CSV/TSV row.
+#[derive(Debug, Clone)]
+pub(crate) struct Field {
+    /// Raw bytes of the field, quotes included.
+    pub range: Range<usize>,
+    /// The field text without its quotes (`""` escapes are still doubled).
+    /// Bytes after the closing quote are not part of it.
+    pub content: Range<usize>,
+    pub quoted: bool,
+}
+
+/// A CSV/TSV record.
+#[derive(Debug)]
+pub(crate) struct Row {
+    pub fields: Vec<Field>,
+    /// Line terminator of the record (empty at the end of input).
+    pub terminator: Range<usize>,
+}
+
+/// Streams the records of a CSV/TSV document. Quoted fields may contain
+/// delimiters, doubled quotes and line breaks.
+pub(crate) struct Rows<'a> {
+    bytes: &'a [u8],
+    delimiter: u8,
+    pos: usize,
+}
+
+impl<'a> Rows<'a> {
+    pub(crate) fn new(input: &'a str, delimiter: u8) -> Self {
+        Self {
+            bytes: input.as_bytes(),
+            delimiter,
+            pos: 0,
+        }
+    }
+
+    fn field(&mut self) -> Field {
+        let start = self.pos;
+        let quoted = self.bytes.get(start) == Some(&b'"');
+        let mut content = start..start;
+        if quoted {
+            self.pos += 1;
+            content = self.pos..self.bytes.len();
+            while let Some(&b) = self.bytes.get(self.pos) {
+                self.pos += 1;
+                if b == b'"' {
+                    if self.bytes.get(self.pos) != Some(&b'"') {
+                        content.end = self.pos - 1;
+                        break;
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+        // Unquoted text, or stray bytes after a closing quote
+        while let Some(&b) = self.bytes.get(self.pos) {
+            if b == self.delimiter || b == b'\n' || b == b'\r' {
+                break;
+            }
+            self.pos += 1;
+        }
+        if !quoted {
+            content.end = self.pos;
+        }
+        Field {
+            range: start..self.pos,
+            content,
+            quoted,
+        }
+    }
+}
+
+impl Iterator for Rows<'_> {
+    type Item = Row;
+
+    fn next(&mut self) -> Option<Row> {
+        if self.pos >= self.bytes.len() {
+            return None;
+        }
+        let mut fields = vec![self.field()];
+        while self.bytes.get(self.pos) == Some(&self.delimiter) {
+            self.pos += 1;
+            fields.push(self.field());
+        }
+        let start = self.pos;
+        if self.bytes.get(self.pos) == Some(&b'\r') {
+            self.pos += 1;
+        }
+        if self.bytes.get(self.pos) == Some(&b'\n') {
+            self.pos += 1;
+        }
+        Some(Row {
+            fields,
+            terminator: start..self.pos,
+        })
+    }
+}
+
+/// Tab for TSV. For CSV, a semicolon when the header has more semicolons
+/// than commas (spreadsheets in locales with a decimal comma).
+pub(crate) fn delimiter(input: &str, hujjat: HujjatTuri) -> u8 {
+    if hujjat == HujjatTuri::Tsv {
+        return b'\t';
+    }
+    let header = input.lines().next().unwrap_or("");
+    if header.matches(';').count() > header.matches(',').count() {
+        b';'
+    } else {
+        b','
+    }
+}
+
+/// The name of a header field: without a byte order mark, surrounding
+/// whitespace and quote escapes.
+pub(crate) fn column_name(input: &str, field: &Field) -> String {
+    let name = input[field.content.clone()]
+        .trim_start_matches('\u{FEFF}')
+        .trim();
+    if field.quoted {
+        name.replace("\"\"", "\"")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Which columns of `header` are converted: those named in `columns` or
+/// given by their 1-based index, or every column when `columns` is empty.
+pub(crate) fn selected_columns(input: &str, header: &Row, columns: &[String]) -> Vec<bool> {
+    header
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let name = column_name(input, field);
+            columns.is_empty()
+                || columns.iter().any(|c| {
+                    c.parse::<usize>()
+                        .map_or_else(|_| c.to_lowercase() == name.to_lowercase(), |n| n == i + 1)
+                })
+        })
+        .collect()
+}
+
+/// Shields a CSV/TSV document except for the selected columns of its
+/// records. The header row and doubled quotes stay shielded.
+pub(super) fn build_mask(input: &str, hujjat: HujjatTuri, columns: &[String], mask: &mut [bool]) {
+    mask.fill(true);
+    let mut rows = Rows::new(input, delimiter(input, hujjat));
+    let Some(header) = rows.next() else {
+        return;
+    };
+    let selected = selected_columns(input, &header, columns);
+
+    for row in rows {
+        for (field, _) in row
+            .fields
+            .iter()
+            .zip(&selected)
+            .filter(|(_, selected)| **selected)
+        {
+            let content = field.content.clone();
+            mask[content.clone()].fill(false);
+            if field.quoted {
+                for (i, _) in input[content.clone()].match_indices("\"\"") {
+                    mask[content.start + i..content.start + i + 2].fill(true);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use super::Source;
+use crate::Qalqon;
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+const UNITS: &str = r"(?:km|cm|mm|nm|µm|m|kg|mg|g|ml|l|kWh|kW|MW|W|mAh|mA|A|kV|V|Hz|kHz|MHz|GHz|KB|MB|GB|TB|Mbit|Gbit|px|pt|dB|°C|°F)(?:[23]?\b|[²³])";
+const PER: &str = r"(?:soat|sek|daq|min|s|h)";
+
+/// A unit after a number (`5 mg`, `90km/soat`) or a compound unit anywhere
+/// (`km/soat`).
+static RE_UNIT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(&format!(
+        r"\d\s?({UNITS}(?:/(?:{UNITS}|{PER}\b))?)|\b({UNITS}/(?:{UNITS}|{PER}\b))"
+    ))
+    .unwrap()
+});
+/// Candidate formulas; the element symbols are checked afterwards.
+static RE_FORMULA: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:[A-Z][a-z]?\d*){2,}\b").unwrap());
+/// `v2`, `v1.2` or a dotted version with at least three parts (`1.2.3-rc.1`).
+static RE_VERSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:v\d+(?:\.\d+)*|\d+(?:\.\d+){2,})(?:-[0-9A-Za-z]+(?:\.[0-9A-Za-z]+)*)?\b")
+        .unwrap()
+});
+static RE_FILE_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b[\w-]+(?:\.[\w-]+)*\.(?:txt|md|pdf|docx?|xlsx?|pptx?|odt|ods|csv|json|ya?ml|toml|xml|html?|css|js|ts|rs|py|go|java|php|rb|cpp|sh|bat|exe|zip|rar|7z|tar|gz|png|jpe?g|gif|svg|webp|mp3|mp4|avi|mkv|mov|wav|sql|log|ini|cfg|conf|lock)\b",
+    )
+    .unwrap()
+});
+/// snake_case, SCREAMING_CASE and camelCase. PascalCase is left out: it is
+/// how brand names are written, and those have dictionary entries.
+static RE_IDENTIFIER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b(?:[A-Za-z][A-Za-z0-9]*(?:_[A-Za-z0-9]+)+|[a-z][a-z0-9]*(?:[A-Z][a-z0-9]*)+)\b")
+        .unwrap()
+});
+/// UUIDs, `0x` literals, `#rgb` colours and long hex strings (hashes).
+static RE_HEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}\b|\b0x[0-9a-f]+\b|#(?:[0-9a-f]{6}|[0-9a-f]{3})\b|\b[0-9a-f]{7,}\b",
+    )
+    .unwrap()
+});
+
+#[rustfmt::skip]
+const ELEMENTS: &[&str] = &[
+    "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S",
+    "Cl", "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga",
+    "Ge", "As", "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd",
+    "Ag", "Cd", "In", "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm",
+    "Sm", "Eu", "Gd", "Tb", "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os",
+    "Ir", "Pt", "Au", "Hg", "Tl", "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa",
+    "U", "Np", "Pu", "Am", "Cm", "Bk", "Cf", "Es", "Fm", "Md", "No", "Lr",
+];
+
+/// The technical token shields in the order they are applied.
+pub(super) const KINDS: [(Qalqon, Source); 6] = [
+    (Qalqon::OnOltilik, Source::Hex),
+    (Qalqon::Versiya, Source::Version),
+    (Qalqon::FaylNomi, Source::FileName),
+    (Qalqon::Birlik, Source::Unit),
+    (Qalqon::KimyoviyFormula, Source::Formula),
+    (Qalqon::Identifikator, Source::Identifier),
+];
+
+/// Spans of the technical tokens recognised by `qalqon`.
+pub(super) fn find(qalqon: Qalqon, input: &str) -> Vec<Range<usize>> {
+    match qalqon {
+        Qalqon::Birlik => RE_UNIT
+            .captures_iter(input)
+            .filter_map(|cap| cap.get(1).or_else(|| cap.get(2)))
+            .map(|m| m.range())
+            .collect(),
+        Qalqon::KimyoviyFormula => ranges(&RE_FORMULA, input, is_formula),
+        Qalqon::Versiya => ranges(&RE_VERSION, input, |_| true),
+        Qalqon::FaylNomi => ranges(&RE_FILE_NAME, input, |_| true),
+        Qalqon::Identifikator => ranges(&RE_IDENTIFIER, input, |_| true),
+        Qalqon::OnOltilik => ranges(&RE_HEX, input, is_hex),
+        _ => Vec::new(),
+    }
+}
+
+fn ranges(re: &Regex, input: &str, keep: fn(&str) -> bool) -> Vec<Range<usize>> {
+    re.find_iter(input)
+        .filter(|m| keep(m.as_str()))
+        .map(|m| m.range())
+        .collect()
+}
+
+/// Element symbols only, with a count or a two-letter symbol, so that
+/// abbreviations such as `SHAHAR` are not taken for formulas.
+fn is_formula(token: &str) -> bool {
+    let mut has_detail = false;
+    let mut rest = token;
+    while let Some(first) = rest.chars().next() {
+        if first.is_ascii_digit() {
+            has_detail = true;
+            rest = &rest[1..];
+            continue;
+        }
+        let len = if rest[1..].starts_with(|c: char| c.is_ascii_lowercase()) {
+            2
+        } else {
+            1
+        };
+        if !ELEMENTS.contains(&&rest[..len]) {
+            return false;
+        }
+        has_detail |= len == 2;
+        rest = &rest[len..];
+    }
+    has_detail
+}
+
+/// Bare hex strings must mix digits and letters, so words such as
+/// `facade` or plain numbers are not matched.
+fn is_hex(token: &str) -> bool {
+    let prefixed = token.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("0x"));
+    if prefixed || token.starts_with('#') || token.contains('-') {
+        return true;
+    }
+    token.bytes().any(|b| b.is_ascii_digit()) && token.bytes().any(|b| b.is_ascii_alphabetic())
+}
@@ -0,0 +1,64 @@
+use std::ops::Range;
+
+/// A matched universal shield: the whole region and the markers inside it.
+pub(super) struct Region {
+    pub end: usize,
+    /// Every opening and closing marker, in source order.
+    pub markers: Vec<Range<usize>>,
+}
+
+/// Index of the marker pair whose opener starts at `pos`.
+pub(super) fn opener_at(input: &str, pos: usize, pairs: &[(String, String)]) -> Option<usize> {
+    let rest = &input.as_bytes()[pos..];
+    pairs
+        .iter()
+        .position(|(open, _)| !open.is_empty() && rest.starts_with(open.as_bytes()))
+}
+
+/// Length of the closing marker starting at `pos`, if any.
+pub(super) fn closer_at(input: &str, pos: usize, pairs: &[(String, String)]) -> Option<usize> {
+    let rest = &input.as_bytes()[pos..];
+    pairs
+        .iter()
+        .find(|(_, close)| !close.is_empty() && rest.starts_with(close.as_bytes()))
+        .map(|(_, close)| close.len())
+}
+
+/// Finds the end of the shield opened by pair `idx` at `pos`. Nested
+/// shields (of any pair) must be closed first; a closer of another pair
+/// is ordinary content. Returns `None` for an unterminated shield.
+pub(super) fn region(
+    input: &str,
+    pos: usize,
+    idx: usize,
+    pairs: &[(String, String)],
+) -> Option<Region> {
+    let bytes = input.as_bytes();
+    let mut stack = vec![idx];
+    let mut i = pos + pairs[idx].0.len();
+    let mut markers = Vec::new();
+    markers.push(pos..i);
+
+    while i < bytes.len() {
+        let top = *stack.last()?;
+        let close = pairs[top].1.as_bytes();
+        if bytes[i..].starts_with(close) {
+            markers.push(i..i + close.len());
+            i += close.len();
+            stack.pop();
+            if stack.is_empty() {
+                return Some(Region { end: i, markers });
+            }
+            continue;
+        }
+        if let Some(inner) = opener_at(input, i, pairs) {
+            let len = pairs[inner].0.len();
+            markers.push(i..i + len);
+            stack.push(inner);
+            i += len;
+            continue;
+        }
+        i += 1;
+    }
+    None
+}
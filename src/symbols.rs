@@ -94,4 +94,42 @@ pub const MAP_1_TO_1: &[(char, &str)] = &[
 
 pub const TRANSLITERABLE_ATTRIBUTES: &[&str] = &["content", "title", "alt", "placeholder", "label"];
 
+/// Front matter keys whose values are converted by default.
+pub const FRONT_MATTER_KEYS: &[&str] = &["title", "description", "tags"];
+
 pub const FULLY_PROTECTED_TAGS: &[&str] = &["script", "style", "code", "pre"];
+
+/// Default LaTeX command table. `{s}`/`{c}` is a required argument that is
+/// shielded/converted, `[s]`/`[c]` an optional one, and `*` shields every
+/// following group. Commands with converted arguments also open text
+/// windows inside math (`$\text{...}$`).
+pub const LATEX_COMMANDS: &[(&str, &str)] = &[
+    ("label", "{s}"),
+    ("ref", "{s}"),
+    ("eqref", "{s}"),
+    ("pageref", "{s}"),
+    ("cite", "[c][c]{s}"),
+    ("include", "{s}"),
+    ("input", "{s}"),
+    ("includegraphics", "[s]{s}"),
+    ("usepackage", "[s]{s}"),
+    ("documentclass", "[s]{s}"),
+    ("begin", "*"),
+    ("end", "{s}"),
+    ("url", "{s}"),
+    ("href", "{s}{c}"),
+    ("selectlanguage", "{s}"),
+    ("foreignlanguage", "[s]{s}{c}"),
+    ("setmainlanguage", "[s]{s}"),
+    ("setdefaultlanguage", "[s]{s}"),
+    ("setotherlanguage", "[s]{s}"),
+    ("text", "{c}"),
+    ("textrm", "{c}"),
+    ("textit", "{c}"),
+    ("textbf", "{c}"),
+    ("textnormal", "{c}"),
+    ("mbox", "{c}"),
+    ("hbox", "{c}"),
+    ("intertext", "{c}"),
+    ("shortintertext", "{c}"),
+];
@@ -1,6 +1,8 @@
 use crate::engine::Engine;
 use crate::validator::Validator;
-use crate::{HabarKaliti, Sozlama, Tartib, TekshiruvHulosasi};
+use crate::{
+    HabarKaliti, HujjatTuri, Koerinish, Sozlama, Tartib, TekshiruvHatosi, TekshiruvHulosasi,
+};
 use std::borrow::Cow;
 use std::io;
 
@@ -49,6 +51,46 @@ impl Oegirgich {
         engine.convert_stream(input, writer)
     }
 
+    /// Streams `input` as a document of the given type, overriding `sozlama.hujjat`.
+    /// Returns the unbalanced universal shield markers left as plain text.
+    pub fn hujjatni_oegir<'a, W: io::Write + ?Sized>(
+        &self,
+        input: &'a str,
+        turi: HujjatTuri,
+        writer: &mut W,
+    ) -> io::Result<Vec<TekshiruvHatosi<'a>>> {
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+        let engine = Engine::with_document(&self.sozlama, turi);
+        engine.convert_reporting(input, writer)
+    }
+
+    /// Unbalanced universal shield markers that conversion of `input` as a
+    /// document of the given type would leave as plain text. Converts `input`
+    /// to discard it; use the result of `hujjatni_oegir` when converting anyway.
+    #[must_use]
+    pub fn ogohlantirishlar<'a>(
+        &self,
+        input: &'a str,
+        turi: HujjatTuri,
+    ) -> Vec<TekshiruvHatosi<'a>> {
+        self.hujjatni_oegir(input, turi, &mut io::sink())
+            .unwrap_or_default()
+    }
+
+    /// Renders `input` with the shielded spans highlighted and labelled with
+    /// the shield that produced them, for finding out why text was not converted.
+    #[must_use]
+    pub fn qalqonlarni_koersat(
+        &self,
+        input: &str,
+        turi: HujjatTuri,
+        koerinish: Koerinish,
+    ) -> String {
+        crate::debug::render(input, &self.sozlama, turi, koerinish)
+    }
+
     #[must_use]
     pub fn tekshir<'a>(&self, input: &'a str, limit: usize) -> TekshiruvHulosasi<'a> {
         if input.is_empty() {
@@ -61,4 +103,23 @@ impl Oegirgich {
         let validator = Validator::new(&self.sozlama);
         validator.check_errors(input, limit)
     }
+
+    /// Validates `input` as a document of the given type, overriding `sozlama.hujjat`.
+    #[must_use]
+    pub fn hujjatni_tekshir<'a>(
+        &self,
+        input: &'a str,
+        turi: HujjatTuri,
+        limit: usize,
+    ) -> TekshiruvHulosasi<'a> {
+        if input.is_empty() {
+            return TekshiruvHulosasi {
+                hatolar: vec![],
+                jami: 0,
+            };
+        }
+
+        let validator = Validator::with_document(&self.sozlama, turi);
+        validator.check_errors(input, limit)
+    }
 }
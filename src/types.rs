@@ -28,4 +28,566 @@ pub enum Tartib {
 pub(crate) enum Chunk<'a> {
     Safe(&'a str),
     Shielded(&'a str),
+    /// Convertible markup attribute value. The converted text is re-escaped
+    /// so it cannot terminate the surrounding quotes.
+    Attribute(&'a str, AttrQuote),
+    /// Convertible source code string literal. Converted text escapes the
+    /// delimiting quote with a backslash.
+    Literal(&'a str, char),
+    /// Shielded `shakl` literal, written as the dictionary literal at the
+    /// given index instead of in its input case.
+    Recased(&'a str, usize),
+    /// Convertible text written as `\uXXXX` escapes in a data document. It
+    /// is decoded for conversion and the result is escaped again.
+    Escaped(&'a str),
+}
+
+/// Quoting style of a markup attribute value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttrQuote {
+    Double,
+    Single,
+    Unquoted,
+}
+
+impl AttrQuote {
+    /// Escapes characters that would end a value written with this quoting.
+    pub(crate) fn escape(self, text: &str) -> Cow<'_, str> {
+        let needs_escape = |c: char| match self {
+            Self::Double => c == '"',
+            Self::Single => c == '\'',
+            Self::Unquoted => matches!(c, '"' | '\'' | '`' | '=' | '<' | '>'),
+        };
+        if !text.contains(needs_escape) {
+            return Cow::Borrowed(text);
+        }
+
+        let mut escaped = String::with_capacity(text.len() + 8);
+        for c in text.chars() {
+            if needs_escape(c) {
+                escaped.push_str(&format!("&#{};", c as u32));
+            } else {
+                escaped.push(c);
+            }
+        }
+        Cow::Owned(escaped)
+    }
+}
+
+/// Escapes `quote` inside converted string literal content.
+pub(crate) fn escape_literal(text: &str, quote: char) -> Cow<'_, str> {
+    if !text.contains(quote) {
+        return Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 4);
+    for c in text.chars() {
+        if c == quote {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    Cow::Owned(escaped)
+}
+
+/// Decodes `\uXXXX` (including surrogate pairs) and `\UXXXXXXXX` escapes.
+pub(crate) fn decode_unicode_escapes(text: &str) -> String {
+    let mut units: Vec<u16> = Vec::new();
+    let mut decoded = String::with_capacity(text.len() / 3);
+    let mut rest = text;
+    while let Some(escape) = rest.strip_prefix('\\') {
+        let (len, wide) = if escape.starts_with('U') {
+            (8, true)
+        } else {
+            (4, false)
+        };
+        let Some(code) = escape
+            .get(1..=len)
+            .and_then(|h| u32::from_str_radix(h, 16).ok())
+        else {
+            break;
+        };
+        if wide {
+            decoded.extend(char::decode_utf16(units.drain(..)).map(|c| c.unwrap_or('\u{FFFD}')));
+            decoded.extend(char::from_u32(code));
+        } else {
+            units.push(code as u16);
+        }
+        rest = &escape[len + 1..];
+    }
+    decoded.extend(char::decode_utf16(units).map(|c| c.unwrap_or('\u{FFFD}')));
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Escapes the non-ASCII characters of converted text the way `original`
+/// was escaped: same hex case (lowercase unless `original` used uppercase
+/// digits), and `\U` or surrogate pairs beyond the BMP.
+pub(crate) fn encode_unicode_escapes(text: &str, original: &str) -> String {
+    let lower = !original.contains(|c: char| matches!(c, 'A'..='F'));
+    let wide = original.contains("\\U");
+    let mut encoded = String::with_capacity(text.len() * 6);
+    for c in text.chars() {
+        if c.is_ascii() {
+            encoded.push(c);
+        } else if wide && u32::from(c) > 0xFFFF {
+            encoded.push_str(&hex_escape('U', u32::from(c), 8, lower));
+        } else {
+            for unit in c.encode_utf16(&mut [0; 2]) {
+                encoded.push_str(&hex_escape('u', u32::from(*unit), 4, lower));
+            }
+        }
+    }
+    encoded
+}
+
+fn hex_escape(prefix: char, code: u32, width: usize, lower: bool) -> String {
+    if lower {
+        format!("\\{prefix}{code:0width$x}")
+    } else {
+        format!("\\{prefix}{code:0width$X}")
+    }
+}
+
+/// A key path selector for data documents, such as `messages.*.text`.
+/// `*` matches one key or array index, `**` any number of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyPattern(Vec<String>);
+
+impl KeyPattern {
+    pub(crate) fn parse(pattern: &str) -> Option<Self> {
+        let segments: Vec<String> = pattern.trim().split('.').map(str::to_string).collect();
+        segments
+            .iter()
+            .all(|s| !s.is_empty())
+            .then_some(Self(segments))
+    }
+
+    pub(crate) fn matches(&self, path: &[String]) -> bool {
+        fn matches(pattern: &[String], path: &[String]) -> bool {
+            match pattern.split_first() {
+                None => path.is_empty(),
+                Some((first, rest)) if first == "**" => {
+                    (0..=path.len()).any(|skip| matches(rest, &path[skip..]))
+                }
+                Some((first, rest)) => path.split_first().is_some_and(|(key, tail)| {
+                    (first == "*" || first == key) && matches(rest, tail)
+                }),
+            }
+        }
+        matches(&self.0, path)
+    }
+}
+
+/// How a LaTeX command argument is treated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LatexArg {
+    /// `{s}` / `{c}`: required braced argument, shielded or converted.
+    Required(bool),
+    /// `[s]` / `[c]`: optional bracketed argument, shielded or converted.
+    Optional(bool),
+    /// `*`: every following `{...}` or `[...]` group is shielded.
+    Rest,
+}
+
+impl LatexArg {
+    /// Parses an argument spec such as `[s]{s}{c}`.
+    /// Returns `None` for malformed specs.
+    pub(crate) fn parse_spec(spec: &str) -> Option<Vec<Self>> {
+        let spec: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut args = Vec::new();
+        let mut rest = spec.as_str();
+
+        while !rest.is_empty() {
+            let (arg, len) = match rest.as_bytes() {
+                [b'*', ..] => (Self::Rest, 1),
+                [b'{', mode, b'}', ..] => (Self::Required(parse_mode(*mode)?), 3),
+                [b'[', mode, b']', ..] => (Self::Optional(parse_mode(*mode)?), 3),
+                _ => return None,
+            };
+            args.push(arg);
+            rest = &rest[len..];
+        }
+        Some(args)
+    }
+}
+
+/// `c` converts, `s` shields.
+fn parse_mode(mode: u8) -> Option<bool> {
+    match mode {
+        b'c' => Some(true),
+        b's' => Some(false),
+        _ => None,
+    }
+}
+
+/// A validator rule that inline directives can suppress
+/// (`<!-- latinga: off tutuq -->`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Rule {
+    /// Apostrophe characters (`tutuq`).
+    Apostrophe,
+    /// Ambiguous `sh`/`ch` letter pairs (`qoesh-harf`).
+    Digraph,
+    /// Letters of the old orthography (`eski-harf`).
+    OldLetter,
+    /// Unbalanced universal shield markers (`qalqon`).
+    Marker,
+}
+
+impl Rule {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "tutuq" => Some(Self::Apostrophe),
+            "qoesh-harf" => Some(Self::Digraph),
+            "eski-harf" => Some(Self::OldLetter),
+            "qalqon" => Some(Self::Marker),
+            _ => None,
+        }
+    }
+}
+
+/// Root elements of the Office Open XML parts that hold document text.
+const OFFICE_ROOTS: &[&str] = &[
+    "w:document",
+    "w:hdr",
+    "w:ftr",
+    "w:footnotes",
+    "w:endnotes",
+    "w:comments",
+    "p:sld",
+    "p:notes",
+    "sst",
+    "worksheet",
+];
+
+/// Document type of the input. Selects which structural shields
+/// `TokenIterator` applies before text reaches the engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HujjatTuri {
+    /// Legacy behaviour: every built-in shield runs on every input.
+    #[default]
+    Umumiy,
+    /// Plain text. `$`, `%`, `\` and `<` are ordinary characters.
+    Matn,
+    /// CommonMark document. Prose, link text, alt text and titles are
+    /// converted; URLs, code, anchors and raw HTML attributes are shielded.
+    Markdown,
+    /// HTML document. Only elements in Uzbek (or without a language tag)
+    /// are converted, and their `lang` is relabelled as `uz-Latn`.
+    Html,
+    /// XML document, tracked through `xml:lang` like HTML.
+    Xml,
+    /// LaTeX document. Math, commands and verbatim environments are shielded.
+    Latex,
+    /// JSON (and JSONC) document: only string values are converted.
+    Json,
+    /// YAML document: only string values are converted.
+    Yaml,
+    /// TOML document: only string values are converted.
+    Toml,
+    /// gettext PO/POT catalog: only translations are converted.
+    Po,
+    /// SRT or WebVTT subtitles: only cue text is converted.
+    Subtitr,
+    /// Comma- (or semicolon-) separated table: only the selected columns
+    /// are converted, row by row.
+    Csv,
+    /// Tab-separated table, converted like `Csv`.
+    Tsv,
+    /// A part of a DOCX, XLSX or PPTX package (`word/document.xml`,
+    /// `xl/sharedStrings.xml`, `ppt/slides/slide1.xml`, ...): only run text
+    /// is converted, paragraph by paragraph, and Uzbek run languages are
+    /// relabelled as `uz-Latn`.
+    Ofis,
+    /// A part of an ODT, ODS or ODP package (`content.xml`, `styles.xml`,
+    /// `meta.xml`) or a flat OpenDocument file: paragraph text is converted
+    /// across `text:span` boundaries and Uzbek text properties are switched
+    /// to the Latin script.
+    Odf,
+    /// The package document (OPF) or NCX table of contents of an EPUB:
+    /// titles, authors, descriptions, subjects and NCX labels are converted
+    /// and Uzbek languages are relabelled as `uz-Latn`. Chapters are `Html`.
+    Epub,
+    /// A FictionBook (FB2) e-book: the bodies and the title, authors,
+    /// annotation and language of `title-info` are converted; code,
+    /// binaries and the other metadata blocks are not.
+    Fb2,
+    /// An RTF document: text runs are decoded from `\'hh` code-page and
+    /// `\uN` escapes, converted a paragraph at a time and written back
+    /// with Unicode escapes.
+    Rtf,
+    /// A MySQL or PostgreSQL dump: only string literals and `COPY` data
+    /// are converted, optionally those of chosen tables and columns.
+    Sql,
+    /// Social media and messenger posts. Domains, @usernames, /commands,
+    /// #hashtags and emoji sequences are shielded.
+    Ijtimoiy,
+    /// Program source. Only string literal contents (and comments, if
+    /// enabled) are converted.
+    Kod(KodTili),
+    /// App localization resource: only translatable values are converted.
+    Tarjima(TarjimaFormati),
+}
+
+/// Programming language of a source file in `HujjatTuri::Kod` mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KodTili {
+    Rust,
+    Python,
+    /// JavaScript and TypeScript.
+    JavaScript,
+    Java,
+    Php,
+    Go,
+}
+
+/// Localization resource format in `HujjatTuri::Tarjima` mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TarjimaFormati {
+    /// Android `strings.xml`: `string`, `plurals` and `string-array`.
+    Android,
+    /// iOS/macOS `.strings`.
+    Strings,
+    /// iOS/macOS `.stringsdict` plural rules.
+    Stringsdict,
+    /// Flutter ARB (JSON with ICU messages).
+    Arb,
+    /// Java `.properties`.
+    Properties,
+    /// XLIFF 1.2 and 2.0.
+    Xliff,
+}
+
+impl HujjatTuri {
+    /// Parses a profile name such as `markdown` or `python`.
+    /// Both Uzbek and English names are accepted.
+    #[must_use]
+    pub fn nomdan(nom: &str) -> Option<Self> {
+        let turi = match nom.trim().to_ascii_lowercase().as_str() {
+            "umumiy" | "general" => Self::Umumiy,
+            "matn" | "plain" | "text" | "txt" => Self::Matn,
+            "markdown" | "md" => Self::Markdown,
+            "html" | "htm" => Self::Html,
+            "xml" => Self::Xml,
+            "latex" | "tex" => Self::Latex,
+            "ijtimoiy" | "social" | "telegram" => Self::Ijtimoiy,
+            "json" | "jsonc" => Self::Json,
+            "yaml" | "yml" => Self::Yaml,
+            "toml" => Self::Toml,
+            "po" | "pot" | "gettext" => Self::Po,
+            "subtitr" | "subtitle" | "srt" | "vtt" | "webvtt" => Self::Subtitr,
+            "csv" => Self::Csv,
+            "tsv" | "tab" => Self::Tsv,
+            "ofis" | "office" | "ooxml" | "docx" | "xlsx" | "pptx" => Self::Ofis,
+            "odf" | "opendocument" | "odt" | "ods" | "odp" => Self::Odf,
+            "epub" | "opf" | "ncx" => Self::Epub,
+            "fb2" | "fictionbook" => Self::Fb2,
+            "rtf" => Self::Rtf,
+            "sql" | "mysql" | "postgresql" => Self::Sql,
+            "rust" | "rs" => Self::Kod(KodTili::Rust),
+            "python" | "py" => Self::Kod(KodTili::Python),
+            "javascript" | "js" | "typescript" | "ts" => Self::Kod(KodTili::JavaScript),
+            "java" => Self::Kod(KodTili::Java),
+            "php" => Self::Kod(KodTili::Php),
+            "go" => Self::Kod(KodTili::Go),
+            "android" => Self::Tarjima(TarjimaFormati::Android),
+            "strings" | "ios" => Self::Tarjima(TarjimaFormati::Strings),
+            "stringsdict" => Self::Tarjima(TarjimaFormati::Stringsdict),
+            "arb" | "flutter" => Self::Tarjima(TarjimaFormati::Arb),
+            "properties" => Self::Tarjima(TarjimaFormati::Properties),
+            "xliff" | "xlf" => Self::Tarjima(TarjimaFormati::Xliff),
+            _ => return None,
+        };
+        Some(turi)
+    }
+
+    /// Guesses the document type from the beginning of the text: an XML
+    /// declaration (and its root element), an HTML doctype, `<?php`, a LaTeX
+    /// preamble, front matter, a `WEBVTT`, RTF or SQL dump header or a
+    /// script shebang.
+    /// Returns `None` when unsure.
+    #[must_use]
+    pub fn matndan_aniqla(matn: &str) -> Option<Self> {
+        let head = matn.trim_start_matches('\u{feff}');
+        let first_line = head.lines().next().unwrap_or("").trim_end();
+
+        if let Some(interpreter) = first_line.strip_prefix("#!") {
+            if interpreter.contains("python") {
+                return Some(Self::Kod(KodTili::Python));
+            }
+            if ["node", "deno", "bun"]
+                .iter()
+                .any(|i| interpreter.contains(i))
+            {
+                return Some(Self::Kod(KodTili::JavaScript));
+            }
+            return None;
+        }
+        if first_line.starts_with("WEBVTT") {
+            return Some(Self::Subtitr);
+        }
+        if head.lines().take(3).any(|line| {
+            [
+                "-- MySQL dump",
+                "-- MariaDB dump",
+                "-- PostgreSQL database dump",
+            ]
+            .iter()
+            .any(|header| line.starts_with(header))
+        }) {
+            return Some(Self::Sql);
+        }
+        if first_line == "---" || first_line == "+++" {
+            return Some(Self::Markdown);
+        }
+
+        let start = head.trim_start();
+        let starts_with = |prefix: &str| {
+            start
+                .as_bytes()
+                .get(..prefix.len())
+                .is_some_and(|b| b.eq_ignore_ascii_case(prefix.as_bytes()))
+        };
+        if start.starts_with("{\\rtf") {
+            return Some(Self::Rtf);
+        }
+        if starts_with("<?php") {
+            return Some(Self::Kod(KodTili::Php));
+        }
+        if starts_with("<!doctype html") || starts_with("<html") {
+            return Some(Self::Html);
+        }
+        if starts_with("<?xml") {
+            // The root element tells resource formats from plain XML
+            let root = start
+                .split('<')
+                .find(|tag| tag.starts_with(|c: char| c.is_ascii_alphabetic()))
+                .unwrap_or("");
+            return Some(if root.starts_with("xliff") {
+                Self::Tarjima(TarjimaFormati::Xliff)
+            } else if root.starts_with("resources") {
+                Self::Tarjima(TarjimaFormati::Android)
+            } else if OFFICE_ROOTS.iter().any(|r| root.starts_with(r)) {
+                Self::Ofis
+            } else if root.starts_with("office:document") {
+                Self::Odf
+            } else if root.starts_with("package") || root.starts_with("ncx") {
+                Self::Epub
+            } else if root.starts_with("FictionBook") {
+                Self::Fb2
+            } else {
+                Self::Xml
+            });
+        }
+        if start.starts_with("\\documentclass") || head.contains("\\begin{document}") {
+            return Some(Self::Latex);
+        }
+        None
+    }
+
+    /// Whether a built-in shield runs for this document type unless
+    /// overridden with `Sozlama::qalqonni_belgila`.
+    #[must_use]
+    pub fn qalqon_fitrati(self, qalqon: Qalqon) -> bool {
+        match qalqon {
+            Qalqon::Latex => matches!(self, Self::Umumiy | Self::Latex),
+            // Cue markup is shielded by the subtitle parser
+            Qalqon::Html => !matches!(
+                self,
+                Self::Matn | Self::Latex | Self::Ijtimoiy | Self::Subtitr
+            ),
+            // Markdown and source files locate their code on their own
+            Qalqon::KodBloki => matches!(self, Self::Umumiy | Self::Ijtimoiy),
+            Qalqon::Ijtimoiy => self == Self::Ijtimoiy,
+            // Keys are already shielded; `a=b` inside values is prose
+            Qalqon::KalitQiymat => !matches!(
+                self,
+                Self::Json | Self::Yaml | Self::Toml | Self::Tarjima(_)
+            ),
+            Qalqon::Umumiy
+            | Qalqon::Url
+            | Qalqon::Email
+            | Qalqon::RimRaqami
+            | Qalqon::HtmlBelgisi
+            | Qalqon::Birlik
+            | Qalqon::KimyoviyFormula
+            | Qalqon::Versiya
+            | Qalqon::FaylNomi
+            | Qalqon::Identifikator
+            | Qalqon::OnOltilik => true,
+        }
+    }
+}
+
+/// A built-in shield that can be switched on or off individually.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Qalqon {
+    /// `{] ... [}` markers.
+    Umumiy,
+    /// Math, commands, comments and verbatim environments.
+    Latex,
+    /// Tags, comments and protected elements.
+    Html,
+    Url,
+    Email,
+    /// Upper-case Roman numerals (`XIX`, `IV`).
+    RimRaqami,
+    /// Character references such as `&amp;`.
+    HtmlBelgisi,
+    /// `key=value` pairs.
+    KalitQiymat,
+    /// Markdown-style code spans and fences in general text.
+    KodBloki,
+    /// Scheme-less domains, @usernames, /commands, #hashtags and emoji.
+    Ijtimoiy,
+    /// Units after a number (`5 mg`) and compound units (`km/soat`).
+    Birlik,
+    /// Chemical formulas such as `H2O` and `NaCl`.
+    KimyoviyFormula,
+    /// Version strings such as `v1.2` and `1.2.3-beta`.
+    Versiya,
+    /// File names with a known extension (`hisobot.xlsx`).
+    FaylNomi,
+    /// `snake_case`, `SCREAMING_CASE` and `camelCase` identifiers.
+    Identifikator,
+    /// UUIDs, `0x` literals, `#rgb` colours and hashes.
+    OnOltilik,
+}
+
+impl Qalqon {
+    /// Parses a shield name such as `latex` or `rim`.
+    /// Both Uzbek and English names are accepted.
+    #[must_use]
+    pub fn nomdan(nom: &str) -> Option<Self> {
+        let qalqon = match nom.trim().to_ascii_lowercase().as_str() {
+            "umumiy" | "universal" => Self::Umumiy,
+            "latex" | "tex" => Self::Latex,
+            "html" => Self::Html,
+            "url" => Self::Url,
+            "email" => Self::Email,
+            "rim" | "roman" => Self::RimRaqami,
+            "belgi" | "entity" => Self::HtmlBelgisi,
+            "kalit-qiymat" | "key-value" => Self::KalitQiymat,
+            "kod" | "code" => Self::KodBloki,
+            "ijtimoiy" | "social" => Self::Ijtimoiy,
+            "birlik" | "unit" => Self::Birlik,
+            "formula" | "chemical" => Self::KimyoviyFormula,
+            "versiya" | "version" => Self::Versiya,
+            "fayl" | "file" => Self::FaylNomi,
+            "identifikator" | "identifier" => Self::Identifikator,
+            "hex" | "uuid" => Self::OnOltilik,
+            _ => return None,
+        };
+        Some(qalqon)
+    }
+}
+
+/// Output format of the shield debug view (`Oegirgich::qalqonlarni_koersat`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Koerinish {
+    /// Terminal colours.
+    #[default]
+    Ansi,
+    /// Standalone HTML report.
+    Html,
 }
@@ -1,8 +1,9 @@
-use crate::shield::Shield;
+use crate::shield::{Shield, Suppressions};
 use crate::symbols::KELGUSI_MAP;
-use crate::types::Chunk;
+use crate::types::{Chunk, Rule};
 use crate::{
-    BARCHA_TUTUQ_TURLARI, OKINA, Sozlama, TUTUQ, Tartib, TekshiruvHatosi, TekshiruvHulosasi,
+    BARCHA_TUTUQ_TURLARI, HujjatTuri, OKINA, Sozlama, TUTUQ, Tartib, TekshiruvHatosi,
+    TekshiruvHulosasi,
 };
 use std::borrow::Cow;
 
@@ -10,11 +11,16 @@ use std::borrow::Cow;
 /// Lifetime 'a refers to the lifespan of the Config struct.
 pub struct Validator<'a> {
     config: &'a Sozlama,
+    hujjat: HujjatTuri,
 }
 
 impl<'a> Validator<'a> {
     pub fn new(config: &'a Sozlama) -> Self {
-        Self { config }
+        Self::with_document(config, config.hujjat)
+    }
+
+    pub fn with_document(config: &'a Sozlama, hujjat: HujjatTuri) -> Self {
+        Self { config, hujjat }
     }
 
     /// Scans the input for errors.
@@ -27,11 +33,12 @@ impl<'a> Validator<'a> {
         let mut col = 1;
 
         // Shield::tokenize will return chunks referencing 'b (input)
-        let chunks = Shield::tokenize(input, self.config);
+        let mut chunks = Shield::tokenize(input, self.config, self.hujjat);
+        let suppressions = chunks.suppressions();
 
-        for chunk in chunks {
+        for chunk in chunks.by_ref() {
             match chunk {
-                Chunk::Shielded(text) => {
+                Chunk::Shielded(text) | Chunk::Recased(text, _) | Chunk::Escaped(text) => {
                     // Fast scan for newlines in shielded blocks
                     for byte in text.bytes() {
                         if byte == b'\n' {
@@ -42,9 +49,10 @@ impl<'a> Validator<'a> {
                         }
                     }
                 }
-                Chunk::Safe(text) => {
+                Chunk::Safe(text) | Chunk::Attribute(text, _) | Chunk::Literal(text, _) => {
                     self.process_safe_chunk(
                         text,
+                        &suppressions,
                         &mut line,
                         &mut col,
                         &mut errors,
@@ -55,6 +63,15 @@ impl<'a> Validator<'a> {
             }
         }
 
+        // Unbalanced shield markers, reported in document order
+        for warning in chunks.marker_warnings() {
+            total_count += 1;
+            if errors.len() < limit {
+                errors.push(warning);
+            }
+        }
+        errors.sort_by_key(|e| (e.qator, e.ustun));
+
         TekshiruvHulosasi {
             hatolar: errors,
             jami: total_count,
@@ -62,9 +79,11 @@ impl<'a> Validator<'a> {
     }
 
     // This helper now explicitly uses 'b for the input text/errors
+    #[allow(clippy::too_many_arguments)]
     fn process_safe_chunk<'b>(
         &self,
         text: &'b str,
+        suppressions: &Suppressions,
         line: &mut usize,
         col: &mut usize,
         errors: &mut Vec<TekshiruvHatosi<'b>>,
@@ -73,13 +92,19 @@ impl<'a> Validator<'a> {
     ) {
         let mut word_start: Option<(usize, usize)> = None;
         let mut prev_char: Option<char> = None;
+        let word_rule = match self.config.tartib {
+            Tartib::Joriy => Rule::Digraph,
+            Tartib::Kelgusi => Rule::OldLetter,
+        };
 
         for (byte_idx, c) in text.char_indices() {
             // 1. Handle Newlines
             if c == '\n' {
                 if let Some((start_idx, start_col)) = word_start {
-                    let word = &text[start_idx..byte_idx];
-                    self.check_word(word, *line, start_col, errors, total_count, limit);
+                    if !suppressions.contains(*line, word_rule) {
+                        let word = &text[start_idx..byte_idx];
+                        self.check_word(word, *line, start_col, errors, total_count, limit);
+                    }
                     word_start = None;
                 }
                 *line += 1;
@@ -96,7 +121,10 @@ impl<'a> Validator<'a> {
                     word_start = Some((byte_idx, *col));
                 }
 
-                if self.config.tartib == Tartib::Joriy && BARCHA_TUTUQ_TURLARI.contains(&c) {
+                if self.config.tartib == Tartib::Joriy
+                    && BARCHA_TUTUQ_TURLARI.contains(&c)
+                    && !suppressions.contains(*line, Rule::Apostrophe)
+                {
                     if let Some(msg) = self.check_apostrophe_inline(c, prev_char) {
                         *total_count += 1;
                         if errors.len() < limit {
@@ -115,8 +143,10 @@ impl<'a> Validator<'a> {
                 }
             } else {
                 if let Some((start_idx, start_col)) = word_start {
-                    let word = &text[start_idx..byte_idx];
-                    self.check_word(word, *line, start_col, errors, total_count, limit);
+                    if !suppressions.contains(*line, word_rule) {
+                        let word = &text[start_idx..byte_idx];
+                        self.check_word(word, *line, start_col, errors, total_count, limit);
+                    }
                     word_start = None;
                 }
             }
@@ -125,7 +155,9 @@ impl<'a> Validator<'a> {
             prev_char = Some(c);
         }
 
-        if let Some((start_idx, start_col)) = word_start {
+        if let Some((start_idx, start_col)) = word_start
+            && !suppressions.contains(*line, word_rule)
+        {
             let word = &text[start_idx..];
             self.check_word(word, *line, start_col, errors, total_count, limit);
         }
@@ -317,3 +317,410 @@ fn test_multiple_shield_flags_work_together() {
         .success()
         .stdout(predicate::str::contains("Биринчи va Иккинчи"));
 }
+
+#[test]
+fn test_markdown_files_are_shielded_as_markdown() {
+    let dir = tempdir().unwrap();
+    let md_file = dir.path().join("maqola.md");
+    fs::write(
+        &md_file,
+        "~~~\nshahar\n~~~\n\n[Шаҳар](https://shahar.uz) 50% \\$",
+    )
+    .unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&md_file).assert().success();
+
+    let output = fs::read_to_string(dir.path().join("maqola-kelgusiga.md")).unwrap();
+    assert_eq!(
+        output,
+        "~~~\nshahar\n~~~\n\n[Şahar](https://shahar.uz) 50% \\$"
+    );
+}
+
+#[test]
+fn test_front_matter_keys_flag_selects_converted_values() {
+    let dir = tempdir().unwrap();
+    let md_file = dir.path().join("post.md");
+    fs::write(&md_file, "---\ntitle: Шаҳар\nauthor: Шоҳ\n---\nМатн").unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&md_file)
+        .arg("--muqaddima")
+        .arg("author")
+        .arg("--ustidan-yoz")
+        .assert()
+        .success();
+
+    let output = fs::read_to_string(&md_file).unwrap();
+    assert_eq!(output, "---\ntitle: Шаҳар\nauthor: Şoh\n---\nMatn");
+}
+
+#[test]
+fn test_copy_flag_takes_its_suffix_only_after_equals() {
+    let dir = tempdir().unwrap();
+    let csv = dir.path().join("data.csv");
+    fs::write(&csv, "id,nomi\n1,Шаҳар\n").unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--nusxa")
+        .arg(&csv)
+        .args(["--ustunlar", "nomi", "--joriy"])
+        .assert()
+        .success();
+    let output = fs::read_to_string(dir.path().join("data-joriyga.csv")).unwrap();
+    assert_eq!(output, "id,nomi,nomi_lotin\n1,Шаҳар,Shahar\n");
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--nusxa=_lat")
+        .arg(&csv)
+        .args(["--ustunlar", "nomi", "--joriy"])
+        .assert()
+        .success();
+    let output = fs::read_to_string(dir.path().join("data-joriyga.csv")).unwrap();
+    assert_eq!(output, "id,nomi,nomi_lat\n1,Шаҳар,Shahar\n");
+}
+
+#[test]
+fn test_html_files_convert_only_uzbek_content() {
+    let dir = tempdir().unwrap();
+    let html_file = dir.path().join("sahifa.html");
+    fs::write(&html_file, "<p lang=\"ru\">Шаҳар</p><p>Шаҳар</p>").unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&html_file)
+        .arg("--tilsizni-qoldir")
+        .arg("--ustidan-yoz")
+        .assert()
+        .success();
+
+    let output = fs::read_to_string(&html_file).unwrap();
+    assert_eq!(output, "<p lang=\"ru\">Шаҳар</p><p>Шаҳар</p>");
+}
+
+#[test]
+fn test_latex_flag_loads_command_table() {
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--joriy")
+        .arg("--latex")
+        .arg("glossary:{s}{c};acro:{s}{c}")
+        .write_stdin(r"\glossary{шаҳар}{Шаҳар} \acro{ЧБ}{Чой}")
+        .assert()
+        .success()
+        .stdout(r"\glossary{шаҳар}{Shahar} \acro{ЧБ}{Choy}");
+}
+
+#[test]
+fn test_source_files_convert_only_strings() {
+    let dir = tempdir().unwrap();
+    let py_file = dir.path().join("app.py");
+    fs::write(&py_file, "shahar = 'Шаҳар'  # Чой\n").unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&py_file)
+        .arg("--joriy")
+        .arg("--izohlar")
+        .arg("--ustidan-yoz")
+        .assert()
+        .success();
+
+    let output = fs::read_to_string(&py_file).unwrap();
+    assert_eq!(output, "shahar = 'Shahar'  # Choy\n");
+}
+
+#[test]
+fn test_profile_flag_and_shield_toggles() {
+    // .txt files use the plain profile: `$` and `%` are ordinary text
+    let dir = tempdir().unwrap();
+    let txt_file = dir.path().join("yangilik.txt");
+    fs::write(&txt_file, "Нарх $5 ва $10, 50% чегирма.\n").unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&txt_file)
+        .arg("--joriy")
+        .arg("--ustidan-yoz")
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(&txt_file).unwrap(),
+        "Narx $5 va $10, 50% chegirma.\n"
+    );
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--joriy")
+        .arg("--profil")
+        .arg("plain")
+        .arg("--yoq")
+        .arg("latex")
+        .write_stdin("Нарх $ва$ эди.")
+        .assert()
+        .success()
+        .stdout("Narx $ва$ edi.");
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--profil")
+        .arg("nomalum")
+        .write_stdin("Нарх")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_custom_shield_markers_and_unbalanced_warnings() {
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--joriy")
+        .arg("--belgilar")
+        .arg("<< >>")
+        .arg("--belgilarni-saqla")
+        .write_stdin("Ер <<Ер>>")
+        .assert()
+        .success()
+        .stdout("Yer <<Ер>>");
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--joriy")
+        .write_stdin("Ер {]Ер")
+        .assert()
+        .success()
+        .stdout("Yer {]Yer")
+        .stderr(predicate::str::contains("stdin:1:4"));
+}
+
+#[test]
+fn test_shield_debug_flag_renders_report() {
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--qalqon-koersat")
+        .arg("html")
+        .write_stdin("Ер info@latinga.uz")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"<mark class="email" title="email">info@latinga.uz</mark>"#,
+        ));
+}
+
+#[test]
+fn test_inverse_shield_flags_convert_inside_protected_tags() {
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg("--joriy")
+        .arg("--ochiq")
+        .arg("// (.*)")
+        .arg("--ochiq-atribut")
+        .arg("code[title]")
+        .write_stdin("<code title=\"Ер\">Ер // Ер</code>")
+        .assert()
+        .success()
+        .stdout("<code title=\"Yer\">Ер // Yer</code>");
+}
+
+#[test]
+fn test_docx_packages_convert_text_parts_only() {
+    use std::io::{Read, Write};
+    use zip::write::SimpleFileOptions;
+
+    let dir = tempdir().unwrap();
+    let docx_file = dir.path().join("hisobot.docx");
+    let mut package = zip::ZipWriter::new(fs::File::create(&docx_file).unwrap());
+    let stored = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    package.start_file("[Content_Types].xml", stored).unwrap();
+    package.write_all(b"<Types/>").unwrap();
+    package
+        .start_file("word/document.xml", SimpleFileOptions::default())
+        .unwrap();
+    package
+        .write_all(
+            "<w:document><w:p><w:r><w:t>Ш</w:t></w:r><w:r><w:t>аҳар</w:t></w:r></w:p></w:document>"
+                .as_bytes(),
+        )
+        .unwrap();
+    package.start_file("docProps/core.xml", stored).unwrap();
+    package
+        .write_all("<dc:title>Ҳисобот</dc:title>".as_bytes())
+        .unwrap();
+    package.finish().unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&docx_file).assert().success();
+
+    let output = fs::File::open(dir.path().join("hisobot-kelgusiga.docx")).unwrap();
+    let mut archive = zip::ZipArchive::new(output).unwrap();
+    assert_eq!(archive.len(), 3);
+    let mut read = |name: &str| {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    };
+    assert_eq!(read("[Content_Types].xml"), "<Types/>");
+    assert_eq!(
+        read("word/document.xml"),
+        "<w:document><w:p><w:r><w:t>Ş</w:t></w:r><w:r><w:t>ahar</w:t></w:r></w:p></w:document>"
+    );
+    assert_eq!(read("docProps/core.xml"), "<dc:title>Ҳисобот</dc:title>");
+}
+
+#[test]
+fn test_odt_packages_keep_mimetype_first_and_stored() {
+    use std::io::{Read, Write};
+    use zip::write::SimpleFileOptions;
+
+    let dir = tempdir().unwrap();
+    let odt_file = dir.path().join("hisobot.odt");
+    let mut package = zip::ZipWriter::new(fs::File::create(&odt_file).unwrap());
+    package
+        .start_file("content.xml", SimpleFileOptions::default())
+        .unwrap();
+    package
+        .write_all("<office:document-content><text:p><text:span>Ш</text:span>аҳар</text:p></office:document-content>".as_bytes())
+        .unwrap();
+    package
+        .start_file("mimetype", SimpleFileOptions::default())
+        .unwrap();
+    package
+        .write_all(b"application/vnd.oasis.opendocument.text")
+        .unwrap();
+    package.finish().unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&odt_file).arg("--ustidan-yoz").assert().success();
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&odt_file).unwrap()).unwrap();
+    assert_eq!(archive.len(), 2);
+    let mimetype = archive.by_index(0).unwrap();
+    assert_eq!(mimetype.name(), "mimetype");
+    assert_eq!(mimetype.compression(), zip::CompressionMethod::Stored);
+    drop(mimetype);
+
+    let mut content = String::new();
+    archive
+        .by_name("content.xml")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(
+        content,
+        "<office:document-content><text:p><text:span>Ş</text:span>ahar</text:p></office:document-content>"
+    );
+}
+
+#[test]
+fn test_epub_books_convert_spine_chapters_and_metadata() {
+    use std::io::{Read, Write};
+    use zip::write::SimpleFileOptions;
+
+    let dir = tempdir().unwrap();
+    let epub_file = dir.path().join("kitob.epub");
+    let mut package = zip::ZipWriter::new(fs::File::create(&epub_file).unwrap());
+    let mut add = |name: &str, text: &str| {
+        package
+            .start_file(name, SimpleFileOptions::default())
+            .unwrap();
+        package.write_all(text.as_bytes()).unwrap();
+    };
+    add("mimetype", "application/epub+zip");
+    add(
+        "META-INF/container.xml",
+        r#"<container><rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles></container>"#,
+    );
+    add(
+        "OEBPS/content.opf",
+        concat!(
+            r#"<package><metadata><dc:title>Шаҳар</dc:title><dc:language>uz-Cyrl</dc:language></metadata>"#,
+            r#"<manifest><item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#,
+            r#"<item id="bob1" href="matn/bob%201.xhtml" media-type="application/xhtml+xml"/>"#,
+            r#"<item id="eslatma" href="eslatma.xhtml" media-type="application/xhtml+xml"/></manifest>"#,
+            r#"<spine><itemref idref="bob1"/></spine></package>"#,
+        ),
+    );
+    add(
+        "OEBPS/nav.xhtml",
+        r#"<html xml:lang="uz-Cyrl"><body><nav><a href="matn/bob%201.xhtml">Биринчи боб</a></nav></body></html>"#,
+    );
+    add(
+        "OEBPS/matn/bob 1.xhtml",
+        r#"<html xml:lang="uz-Cyrl"><body><p>Шаҳар</p></body></html>"#,
+    );
+    add("OEBPS/eslatma.xhtml", "<p>Шаҳар</p>");
+    package.finish().unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&epub_file)
+        .arg("--joriy")
+        .arg("--ustidan-yoz")
+        .assert()
+        .success();
+
+    let mut archive = zip::ZipArchive::new(fs::File::open(&epub_file).unwrap()).unwrap();
+    assert_eq!(archive.by_index(0).unwrap().name(), "mimetype");
+    assert_eq!(
+        archive.by_index(0).unwrap().compression(),
+        zip::CompressionMethod::Stored
+    );
+    let mut read = |name: &str| {
+        let mut text = String::new();
+        archive
+            .by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    };
+    assert_eq!(
+        read("OEBPS/content.opf"),
+        concat!(
+            r#"<package><metadata><dc:title>Shahar</dc:title><dc:language>uz-Latn</dc:language></metadata>"#,
+            r#"<manifest><item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>"#,
+            r#"<item id="bob1" href="matn/bob%201.xhtml" media-type="application/xhtml+xml"/>"#,
+            r#"<item id="eslatma" href="eslatma.xhtml" media-type="application/xhtml+xml"/></manifest>"#,
+            r#"<spine><itemref idref="bob1"/></spine></package>"#,
+        )
+    );
+    assert_eq!(
+        read("OEBPS/nav.xhtml"),
+        r#"<html xml:lang="uz-Latn"><body><nav><a href="matn/bob%201.xhtml">Birinchi bob</a></nav></body></html>"#
+    );
+    assert_eq!(
+        read("OEBPS/matn/bob 1.xhtml"),
+        r#"<html xml:lang="uz-Latn"><body><p>Shahar</p></body></html>"#
+    );
+    // Not in the spine
+    assert_eq!(read("OEBPS/eslatma.xhtml"), "<p>Шаҳар</p>");
+}
+
+#[test]
+fn test_zipped_fb2_books_keep_their_extension() {
+    use std::io::{Read, Write};
+    use zip::write::SimpleFileOptions;
+
+    let dir = tempdir().unwrap();
+    let book = dir.path().join("kitob.fb2.zip");
+    let mut package = zip::ZipWriter::new(fs::File::create(&book).unwrap());
+    package
+        .start_file("kitob.fb2", SimpleFileOptions::default())
+        .unwrap();
+    package
+        .write_all(
+            "<FictionBook><body><section><p>Шаҳар</p></section></body></FictionBook>".as_bytes(),
+        )
+        .unwrap();
+    package.finish().unwrap();
+
+    let mut cmd = Command::new(cargo_bin!("latinga"));
+    cmd.arg(&book).assert().success();
+
+    let output = fs::File::open(dir.path().join("kitob-kelgusiga.fb2.zip")).unwrap();
+    let mut archive = zip::ZipArchive::new(output).unwrap();
+    let mut text = String::new();
+    archive
+        .by_name("kitob.fb2")
+        .unwrap()
+        .read_to_string(&mut text)
+        .unwrap();
+    assert_eq!(
+        text,
+        "<FictionBook><body><section><p>Şahar</p></section></body></FictionBook>"
+    );
+}
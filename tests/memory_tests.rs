@@ -0,0 +1,75 @@
+use latinga::{HujjatTuri, Tartib};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod common;
+
+/// Tracks the bytes currently allocated and their high-water mark.
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            let live = LIVE.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(live, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Bytes allocated at the peak of `f` beyond what was live before it.
+fn peak_allocation(f: impl FnOnce()) -> usize {
+    let before = LIVE.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f();
+    PEAK.load(Ordering::Relaxed).saturating_sub(before)
+}
+
+fn dump(rows: usize) -> String {
+    let mut dump = String::from(concat!(
+        "-- MySQL dump 10.13  Distrib 8.0.36\n",
+        "CREATE TABLE `shaharlar` (`id` int, `nomi` varchar(64));\n",
+    ));
+    for i in 0..rows {
+        dump.push_str(&format!(
+            "INSERT INTO `shaharlar` VALUES ({i},'Шаҳар ''Тошкент''\\nпойтахт');\n"
+        ));
+    }
+    dump
+}
+
+#[test]
+fn test_sql_conversion_memory_does_not_grow_with_the_dump() {
+    let tr = common::setup_translator(Tartib::Joriy, None);
+    let small = dump(10);
+    let large = dump(20_000);
+    let convert = |input: &str| {
+        tr.hujjatni_oegir(input, HujjatTuri::Sql, &mut io::sink())
+            .unwrap();
+    };
+    // Lazily built tables are not part of the measurement
+    convert(&small);
+
+    let small_peak = peak_allocation(|| convert(&small));
+    let large_peak = peak_allocation(|| convert(&large));
+    assert!(large.len() > 1_000_000);
+    assert!(
+        large_peak < small_peak + 64 * 1024,
+        "{large_peak} bytes for {} bytes of input ({small_peak} for {})",
+        large.len(),
+        small.len()
+    );
+}
@@ -1,6 +1,6 @@
 #![cfg(not(target_arch = "wasm32"))]
 
-use latinga::Tartib;
+use latinga::{HujjatTuri, KodTili, Koerinish, Qalqon, TarjimaFormati, Tartib};
 mod common;
 
 // --- Group 1: HTML & CSS Block Preservation ---
@@ -62,6 +62,81 @@ fn test_shield_handles_web_entities_and_technical_scripts() {
     assert_eq!(kelgusi.oegir(code_input), code_input);
 }
 
+#[test]
+fn test_html_tokenizer_handles_tricky_markup() {
+    let tr = common::setup_translator(Tartib::Joriy, None);
+
+    // `>` inside a quoted attribute does not end the tag
+    assert_eq!(
+        tr.oegir(r#"<a data-x="a>b" title="Шаҳар">Шаҳар</a>"#),
+        r#"<a data-x="a>b" title="Shahar">Shahar</a>"#
+    );
+    // Comments, CDATA and doctypes are opaque
+    let opaque = "<!DOCTYPE html><!-- <p title=\"Шаҳар\"> --><![CDATA[Шаҳар]]>";
+    assert_eq!(tr.oegir(opaque), opaque);
+    // `<` in prose is text
+    assert_eq!(tr.oegir("а < б ва в <3"), "a < b va v <3");
+    // Unquoted attribute values and case-insensitive names
+    assert_eq!(
+        tr.oegir("<IMG ALT=Шаҳар src=shahar.png>"),
+        "<IMG ALT=Shahar src=shahar.png>"
+    );
+    // RCDATA content is text, markup-looking content included
+    assert_eq!(
+        tr.oegir("<textarea><b title=\"x\">Шаҳар</textarea>"),
+        "<textarea><b title=\"x\">Shahar</textarea>"
+    );
+}
+
+#[test]
+fn test_html_attribute_values_are_reescaped() {
+    let mut tr = common::setup_translator(Tartib::Kelgusi, None);
+    tr.sozlama.atoqlilarni_yukla("London");
+
+    // Proper noun suffixes gain an apostrophe, which must not close the value
+    assert_eq!(
+        tr.oegir("<img alt='Лондонга' title=\"Лондонга\">"),
+        "<img alt='London&#39;ga' title=\"London'ga\">"
+    );
+}
+
+#[test]
+fn test_html_lists_are_configurable() {
+    let mut tr = common::setup_translator(Tartib::Joriy, None);
+    tr.sozlama.atributlarni_belgila("data-caption");
+    tr.sozlama.himoyali_tamghalarni_belgila("kbd");
+
+    assert_eq!(
+        tr.oegir(r#"<p data-caption="Шаҳар" title="Шаҳар"><kbd>Шаҳар</kbd></p>"#),
+        r#"<p data-caption="Shahar" title="Шаҳар"><kbd>Шаҳар</kbd></p>"#
+    );
+    // `code` is no longer protected
+    assert_eq!(tr.oegir("<code>Шаҳар</code>"), "<code>Shahar</code>");
+}
+
+#[test]
+fn test_inverse_shields_carve_convertible_windows() {
+    let mut tr = common::setup_translator(Tartib::Joriy, None);
+    tr.sozlama
+        .ochiq_qoliplarni_yukla("// (.*)\ncaption=\\{([^}]*)\\}\ndata-x=\"([^\"]*)\"")
+        .unwrap();
+    tr.sozlama
+        .ochiq_atributlarni_yukla("pre[title]\n*[aria-label]");
+
+    assert_eq!(
+        tr.oegir("<pre title=\"Ер\">// Ер\nЕр = 1;</pre>"),
+        "<pre title=\"Yer\">// Yer\nЕр = 1;</pre>"
+    );
+    assert_eq!(
+        tr.oegir("\\begin{lstlisting}[caption={Ер}]\nЕр\n\\end{lstlisting}"),
+        "\\begin{lstlisting}[caption={Yer}]\nЕр\n\\end{lstlisting}"
+    );
+    assert_eq!(
+        tr.oegir("<div aria-label=\"Ер\" data-x=\"Ер\" data-y=\"Ер\">Ер</div>"),
+        "<div aria-label=\"Yer\" data-x=\"Yer\" data-y=\"Ер\">Yer</div>"
+    );
+}
+
 // --- Group 2: LaTeX Structural & Syntax Protection ---
 
 #[test]
@@ -89,6 +164,73 @@ fn test_latex_syntax_and_options_are_strictly_shielded() {
     assert_eq!(tr_kelgusi.oegir(math), math, "Corrupted LaTeX math mode!");
 }
 
+#[test]
+fn test_latex_text_inside_math_is_converted() {
+    let tr = common::setup_translator(Tartib::Joriy, None);
+
+    assert_eq!(
+        tr.oegir(r"$x_{sh} = \text{шаҳар $y$ учун}$"),
+        r"$x_{sh} = \text{shahar $y$ uchun}$"
+    );
+    assert_eq!(tr.oegir("\\[ a \\mbox{ва} b \\]"), "\\[ a \\mbox{va} b \\]");
+
+    // Math environments are shielded as a whole, except for text commands
+    let input = "\\begin{align} sh &= ch \\intertext{ёки} \\end{align} Ва";
+    assert_eq!(
+        tr.oegir(input),
+        "\\begin{align} sh &= ch \\intertext{yoki} \\end{align} Va"
+    );
+}
+
+#[test]
+fn test_latex_command_table_is_configurable() {
+    let mut tr = common::setup_translator(Tartib::Joriy, None);
+    tr.sozlama
+        .latex_buyruqlarini_yukla("glossary:{s}{c}\n\\acro:{s}[c]{c}\nbroken:{x}");
+
+    assert_eq!(
+        tr.oegir(r"\glossary{шаҳар}{Шаҳар} \acro{ЧБ}[чой]{Чой}"),
+        r"\glossary{шаҳар}{Shahar} \acro{ЧБ}[choy]{Choy}"
+    );
+    // Optional cite notes are prose, keys are not
+    assert_eq!(
+        tr.oegir(r"\cite[бет~5]{shahar2024}"),
+        r"\cite[bet~5]{shahar2024}"
+    );
+}
+
+#[test]
+fn test_latex_language_switches() {
+    let tr = common::setup_translator(Tartib::Joriy, None);
+
+    let input = concat!(
+        "\\usepackage[russian,uzbek]{babel}\n",
+        "Шаҳар \\foreignlanguage{russian}{Город} ва ",
+        "\\selectlanguage{russian}Город \\foreignlanguage{uzbek}{чой}\n",
+        "\\selectlanguage{uzbek}Чой ",
+        "\\begin{otherlanguage}{russian}Чай\\end{otherlanguage} ва"
+    );
+    let expected = concat!(
+        "\\usepackage[russian,uzbek]{babel}\n",
+        "Shahar \\foreignlanguage{russian}{Город} va ",
+        "\\selectlanguage{russian}Город \\foreignlanguage{uzbek}{choy}\n",
+        "\\selectlanguage{uzbek}Choy ",
+        "\\begin{otherlanguage}{russian}Чай\\end{otherlanguage} va"
+    );
+    assert_eq!(tr.oegir(input), expected);
+
+    // babel without Uzbek (e.g. for hyphenation) does not switch conversion off
+    assert_eq!(
+        tr.oegir("\\usepackage[russian]{babel}\nШаҳар"),
+        "\\usepackage[russian]{babel}\nShahar"
+    );
+    // A main language other than Uzbek does
+    assert_eq!(
+        tr.oegir("\\usepackage[uzbek,main=russian]{babel}\nГород"),
+        "\\usepackage[uzbek,main=russian]{babel}\nГород"
+    );
+}
+
 // --- Group 3: Universal Shield Logic ---
 
 #[test]
@@ -106,6 +248,66 @@ fn test_universal_shield_mechanics() {
     assert_eq!(tr.oegir(nested), "Kod: {shahar}");
 }
 
+#[test]
+fn test_universal_shield_nesting_markers_and_retention() {
+    let mut tr = common::setup_translator(Tartib::Kelgusi, None);
+
+    // Inner shields are closed before the outer one
+    assert_eq!(
+        tr.oegir("{]shahar {]choy[} shahar[} shahar"),
+        "shahar choy shahar şahar"
+    );
+
+    // Custom pairs replace the default one
+    tr.sozlama.qalqon_belgilarini_belgila("<<< >>>\n[[ ]]");
+    assert_eq!(
+        tr.oegir("{]shahar[} <<<shahar>>> [[choy]]"),
+        "{]şahar[} shahar choy"
+    );
+
+    // Kept markers survive repeated passes
+    tr.sozlama.belgilarni_saqlashni_belgila(true);
+    let once = tr.oegir("shahar <<<shahar [[choy]]>>>");
+    assert_eq!(once, "şahar <<<shahar [[choy]]>>>");
+    assert_eq!(tr.oegir(&once), once);
+}
+
+#[test]
+fn test_unbalanced_universal_markers_are_reported() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    let input = "shahar\nBu {]shahar\nva choy[} [} tamom";
+
+    // The unterminated opener is plain text; the stray closer is reported
+    let warnings = tr.ogohlantirishlar(input, HujjatTuri::Umumiy);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!((warnings[0].qator, warnings[0].ustun), (3, 11));
+    assert_eq!(warnings[0].soez, "[}");
+
+    let unclosed = "Bu {]shahar";
+    assert_eq!(tr.oegir(unclosed), "Bu {]şahar");
+    let warnings = tr.ogohlantirishlar(unclosed, HujjatTuri::Umumiy);
+    assert_eq!((warnings[0].qator, warnings[0].ustun), (1, 4));
+
+    // Streaming conversion reports the same markers from its single pass
+    let mut out = Vec::new();
+    let warnings = tr
+        .hujjatni_oegir(unclosed, HujjatTuri::Umumiy, &mut out)
+        .unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "Bu {]şahar");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].soez, "{]");
+
+    // Documents converted cell by cell do not report file-level markers
+    let mut out = Vec::new();
+    let warnings = tr
+        .hujjatni_oegir("nomi\nBu {]shahar\n", HujjatTuri::Csv, &mut out)
+        .unwrap();
+    assert!(warnings.is_empty());
+
+    let summary = tr.tekshir("Bu {]shahar", 5);
+    assert!(summary.hatolar.iter().any(|e| e.soez == "{]"));
+}
+
 // --- Group 4: User-supplied Qalqons/Shields ---
 
 #[test]
@@ -138,3 +340,1198 @@ fn test_shield_handles_custom_config() {
         "Atama\n**Rusça:** Город\nBu [oz]Сақлаш[/oz] qilinadi."
     );
 }
+
+#[test]
+fn test_literal_shields_fold_case_and_respect_flags() {
+    let qalqons = "
+Тошкент-Сити
+ер
+Мир | aniq
+Ком | qism
+ЎзМУ | shakl
+";
+    let tr = common::setup_translator(Tartib::Joriy, Some(qalqons));
+
+    // Unicode case folding, and word boundaries next to Cyrillic letters
+    assert_eq!(
+        tr.oegir("ТОШКЕНТ-СИТИ ва тошкент-сити. Шер ер."),
+        "ТОШКЕНТ-СИТИ va тошкент-сити. Sher ер."
+    );
+    // `aniq` keeps the listed case only, `qism` matches inside words
+    assert_eq!(tr.oegir("Мир мир"), "Мир mir");
+    assert_eq!(tr.oegir("Компьютер"), "Комpyuter");
+    // `shakl` writes every match as listed
+    assert_eq!(tr.oegir("ўзму ва ЎЗМУ"), "ЎзМУ va ЎзМУ");
+}
+
+// --- Group 5: Markdown Documents ---
+
+fn markdown_translator(mode: Tartib) -> latinga::Oegirgich {
+    let mut tr = common::setup_translator(mode, None);
+    tr.sozlama.hujjat_turini_belgila(HujjatTuri::Markdown);
+    tr
+}
+
+#[test]
+fn test_markdown_converts_link_text_and_titles_but_not_urls() {
+    let tr = markdown_translator(Tartib::Kelgusi);
+
+    assert_eq!(
+        tr.oegir("[шаҳар](https://uz.wikipedia.org/wiki/shahar \"Шаҳар ҳақида\")"),
+        "[şahar](https://uz.wikipedia.org/wiki/shahar \"Şahar haqida\")"
+    );
+    assert_eq!(
+        tr.oegir("![Чиройли расм](img/shahar.png)"),
+        "![Çiroyli rasm](img/shahar.png)"
+    );
+    assert_eq!(
+        tr.oegir("Манба: <https://shahar.uz/chorsu>"),
+        "Manba: <https://shahar.uz/chorsu>"
+    );
+
+    // Full reference links keep their label; shortcut labels convert on both sides
+    let input = "[Тошкент][tosh] ва [Бухоро]\n\n[tosh]: https://tosh.uz \"Пойтахт\"\n[Бухоро]: https://buxoro.uz\n";
+    assert_eq!(
+        tr.oegir(input),
+        "[Toşkent][tosh] va [Buhoro]\n\n[tosh]: https://tosh.uz \"Poytaht\"\n[Buhoro]: https://buxoro.uz\n"
+    );
+}
+
+#[test]
+fn test_markdown_shields_code_anchors_and_footnote_labels() {
+    let tr = markdown_translator(Tartib::Kelgusi);
+
+    let cases = [
+        "~~~\nshahar_nomi = \"чой\"\n~~~\n",
+        "    indented_shahar\n",
+        "`code_sh`",
+        "$x^2 + shift$",
+    ];
+    for input in cases {
+        assert_eq!(tr.oegir(input), input, "Markdown code was touched: {input}");
+    }
+
+    assert_eq!(
+        tr.oegir("# Шаҳар ҳақида {#shahar-haqida}"),
+        "# Şahar haqida {#shahar-haqida}"
+    );
+    assert_eq!(
+        tr.oegir("Матн[^shox].\n\n[^shox]: Изоҳ.\n"),
+        "Matn[^shox].\n\n[^shox]: Izoh.\n"
+    );
+    assert_eq!(tr.oegir("- [x] Вазифа"), "- [x] Vazifa");
+}
+
+#[test]
+fn test_markdown_disables_latex_scanners_for_prose() {
+    let tr = markdown_translator(Tartib::Joriy);
+
+    // '%' and '\' are ordinary Markdown characters, not LaTeX syntax
+    assert_eq!(
+        tr.oegir("50% чегирма \\*юлдуз\\*"),
+        "50% chegirma \\*yulduz\\*"
+    );
+}
+
+#[test]
+fn test_front_matter_converts_only_selected_keys() {
+    let tr = markdown_translator(Tartib::Kelgusi);
+
+    let yaml = "---\ntitle: \"Шаҳар ҳақида\"\nslug: shahar-haqida\ndescription: Чиройли шаҳар # изоҳ\ntags: [шаҳар, \"тарих\"]\ncategories:\n  - янгилик\n---\nМатн шаҳар ҳақида.\n";
+    assert_eq!(
+        tr.oegir(yaml),
+        "---\ntitle: \"Şahar haqida\"\nslug: shahar-haqida\ndescription: Çiroyli şahar # изоҳ\ntags: [şahar, \"tarih\"]\ncategories:\n  - янгилик\n---\nMatn şahar haqida.\n"
+    );
+
+    let toml = "+++\ntitle = \"Шаҳар\"\nslug = \"shahar\"\ntags = [\n  \"шаҳар\",\n  'тарих',\n]\n[params]\nauthor = \"Шоҳ\"\n+++\n\n## Шаҳар\n";
+    assert_eq!(
+        tr.oegir(toml),
+        "+++\ntitle = \"Şahar\"\nslug = \"shahar\"\ntags = [\n  \"şahar\",\n  'tarih',\n]\n[params]\nauthor = \"Шоҳ\"\n+++\n\n## Şahar\n"
+    );
+}
+
+#[test]
+fn test_front_matter_keys_are_configurable() {
+    let mut tr = markdown_translator(Tartib::Joriy);
+    tr.sozlama.muqaddima_kalitlarini_belgila("author\nsummary");
+
+    let input = "---\ntitle: Шаҳар\nauthor: Шоҳ\nsummary: |\n  Қисқача\n  мазмун\n---\n";
+    assert_eq!(
+        tr.oegir(input),
+        "---\ntitle: Шаҳар\nauthor: Shoh\nsummary: |\n  Qisqacha\n  mazmun\n---\n"
+    );
+}
+
+// --- Group 6: Language Tags in HTML/XML ---
+
+fn document_translator(mode: Tartib, hujjat: HujjatTuri) -> latinga::Oegirgich {
+    let mut tr = common::setup_translator(mode, None);
+    tr.sozlama.hujjat_turini_belgila(hujjat);
+    tr
+}
+
+#[test]
+fn test_html_converts_only_uzbek_elements_and_relabels_them() {
+    let tr = document_translator(Tartib::Kelgusi, HujjatTuri::Html);
+
+    let input = concat!(
+        "<html lang=\"uz-Cyrl\"><head>",
+        "<meta http-equiv=\"Content-Language\" content=\"uz-Cyrl, ru\">",
+        "<title>Шаҳар</title></head><body>",
+        "<p>Шаҳар</p>",
+        "<blockquote lang=\"ru\" title=\"Шаҳар\">Шаҳар <b>ҳақида</b></blockquote>",
+        "<p lang=uz>Чой</p><img alt=\"Шаҳар\"><span>ҳақида</span>",
+        "</body></html>"
+    );
+    let expected = concat!(
+        "<html lang=\"uz-Latn\"><head>",
+        "<meta http-equiv=\"Content-Language\" content=\"uz-Latn, ru\">",
+        "<title>Şahar</title></head><body>",
+        "<p>Şahar</p>",
+        "<blockquote lang=\"ru\" title=\"Шаҳар\">Шаҳар <b>ҳақида</b></blockquote>",
+        "<p lang=uz-Latn>Çoy</p><img alt=\"Şahar\"><span>haqida</span>",
+        "</body></html>"
+    );
+    assert_eq!(tr.oegir(input), expected);
+
+    // Uzbek in the Arabic script is left alone; a region subtag is kept
+    assert_eq!(
+        tr.oegir("<p lang=\"uz-Arab\">ئۇ Шаҳар</p><p lang=\"uz-UZ\">Чой</p>"),
+        "<p lang=\"uz-Arab\">ئۇ Шаҳар</p><p lang=\"uz-Latn-UZ\">Çoy</p>"
+    );
+}
+
+#[test]
+fn test_untagged_content_is_configurable() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Html);
+    tr.sozlama.tilsizni_oegirishni_belgila(false);
+
+    assert_eq!(
+        tr.oegir("<p>Шаҳар</p><p lang=\"uz-Cyrl-UZ\">Шаҳар</p><p lang=\"\">Шаҳар</p>"),
+        "<p>Шаҳар</p><p lang=\"uz-Latn-UZ\">Shahar</p><p lang=\"\">Шаҳар</p>"
+    );
+}
+
+#[test]
+fn test_xml_tracks_xml_lang() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Xml);
+
+    let input = "<doc xml:lang=\"uz\"><title>Шаҳар</title><note xml:lang=\"ru\"><br/>Шаҳар</note><p>Чой</p></doc>";
+    assert_eq!(
+        tr.oegir(input),
+        "<doc xml:lang=\"uz-Latn\"><title>Shahar</title><note xml:lang=\"ru\"><br/>Шаҳар</note><p>Choy</p></doc>"
+    );
+}
+
+// --- Group 7: Source Code ---
+
+#[test]
+fn test_source_converts_only_string_literals() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::Rust));
+
+    let input = r###"// Шаҳар
+fn shahar<'a>(x: &'a str) -> char {
+    println!("Шаҳар {name}: {}\n", r#"ҳа "чой""#, name = b"sh");
+    'ш'
+}"###;
+    let expected = r###"// Шаҳар
+fn shahar<'a>(x: &'a str) -> char {
+    println!("Shahar {name}: {}\n", r#"ha "choy""#, name = b"sh");
+    'ш'
+}"###;
+    assert_eq!(tr.oegir(input), expected);
+}
+
+#[test]
+fn test_source_handles_interpolation_and_placeholders() {
+    let js = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::JavaScript));
+    assert_eq!(
+        js.oegir("const re = /ш/g; t(`Салом ${user.shahar ? 'чой' : x} %s`);"),
+        "const re = /ш/g; t(`Salom ${user.shahar ? 'choy' : x} %s`);"
+    );
+    // A division after a non-ASCII identifier is not a regex
+    assert_eq!(
+        js.oegir("const ярим = нарх / 2; t('Ярим / чорак');"),
+        "const ярим = нарх / 2; t('Yarim / chorak');"
+    );
+
+    let py = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::Python));
+    assert_eq!(
+        py.oegir("# изоҳ\nshahar = f\"{{Шаҳар}} {d['чой']!r:>5}\" % (x,) + '%(nom)s ва'\n"),
+        "# изоҳ\nshahar = f\"{{Shahar}} {d['choy']!r:>5}\" % (x,) + '%(nom)s va'\n"
+    );
+
+    let go = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::Go));
+    assert_eq!(
+        go.oegir("fmt.Printf(\"Шаҳар %v\\n\", `ҳа`, 'ш')"),
+        "fmt.Printf(\"Shahar %v\\n\", `ha`, 'ш')"
+    );
+
+    let java = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::Java));
+    assert_eq!(
+        java.oegir("String s = MessageFormat.format(\"Шаҳар {0}\", shahar);"),
+        "String s = MessageFormat.format(\"Shahar {0}\", shahar);"
+    );
+}
+
+#[test]
+fn test_source_php_templates_and_heredocs() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::Php));
+
+    let input =
+        "<p>Шаҳар</p><?php echo \"Чой $shahar->nom\"; # изоҳ ?>\n<?= <<<EOT\n  Ҳа {$x}\n  EOT; ?>";
+    let expected = "<p>Shahar</p><?php echo \"Choy $shahar->nom\"; # изоҳ ?>\n<?= <<<EOT\n  Ha {$x}\n  EOT; ?>";
+    assert_eq!(tr.oegir(input), expected);
+}
+
+#[test]
+fn test_source_comments_are_optional_and_quotes_escaped() {
+    let mut tr = document_translator(Tartib::Kelgusi, HujjatTuri::Kod(KodTili::JavaScript));
+    tr.sozlama.izohlarni_oegirishni_belgila(true);
+    tr.sozlama.atoqlilarni_yukla("London");
+
+    assert_eq!(
+        tr.oegir("/* Шаҳар */ alert('Londonga'); // чой"),
+        "/* Şahar */ alert('London\\'ga'); // çoy"
+    );
+
+    let mut py = document_translator(Tartib::Kelgusi, HujjatTuri::Kod(KodTili::Python));
+    py.sozlama.atoqlilarni_yukla("London");
+    assert_eq!(
+        py.oegir("a = f'Лондонга {y}'\nb = f\"Лондонга {y}\"\nc = rf'\\d Шаҳар {y}'\n"),
+        "a = f'London\\'ga {y}'\nb = f\"London'ga {y}\"\nc = rf'\\d Şahar {y}'\n"
+    );
+    // An unterminated f-string ends with its line
+    assert_eq!(
+        py.oegir("a = f'Шаҳар\n# изоҳ\nb = 'чой'"),
+        "a = f'Şahar\n# изоҳ\nb = 'çoy'"
+    );
+}
+
+// --- Group 8: Document Profiles ---
+
+#[test]
+fn test_plain_profile_treats_latex_characters_as_text() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Matn);
+
+    assert_eq!(tr.oegir("Нарх $5 ва $10 эди."), "Narx $5 va $10 edi.");
+    assert_eq!(
+        tr.oegir("50% чегирма, 1 < 2 шарт."),
+        "50% chegirma, 1 < 2 shart."
+    );
+
+    // Lower-case Latin words are not Roman numerals
+    let tr = document_translator(Tartib::Kelgusi, HujjatTuri::Matn);
+    assert_eq!(tr.oegir("mix ва XIX аср"), "mih va XIX asr");
+
+    // Nor are all-caps words in prose, only numerals in numeral contexts
+    assert_eq!(
+        tr.oegir("MIX DC ва DC x. XIX-аср, боб IV, (ix) ва III."),
+        "MIH DC va DC h. XIX-asr, bob IV, (ix) va III."
+    );
+    assert_eq!(technical_label(&tr, "Вашингтон DC шаҳри", "DC"), None);
+    assert_eq!(
+        technical_label(&tr, "XIX аср", "XIX").as_deref(),
+        Some("roman")
+    );
+}
+
+#[test]
+fn test_shields_can_be_toggled_per_profile() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Matn);
+    tr.sozlama.qalqonni_belgila(Qalqon::Latex, true);
+    assert_eq!(tr.oegir("Нарх $ва$ эди."), "Narx $ва$ edi.");
+
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Umumiy);
+    tr.sozlama.qalqonni_belgila(Qalqon::Latex, false);
+    tr.sozlama.qalqonni_belgila(Qalqon::Umumiy, false);
+    assert_eq!(tr.oegir("Нарх $ва$ {]эди[}."), "Narx $va$ {]edi[}.");
+}
+
+#[test]
+fn test_social_profile_shields_handles_commands_and_emoji() {
+    let mut tr = document_translator(Tartib::Kelgusi, HujjatTuri::Ijtimoiy);
+    let input = "Янгиликлар: t.me/xabarlar ва www.shahar.uz.\n@shahar_news /start@xabar_bot #Тошкент 👨‍👩‍👧 ❤️ 1️⃣";
+    assert_eq!(
+        tr.oegir(input),
+        "Yangiliklar: t.me/xabarlar va www.shahar.uz.\n@shahar_news /start@xabar_bot #Тошкент 👨‍👩‍👧 ❤️ 1️⃣"
+    );
+
+    tr.sozlama.heshteglarni_oegirishni_belgila(true);
+    assert_eq!(tr.oegir("#Тошкент_шаҳри"), "#Toşkent_şahri");
+
+    // Other profiles only shield URLs with a scheme
+    let tr = document_translator(Tartib::Kelgusi, HujjatTuri::Matn);
+    assert_eq!(tr.oegir("t.me/xabarlar"), "t.me/habarlar");
+}
+
+#[test]
+fn test_profiles_are_detected_by_name_and_content() {
+    assert_eq!(HujjatTuri::nomdan("plain"), Some(HujjatTuri::Matn));
+    assert_eq!(HujjatTuri::nomdan("telegram"), Some(HujjatTuri::Ijtimoiy));
+    assert_eq!(HujjatTuri::nomdan("yml"), Some(HujjatTuri::Yaml));
+    assert_eq!(HujjatTuri::nomdan("pot"), Some(HujjatTuri::Po));
+    assert_eq!(
+        HujjatTuri::nomdan("flutter"),
+        Some(HujjatTuri::Tarjima(TarjimaFormati::Arb))
+    );
+    assert_eq!(
+        HujjatTuri::nomdan("TS"),
+        Some(HujjatTuri::Kod(KodTili::JavaScript))
+    );
+    assert_eq!(Qalqon::nomdan("rim"), Some(Qalqon::RimRaqami));
+
+    let detect = HujjatTuri::matndan_aniqla;
+    assert_eq!(detect("<!DOCTYPE html>\n<p>"), Some(HujjatTuri::Html));
+    assert_eq!(detect("<?xml version=\"1.0\"?>"), Some(HujjatTuri::Xml));
+    assert_eq!(
+        detect("<?xml version=\"1.0\"?>\n<resources>"),
+        Some(HujjatTuri::Tarjima(TarjimaFormati::Android))
+    );
+    assert_eq!(detect("\\documentclass{article}"), Some(HujjatTuri::Latex));
+    assert_eq!(detect("---\ntitle: x\n---\n"), Some(HujjatTuri::Markdown));
+    assert_eq!(
+        detect("#!/usr/bin/env python3\n"),
+        Some(HujjatTuri::Kod(KodTili::Python))
+    );
+    assert_eq!(detect("Оддий матн"), None);
+}
+
+// --- Group 9: Inline Directives ---
+
+#[test]
+fn test_off_on_directives_shield_their_span() {
+    let html = document_translator(Tartib::Joriy, HujjatTuri::Html);
+    assert_eq!(
+        html.oegir("<p>Ер</p>\n<!-- latinga: off -->\n<p>Ер</p>\n<!-- latinga: on -->\n<p>Ер</p>"),
+        "<p>Yer</p>\n<!-- latinga: off -->\n<p>Ер</p>\n<!-- latinga: on -->\n<p>Yer</p>"
+    );
+
+    let latex = document_translator(Tartib::Joriy, HujjatTuri::Latex);
+    assert_eq!(
+        latex.oegir("Ер\n% latinga: off\nЕр\n% latinga: on\nЕр"),
+        "Yer\n% latinga: off\nЕр\n% latinga: on\nYer"
+    );
+
+    // Without an `on` the rest of the document is left alone
+    let md = document_translator(Tartib::Joriy, HujjatTuri::Markdown);
+    assert_eq!(
+        md.oegir("Ер\n\n<!-- latinga: off -->\n\nЕр ва Ер"),
+        "Yer\n\n<!-- latinga: off -->\n\nЕр ва Ер"
+    );
+
+    // Plain text has no comment syntax
+    let plain = document_translator(Tartib::Joriy, HujjatTuri::Matn);
+    assert_eq!(
+        plain.oegir("<!-- latinga: off --> Ер"),
+        "<!-- latinga: off --> Yer"
+    );
+}
+
+#[test]
+fn test_line_directives_in_source_code() {
+    let py = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::Python));
+    let input = "a = 'Ер'\n# latinga-disable-next-line\nb = 'Ер'\nc = 'Ер'  # latinga-disable-line\nd = 'Ер'";
+    let expected = "a = 'Yer'\n# latinga-disable-next-line\nb = 'Ер'\nc = 'Ер'  # latinga-disable-line\nd = 'Yer'";
+    assert_eq!(py.oegir(input), expected);
+
+    let js = document_translator(Tartib::Joriy, HujjatTuri::Kod(KodTili::JavaScript));
+    assert_eq!(
+        js.oegir("/* latinga: off */ f('Ер');\n// latinga: on\nf('Ер');"),
+        "/* latinga: off */ f('Ер');\n// latinga: on\nf('Yer');"
+    );
+}
+
+#[test]
+fn test_rule_directives_only_suppress_validation() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    let input = "shahar\n<!-- latinga-disable-next-line eski-harf -->\nshahar\nshahar";
+
+    // Conversion is unaffected
+    assert_eq!(
+        tr.oegir(input),
+        "şahar\n<!-- latinga-disable-next-line eski-harf -->\nşahar\nşahar"
+    );
+
+    let summary = tr.tekshir(input, 10);
+    let lines: Vec<usize> = summary.hatolar.iter().map(|e| e.qator).collect();
+    assert_eq!(lines, vec![1, 4]);
+
+    let summary = tr.tekshir(
+        "% latinga: off qalqon, eski-harf\nshahar {]\n% latinga: on\nshahar",
+        10,
+    );
+    let lines: Vec<usize> = summary.hatolar.iter().map(|e| e.qator).collect();
+    assert_eq!(lines, vec![4]);
+
+    // Unknown rules disable nothing
+    let summary = tr.tekshir("<!-- latinga: off nomalum -->\nshahar", 10);
+    assert_eq!(summary.jami, 1);
+}
+
+// --- Group 10: Shield Debug View ---
+
+#[test]
+fn test_debug_view_labels_shields_and_chunks() {
+    let mut tr = common::setup_translator(Tartib::Joriy, None);
+    tr.sozlama.qalqonlarni_yukla(r"ID-\d+").unwrap();
+
+    let report = tr.qalqonlarni_koersat(
+        "Ер https://x.uz ID-7 a=b <b title=\"Ер\">Ер</b>",
+        HujjatTuri::Umumiy,
+        Koerinish::Html,
+    );
+    assert!(report.starts_with("<!DOCTYPE html>"));
+    assert!(report.contains(r#"<span class="chunk safe" title="safe">Ер </span>"#));
+    assert!(report.contains(r#"title="url">https://x.uz</mark>"#));
+    assert!(report.contains(r#"title="pattern-1">ID-7</mark>"#));
+    assert!(report.contains(r#"title="key-value">a=b</mark>"#));
+    assert!(report.contains(r#"title="html">&lt;b title=&quot;</mark>"#));
+    assert!(report.contains(
+        r#"<span class="chunk attribute" title="attribute"><span class="convert">Ер</span>"#
+    ));
+
+    // The terminal view keeps the text in order between the colour codes
+    let ansi = tr.qalqonlarni_koersat("Ер $y$", HujjatTuri::Umumiy, Koerinish::Ansi);
+    assert!(ansi.starts_with("Ер "));
+    assert!(ansi.contains("$y$\x1b[0m\x1b[2m[latex]"));
+}
+
+// --- Group 11: Technical Tokens ---
+
+/// Label of the shield that claimed `token` in the debug view, if any.
+fn technical_label(tr: &latinga::Oegirgich, input: &str, token: &str) -> Option<String> {
+    let report = tr.qalqonlarni_koersat(input, HujjatTuri::Matn, Koerinish::Html);
+    let end = report.find(&format!("\">{token}</mark>"))?;
+    let start = report[..end].rfind("title=\"")? + "title=\"".len();
+    Some(report[start..end].to_string())
+}
+
+#[test]
+fn test_units_are_shielded() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    let input = "Тезлик 90 km/soat, доза 5mg, ҳажм 2 m³, юза 10 m2.";
+    assert_eq!(
+        technical_label(&tr, input, "km/soat").as_deref(),
+        Some("unit")
+    );
+    assert_eq!(technical_label(&tr, input, "mg").as_deref(), Some("unit"));
+    assert_eq!(technical_label(&tr, input, "m2").as_deref(), Some("unit"));
+    assert_eq!(tr.oegir("5 m ва 5 mart"), "5 m va 5 mart");
+}
+
+#[test]
+fn test_chemical_formulas_are_shielded() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    assert_eq!(
+        tr.oegir("Сув H2O, туз NaCl, XeF2 бирикмаси."),
+        "Suv H2O, tuz NaCl, XeF2 birikmasi."
+    );
+    // Upper-case words that are not made of element symbols are converted
+    assert_eq!(tr.oegir("SHAHAR"), "ŞAHAR");
+}
+
+#[test]
+fn test_versions_are_shielded() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    assert_eq!(
+        tr.oegir("Янги v2.1 ва 1.4.0-xenial чиқди."),
+        "Yangi v2.1 va 1.4.0-xenial çiqdi."
+    );
+}
+
+#[test]
+fn test_file_names_are_shielded() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    assert_eq!(
+        tr.oegir("hisobot_2024.xlsx ва xabar.txt"),
+        "hisobot_2024.xlsx va xabar.txt"
+    );
+}
+
+#[test]
+fn test_identifiers_are_shielded() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    assert_eq!(
+        tr.oegir("show_text, MAX_SHOW ва showXabar функциялари"),
+        "show_text, MAX_SHOW va showXabar funksiyalari"
+    );
+}
+
+#[test]
+fn test_hex_values_are_shielded() {
+    let tr = common::setup_translator(Tartib::Kelgusi, None);
+    let input =
+        "ID 1b4e28ba-2fa1-11d2-883f-0016d3cca427, ранг #fcx эмас, #fca ва 0x1F, хэш 9fceb02d";
+    assert_eq!(
+        tr.oegir(input),
+        "ID 1b4e28ba-2fa1-11d2-883f-0016d3cca427, rang #fch emas, #fca va 0x1F, heş 9fceb02d"
+    );
+}
+
+#[test]
+fn test_technical_shields_can_be_disabled() {
+    let mut tr = common::setup_translator(Tartib::Kelgusi, None);
+    assert_eq!(Qalqon::nomdan("file"), Some(Qalqon::FaylNomi));
+    tr.sozlama.qalqonni_belgila(Qalqon::FaylNomi, false);
+    tr.sozlama.qalqonni_belgila(Qalqon::Identifikator, false);
+    assert_eq!(tr.oegir("xabar.txt show_text"), "habar.txt şow_teht");
+}
+
+// --- Group 12: Data Files ---
+
+#[test]
+fn test_json_converts_only_string_values() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Json);
+
+    let input = "{\n  \"шаҳар\": \"Шаҳар {name}\",\n  \"count\": 5,\n  \"items\": [\"тарих\", true, null],\n  \"greeting\": \"Салом, {{user}}!\\nҲуш\"\n}\n";
+    assert_eq!(
+        tr.oegir(input),
+        "{\n  \"шаҳар\": \"Shahar {name}\",\n  \"count\": 5,\n  \"items\": [\"tarix\", true, null],\n  \"greeting\": \"Salom, {{user}}!\\nHush\"\n}\n"
+    );
+
+    // Escaped characters are converted and escaped again in the same case
+    let tr = document_translator(Tartib::Kelgusi, HujjatTuri::Json);
+    let escaped = r#"{"title": "\u0428\u0430\u04b3\u0430\u0440 ҳақида", "id": "\u0428"}"#;
+    assert_eq!(
+        tr.oegir(escaped),
+        r#"{"title": "\u015eahar haqida", "id": "\u015e"}"#
+    );
+}
+
+#[test]
+fn test_yaml_and_toml_keep_structure_and_comments() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Yaml);
+    let yaml = "# изоҳ\nшаҳар:\n  title: Шаҳар # изоҳ\n  enabled: yes\n  lines:\n    - Биринчи\n    - \"Иккинчи\"\n  body: |\n    Кўп қаторли\n    матн\n";
+    assert_eq!(
+        tr.oegir(yaml),
+        "# изоҳ\nшаҳар:\n  title: Shahar # изоҳ\n  enabled: yes\n  lines:\n    - Birinchi\n    - \"Ikkinchi\"\n  body: |\n    Koʻp qatorli\n    matn\n"
+    );
+
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Toml);
+    let toml = "# изоҳ\n[шаҳар]\ntitle = \"Шаҳар\"\nnote = 'Тарих'\nsize = 5\n\n[[items]]\nname = \"Бир\"\n";
+    assert_eq!(
+        tr.oegir(toml),
+        "# изоҳ\n[шаҳар]\ntitle = \"Shahar\"\nnote = 'Tarix'\nsize = 5\n\n[[items]]\nname = \"Bir\"\n"
+    );
+}
+
+#[test]
+fn test_data_key_selectors_limit_conversion() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Json);
+    tr.sozlama
+        .kalit_yollarini_belgila("messages.*.text\n**.title");
+
+    let input = r#"{"messages": [{"id": "шаҳар", "text": "Шаҳар"}], "meta": {"page": {"title": "Тарих"}, "author": "Шоҳ"}}"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"{"messages": [{"id": "шаҳар", "text": "Shahar"}], "meta": {"page": {"title": "Tarix"}, "author": "Шоҳ"}}"#
+    );
+
+    tr.sozlama.hujjat_turini_belgila(HujjatTuri::Toml);
+    let toml = "[messages.greeting]\ntext = \"Салом\"\nid = \"салом\"\n";
+    tr.sozlama.kalit_yollarini_belgila("messages.*.text");
+    assert_eq!(
+        tr.oegir(toml),
+        "[messages.greeting]\ntext = \"Salom\"\nid = \"салом\"\n"
+    );
+}
+
+// --- Group 13: gettext Catalogs ---
+
+const CATALOG: &str = r#"# Uzbek translation.
+msgid ""
+msgstr ""
+"Project-Id-Version: shahar 1.0\n"
+"Language: uz\n"
+"Plural-Forms: nplurals=INTEGER; plural=EXPRESSION;\n"
+
+#. Шаҳар номи
+#: src/main.c:10
+#, c-format
+msgctxt "меню"
+msgid "City %s"
+msgstr "Шаҳар %s\n"
+
+#, fuzzy, python-format
+msgid "%(count)d files"
+msgstr "%(count)d та файл"
+
+msgid "One item"
+msgid_plural "%d items"
+msgstr[0] "<b>Бир</b> "
+"нарса"
+msgstr[1] "%d та нарса"
+"#;
+
+#[test]
+fn test_po_converts_translations_and_relabels_headers() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Po);
+
+    assert_eq!(
+        tr.oegir(CATALOG),
+        r#"# Uzbek translation.
+msgid ""
+msgstr ""
+"Project-Id-Version: shahar 1.0\n"
+"Language: uz@latin\n"
+"Plural-Forms: nplurals=2; plural=(n != 1);\n"
+
+#. Шаҳар номи
+#: src/main.c:10
+#, c-format
+msgctxt "меню"
+msgid "City %s"
+msgstr "Shahar %s\n"
+
+#, fuzzy, python-format
+msgid "%(count)d files"
+msgstr "%(count)d ta fayl"
+
+msgid "One item"
+msgid_plural "%d items"
+msgstr[0] "<b>Bir</b> "
+"narsa"
+msgstr[1] "%d ta narsa"
+"#
+    );
+}
+
+#[test]
+fn test_po_fuzzy_entries_and_source_strings_are_configurable() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Po);
+    tr.sozlama.noaniqlarni_oegirishni_belgila(false);
+    let fuzzy = tr.oegir(CATALOG);
+    assert!(fuzzy.contains("msgstr \"%(count)d та файл\""));
+    assert!(fuzzy.contains("msgstr \"Shahar %s\\n\""));
+
+    tr.sozlama.asl_matnni_oegirishni_belgila(true);
+    let source = "msgid \"Салом, %s!\"\nmsgstr \"\"\n";
+    assert_eq!(tr.oegir(source), "msgid \"Salom, %s!\"\nmsgstr \"\"\n");
+}
+
+// --- Group 14: Localization Resources ---
+
+fn resource_translator(format: TarjimaFormati) -> latinga::Oegirgich {
+    document_translator(Tartib::Joriy, HujjatTuri::Tarjima(format))
+}
+
+#[test]
+fn test_android_strings_convert_translatable_values() {
+    let tr = resource_translator(TarjimaFormati::Android);
+
+    let input = r#"<resources xmlns:xliff="urn:oasis:names:tc:xliff:document:1.2">
+    <!-- <string name="old">Эски</string> -->
+    <string name="app_name" translatable="false">Шаҳар</string>
+    <string name="welcome">Салом, <xliff:g id="name">%1$s</xliff:g>!\nХуш келибсиз</string>
+    <string name="html"><![CDATA[<b>Янги</b> хабар]]></string>
+    <string name="unicode">\u0428\u0430\u04b3\u0430\u0440 ёз</string>
+    <plurals name="files">
+        <item quantity="one">%d та файл</item>
+        <item quantity="other">%d та файл</item>
+    </plurals>
+    <string-array name="days">
+        <item>Душанба</item>
+    </string-array>
+</resources>"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"<resources xmlns:xliff="urn:oasis:names:tc:xliff:document:1.2">
+    <!-- <string name="old">Эски</string> -->
+    <string name="app_name" translatable="false">Шаҳар</string>
+    <string name="welcome">Salom, <xliff:g id="name">%1$s</xliff:g>!\nXush kelibsiz</string>
+    <string name="html"><![CDATA[<b>Yangi</b> xabar]]></string>
+    <string name="unicode">Shahar yoz</string>
+    <plurals name="files">
+        <item quantity="one">%d ta fayl</item>
+        <item quantity="other">%d ta fayl</item>
+    </plurals>
+    <string-array name="days">
+        <item>Dushanba</item>
+    </string-array>
+</resources>"#
+    );
+}
+
+#[test]
+fn test_apple_strings_and_stringsdict() {
+    let tr = resource_translator(TarjimaFormati::Strings);
+    let input = "/* Сарлавҳа */\n\"title\" = \"Шаҳар %@\";\n\"шаҳар\" = \"Ҳа\\nЙўқ\";\n";
+    assert_eq!(
+        tr.oegir(input),
+        "/* Сарлавҳа */\n\"title\" = \"Shahar %@\";\n\"шаҳар\" = \"Ha\\nYoʻq\";\n"
+    );
+
+    let tr = resource_translator(TarjimaFormati::Stringsdict);
+    let input = "<dict>\n<key>NSStringLocalizedFormatKey</key>\n<string>%#@files@ топилди</string>\n<key>NSStringFormatValueTypeKey</key>\n<string>d</string>\n<key>other</key>\n<string>%d та файл</string>\n</dict>";
+    assert_eq!(
+        tr.oegir(input),
+        "<dict>\n<key>NSStringLocalizedFormatKey</key>\n<string>%#@files@ topildi</string>\n<key>NSStringFormatValueTypeKey</key>\n<string>d</string>\n<key>other</key>\n<string>%d ta fayl</string>\n</dict>"
+    );
+}
+
+#[test]
+fn test_arb_keeps_icu_syntax_and_metadata() {
+    let tr = resource_translator(TarjimaFormati::Arb);
+    let input = r#"{
+  "@@locale": "uz",
+  "greeting": "Салом, {name}!",
+  "@greeting": {"description": "Саломлашув"},
+  "files": "{count, plural, =0{Файл йўқ} one{{count} та файл} other{# та файл}}"
+}"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"{
+  "@@locale": "uz",
+  "greeting": "Salom, {name}!",
+  "@greeting": {"description": "Саломлашув"},
+  "files": "{count, plural, =0{Fayl yoʻq} one{{count} ta fayl} other{# ta fayl}}"
+}"#
+    );
+}
+
+#[test]
+fn test_properties_decode_unicode_escapes() {
+    let tr = resource_translator(TarjimaFormati::Properties);
+    let input = "# Изоҳ\nshahar.title=\\u0428\\u0430\\u04b3\\u0430\\u0440 {0}\nmessage : Салом \\\n    дунё\nшаҳар Ҳа\n";
+    assert_eq!(
+        tr.oegir(input),
+        "# Изоҳ\nshahar.title=Shahar {0}\nmessage : Salom \\\n    dunyo\nшаҳар Ha\n"
+    );
+}
+
+#[test]
+fn test_xliff_converts_targets_and_relabels_language() {
+    let tr = resource_translator(TarjimaFormati::Xliff);
+    let input = r#"<xliff version="1.2"><file source-language="en" target-language="uz-Cyrl">
+<trans-unit id="1"><source>City</source><target>Шаҳар <ph id="1">%s</ph> <g id="2">ҳақида</g></target></trans-unit>
+<trans-unit id="2" translate="no"><source>Brand</source><target>Шаҳар</target></trans-unit>
+</file></xliff>"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"<xliff version="1.2"><file source-language="en" target-language="uz-Latn">
+<trans-unit id="1"><source>City</source><target>Shahar <ph id="1">%s</ph> <g id="2">haqida</g></target></trans-unit>
+<trans-unit id="2" translate="no"><source>Brand</source><target>Шаҳар</target></trans-unit>
+</file></xliff>"#
+    );
+
+    let v2 = r#"<xliff version="2.0" srcLang="en" trgLang="uz"><file id="f"><unit id="u"><segment><source>Yes</source><target>Ҳа</target></segment></unit></file></xliff>"#;
+    assert_eq!(
+        tr.oegir(v2),
+        r#"<xliff version="2.0" srcLang="en" trgLang="uz-Latn"><file id="f"><unit id="u"><segment><source>Yes</source><target>Ha</target></segment></unit></file></xliff>"#
+    );
+}
+
+// --- Group 15: Subtitles ---
+
+#[test]
+fn test_srt_converts_only_cue_text() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Subtitr);
+    let input = "1\n00:00:01,000 --> 00:00:03,500\n{\\an8}<i>Салом, дунё!</i>\n- Ҳа.\n\n2\n00:00:04,000 --> 00:00:06,000\n<font color=\"#ffff00\">Шаҳар</font>\n";
+    assert_eq!(
+        tr.oegir(input),
+        "1\n00:00:01,000 --> 00:00:03,500\n{\\an8}<i>Salom, dunyo!</i>\n- Ha.\n\n2\n00:00:04,000 --> 00:00:06,000\n<font color=\"#ffff00\">Shahar</font>\n"
+    );
+}
+
+#[test]
+fn test_webvtt_keeps_headers_settings_and_voice_tags() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Subtitr);
+    let input = "WEBVTT - Шаҳар\n\nNOTE Таржимон изоҳи\n\nSTYLE\n::cue { color: white }\n\nсалом-1\n00:01.000 --> 00:04.000 align:start line:0%\n<v Алишер>Салом, <c.yellow>дўстим</c>!\n<00:02.500>Ҳа\n";
+    assert_eq!(
+        tr.oegir(input),
+        "WEBVTT - Шаҳар\n\nNOTE Таржимон изоҳи\n\nSTYLE\n::cue { color: white }\n\nсалом-1\n00:01.000 --> 00:04.000 align:start line:0%\n<v Алишер>Salom, <c.yellow>doʻstim</c>!\n<00:02.500>Ha\n"
+    );
+    assert_eq!(HujjatTuri::matndan_aniqla(input), Some(HujjatTuri::Subtitr));
+}
+
+// --- Group 16: Tables ---
+
+#[test]
+fn test_csv_converts_selected_columns_by_name_and_index() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Csv);
+    tr.sozlama.ustunlarni_belgila("Nomi\n3");
+    let input = "id,nomi,izoh,kod\n1,Шаҳар,\"Катта, эски шаҳар\",ШҲР\n2,\"Тошкент \"\"пойтахт\"\"\",\"Икки\nқатор\",ТШК\n";
+    assert_eq!(
+        tr.oegir(input),
+        "id,nomi,izoh,kod\n1,Shahar,\"Katta, eski shahar\",ШҲР\n2,\"Toshkent \"\"poytaxt\"\"\",\"Ikki\nqator\",ТШК\n"
+    );
+}
+
+#[test]
+fn test_tsv_and_semicolon_csv_keep_their_delimiters() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Tsv);
+    assert_eq!(
+        tr.oegir("ном\tизоҳ\r\nЎзбек\tа;б, в\r\n"),
+        "ном\tизоҳ\r\nOʻzbek\ta;b, v\r\n"
+    );
+
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Csv);
+    assert_eq!(tr.oegir("ном;нарх\nОлма;1,5\n"), "ном;нарх\nOlma;1,5\n");
+}
+
+#[test]
+fn test_csv_adds_converted_copy_columns() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Csv);
+    tr.sozlama.ustunlarni_belgila("nomi");
+    tr.sozlama.nusxa_qoeshimchasini_belgila("_lotin");
+    let input = "id,\"nomi\",kod\n1,Шаҳар,ШҲР\n2,\"Боғ, кўча\",БК";
+    assert_eq!(
+        tr.oegir(input),
+        "id,\"nomi\",\"nomi_lotin\",kod\n1,Шаҳар,Shahar,ШҲР\n2,\"Боғ, кўча\",\"Bogʻ, koʻcha\",БК"
+    );
+
+    // Copy names leave out the byte order mark and keep quote escapes single
+    tr.sozlama.ustunlarni_belgila("1\n2");
+    assert_eq!(
+        tr.oegir("\u{FEFF}nomi,\"a\"\"b\"\nШаҳар,Боғ\n"),
+        "\u{FEFF}nomi,nomi_lotin,\"a\"\"b\",\"a\"\"b_lotin\"\nШаҳар,Shahar,Боғ,Bogʻ\n"
+    );
+}
+
+#[test]
+fn test_csv_keeps_malformed_quoted_fields_intact() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Csv);
+    assert_eq!(
+        tr.oegir("nomi,izoh\n\"салом\"ҳа,x\n\"Тошкент"),
+        "nomi,izoh\n\"salom\"ҳа,x\n\"Toshkent"
+    );
+    assert_eq!(tr.oegir("\"ном"), "\"ном");
+}
+
+// --- Group 17: Office documents ---
+
+#[test]
+fn test_docx_words_split_across_runs_convert_as_one_word() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Ofis);
+    let input = concat!(
+        r#"<w:document><w:body><w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr>"#,
+        r#"<w:r><w:rPr><w:b/><w:lang w:val="uz-Cyrl-UZ"/></w:rPr><w:t>Ш</w:t></w:r>"#,
+        r#"<w:r><w:t xml:space="preserve">аҳар ва </w:t></w:r>"#,
+        r#"<w:r><w:rPr><w:i/></w:rPr><w:t>ТОШ</w:t></w:r><w:r><w:t>КЕНТ</w:t></w:r></w:p>"#,
+        r#"<w:p><w:ins w:id="1" w:author="Алишер"><w:r><w:t>Янги</w:t></w:r></w:ins>"#,
+        r#"<w:del w:id="2" w:author="Алишер"><w:r><w:delText>Эски</w:delText></w:r></w:del>"#,
+        r#"<w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText> HYPERLINK "https://шаҳар.уз" </w:instrText></w:r>"#,
+        r#"<w:r><w:t>&lt;мактаб&gt; &amp; боғ</w:t></w:r></w:p></w:body></w:document>"#,
+    );
+    let expected = concat!(
+        r#"<w:document><w:body><w:p><w:pPr><w:pStyle w:val="Title"/></w:pPr>"#,
+        r#"<w:r><w:rPr><w:b/><w:lang w:val="uz-Latn-UZ"/></w:rPr><w:t>Sh</w:t></w:r>"#,
+        r#"<w:r><w:t xml:space="preserve">ahar va </w:t></w:r>"#,
+        r#"<w:r><w:rPr><w:i/></w:rPr><w:t>TOSH</w:t></w:r><w:r><w:t>KENT</w:t></w:r></w:p>"#,
+        r#"<w:p><w:ins w:id="1" w:author="Алишер"><w:r><w:t>Yangi</w:t></w:r></w:ins>"#,
+        r#"<w:del w:id="2" w:author="Алишер"><w:r><w:delText>Эски</w:delText></w:r></w:del>"#,
+        r#"<w:r><w:fldChar w:fldCharType="begin"/></w:r><w:r><w:instrText> HYPERLINK "https://шаҳар.уз" </w:instrText></w:r>"#,
+        r#"<w:r><w:t>&lt;maktab&gt; &amp; bogʻ</w:t></w:r></w:p></w:body></w:document>"#,
+    );
+    assert_eq!(tr.oegir(input), expected);
+    assert_eq!(
+        HujjatTuri::matndan_aniqla(&format!("<?xml version=\"1.0\"?>\n{input}")),
+        Some(HujjatTuri::Ofis)
+    );
+}
+
+#[test]
+fn test_xlsx_shared_strings_and_pptx_slides_convert_run_text() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Ofis);
+    let input = "<sst><si><t>Ҳисобот</t></si><si><r><rPr><b/></rPr><t>Ўз</t></r><r><t>бекистон</t></r><rPh sb=\"0\" eb=\"1\"><t>ўз</t></rPh></si></sst>";
+    assert_eq!(
+        tr.oegir(input),
+        "<sst><si><t>Hisobot</t></si><si><r><rPr><b/></rPr><t>Oʻz</t></r><r><t>bekiston</t></r><rPh sb=\"0\" eb=\"1\"><t>ўз</t></rPh></si></sst>"
+    );
+
+    let input = r#"<p:sld><a:p><a:r><a:rPr lang="uz-Cyrl-UZ" b="1"/><a:t>Салом</a:t></a:r><a:endParaRPr lang="ru-RU"/></a:p></p:sld>"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"<p:sld><a:p><a:r><a:rPr lang="uz-Latn-UZ" b="1"/><a:t>Salom</a:t></a:r><a:endParaRPr lang="ru-RU"/></a:p></p:sld>"#
+    );
+}
+
+#[test]
+fn test_docx_runs_of_single_letters_keep_their_converted_letters() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Ofis);
+    let runs = |letters: &[&str]| {
+        let runs: String = letters
+            .iter()
+            .map(|letter| format!("<w:r><w:t xml:space=\"preserve\">{letter}</w:t></w:r>"))
+            .collect();
+        format!("<w:document><w:body><w:p>{runs}</w:p></w:body></w:document>")
+    };
+    let input = runs(&[
+        "Ш", "а", "ҳ", "а", "р", " ", "ё", "з", "и", " ", "Ч", "Ў", "Л",
+    ]);
+    let expected = runs(&[
+        "Sh", "a", "h", "a", "r", " ", "yo", "z", "i", " ", "CH", "Oʻ", "L",
+    ]);
+    assert_eq!(tr.oegir(&input), expected);
+}
+
+// --- Group 18: OpenDocument ---
+
+#[test]
+fn test_odt_paragraphs_convert_across_spans() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Odf);
+    let input = concat!(
+        r#"<office:document-content><office:automatic-styles>"#,
+        r#"<style:style style:name="T1"><style:text-properties fo:font-weight="bold" fo:language="uz" fo:script="Cyrl" fo:country="UZ" style:script-type="complex"/></style:style>"#,
+        r#"<style:style style:name="T2"><style:text-properties fo:language="ru" fo:country="RU"/></style:style>"#,
+        r#"</office:automatic-styles><office:body><office:text>"#,
+        r#"<text:tracked-changes><text:changed-region><text:deletion><text:p>Эски</text:p></text:deletion></text:changed-region></text:tracked-changes>"#,
+        r#"<text:h text:outline-level="1"><text:span text:style-name="T1">Ш</text:span>аҳар<text:s/>ва<text:tab/><text:a xlink:href="https://шаҳар.уз">боғ</text:a></text:h>"#,
+        r#"<text:p>Изоҳ<text:note><text:note-citation>1</text:note-citation><text:note-body><text:p>Янги</text:p></text:note-body></text:note></text:p>"#,
+        r#"</office:text></office:body></office:document-content>"#,
+    );
+    let expected = concat!(
+        r#"<office:document-content><office:automatic-styles>"#,
+        r#"<style:style style:name="T1"><style:text-properties fo:font-weight="bold" fo:language="uz" fo:script="Latn" fo:country="UZ" style:script-type="latin"/></style:style>"#,
+        r#"<style:style style:name="T2"><style:text-properties fo:language="ru" fo:country="RU"/></style:style>"#,
+        r#"</office:automatic-styles><office:body><office:text>"#,
+        r#"<text:tracked-changes><text:changed-region><text:deletion><text:p>Эски</text:p></text:deletion></text:changed-region></text:tracked-changes>"#,
+        r#"<text:h text:outline-level="1"><text:span text:style-name="T1">Sh</text:span>ahar<text:s/>va<text:tab/><text:a xlink:href="https://шаҳар.уз">bogʻ</text:a></text:h>"#,
+        r#"<text:p>Izoh<text:note><text:note-citation>1</text:note-citation><text:note-body><text:p>Yangi</text:p></text:note-body></text:note></text:p>"#,
+        r#"</office:text></office:body></office:document-content>"#,
+    );
+    assert_eq!(tr.oegir(input), expected);
+    assert_eq!(
+        HujjatTuri::matndan_aniqla(&format!("<?xml version=\"1.0\"?>\n{input}")),
+        Some(HujjatTuri::Odf)
+    );
+}
+
+#[test]
+fn test_odf_uzbek_styles_without_a_script_are_marked_latin() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Odf);
+    let input = r#"<style:default-style><style:text-properties fo:language="uz" fo:country="UZ"/></style:default-style>"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"<style:default-style><style:text-properties fo:language="uz" fo:script="Latn" fo:country="UZ"/></style:default-style>"#
+    );
+}
+
+#[test]
+fn test_odf_meta_converts_title_and_relabels_language() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Odf);
+    let input = "<office:document-meta><office:meta><dc:title>Ҳисобот</dc:title><dc:creator>Алишер</dc:creator><meta:keyword>шаҳар</meta:keyword><dc:language>uz-Cyrl-UZ</dc:language></office:meta></office:document-meta>";
+    assert_eq!(
+        tr.oegir(input),
+        "<office:document-meta><office:meta><dc:title>Hisobot</dc:title><dc:creator>Алишер</dc:creator><meta:keyword>shahar</meta:keyword><dc:language>uz-Latn-UZ</dc:language></office:meta></office:document-meta>"
+    );
+}
+
+// --- Group 19: EPUB ---
+
+#[test]
+fn test_epub_package_document_converts_metadata() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Epub);
+    let input = concat!(
+        r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0" xml:lang="uz-Cyrl">"#,
+        r#"<metadata><dc:identifier id="id">urn:uuid:1</dc:identifier><dc:title>Ўткан кунлар</dc:title>"#,
+        r#"<dc:creator id="muallif">Абдулла Қодирий</dc:creator><dc:language>uz</dc:language>"#,
+        r#"<dc:description>Тарихий роман</dc:description><meta property="dcterms:modified">2024-01-01T00:00:00Z</meta></metadata>"#,
+        r#"<manifest><item id="bob1" href="bob1.xhtml" media-type="application/xhtml+xml"/></manifest></package>"#,
+    );
+    let expected = concat!(
+        r#"<package xmlns="http://www.idpf.org/2007/opf" version="3.0" xml:lang="uz-Latn">"#,
+        r#"<metadata><dc:identifier id="id">urn:uuid:1</dc:identifier><dc:title>Oʻtkan kunlar</dc:title>"#,
+        r#"<dc:creator id="muallif">Abdulla Qodiriy</dc:creator><dc:language>uz-Latn</dc:language>"#,
+        r#"<dc:description>Tarixiy roman</dc:description><meta property="dcterms:modified">2024-01-01T00:00:00Z</meta></metadata>"#,
+        r#"<manifest><item id="bob1" href="bob1.xhtml" media-type="application/xhtml+xml"/></manifest></package>"#,
+    );
+    assert_eq!(tr.oegir(input), expected);
+}
+
+#[test]
+fn test_epub_ncx_converts_labels() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Epub);
+    let input = r#"<ncx xml:lang="uz-Cyrl-UZ"><docTitle><text>Ўткан кунлар</text></docTitle><navMap><navPoint id="p1" playOrder="1"><navLabel><text>Биринчи боб</text></navLabel><content src="bob1.xhtml"/></navPoint></navMap></ncx>"#;
+    assert_eq!(
+        tr.oegir(input),
+        r#"<ncx xml:lang="uz-Latn-UZ"><docTitle><text>Oʻtkan kunlar</text></docTitle><navMap><navPoint id="p1" playOrder="1"><navLabel><text>Birinchi bob</text></navLabel><content src="bob1.xhtml"/></navPoint></navMap></ncx>"#
+    );
+    assert_eq!(
+        HujjatTuri::matndan_aniqla(&format!("<?xml version=\"1.0\"?>\n{input}")),
+        Some(HujjatTuri::Epub)
+    );
+}
+
+// --- Group 20: FictionBook ---
+
+#[test]
+fn test_fb2_converts_bodies_and_title_info() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Fb2);
+    let input = concat!(
+        r#"<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">"#,
+        r#"<description><title-info><genre>prose_classic</genre><author><first-name>Абдулла</first-name><last-name>Қодирий</last-name></author>"#,
+        r#"<book-title>Ўткан кунлар</book-title><annotation><p>Тарихий роман.</p></annotation><lang>uz</lang></title-info>"#,
+        r#"<src-title-info><book-title>Ўткан кунлар</book-title><lang>uz</lang></src-title-info>"#,
+        r#"<document-info><author><nickname>Тузувчи</nickname></author><program-used>FB Editor</program-used></document-info></description>"#,
+        r##"<body><title><p>Биринчи боб</p></title><section><p><strong>Ш</strong>аҳар <a l:href="#n1" type="note">1</a> <code>Шаҳар</code></p>"##,
+        r#"<poem><stanza><v>Ёр</v></stanza></poem></section></body>"#,
+        r#"<binary id="muqova.jpg" content-type="image/jpeg">/9j/4AAQ</binary></FictionBook>"#,
+    );
+    let expected = concat!(
+        r#"<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">"#,
+        r#"<description><title-info><genre>prose_classic</genre><author><first-name>Abdulla</first-name><last-name>Qodiriy</last-name></author>"#,
+        r#"<book-title>Oʻtkan kunlar</book-title><annotation><p>Tarixiy roman.</p></annotation><lang>uz-Latn</lang></title-info>"#,
+        r#"<src-title-info><book-title>Ўткан кунлар</book-title><lang>uz</lang></src-title-info>"#,
+        r#"<document-info><author><nickname>Тузувчи</nickname></author><program-used>FB Editor</program-used></document-info></description>"#,
+        r##"<body><title><p>Birinchi bob</p></title><section><p><strong>Sh</strong>ahar <a l:href="#n1" type="note">1</a> <code>Шаҳар</code></p>"##,
+        r#"<poem><stanza><v>Yor</v></stanza></poem></section></body>"#,
+        r#"<binary id="muqova.jpg" content-type="image/jpeg">/9j/4AAQ</binary></FictionBook>"#,
+    );
+    assert_eq!(tr.oegir(input), expected);
+    assert_eq!(
+        HujjatTuri::matndan_aniqla(&format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{input}"
+        )),
+        Some(HujjatTuri::Fb2)
+    );
+}
+
+// --- Group 21: RTF ---
+
+#[test]
+fn test_rtf_decodes_converts_and_reencodes_text() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Rtf);
+    let input = concat!(
+        r"{\rtf1\ansi\ansicpg1251\deff0{\fonttbl{\f0\fcharset204 Times New Roman;}{\f1\fcharset0 Arial;}}",
+        "\r\n",
+        r"{\info{\title \'d8\'e0\'f5\'e0\'f0}}",
+        "\r\n",
+        r"\pard\plain\f0\fs24 {\b \'d8}\'e0\u1203?\'e0\'f0 \u1179?\'e8\'f8\'eb\'ee\u1179?\par",
+        "\r\n",
+        r#"{\field{\*\fldinst HYPERLINK "http://toshkent.uz"}{\fldrslt \'d2\'ee\'f8\'ea\'e5\'ed\'f2}}\par"#,
+        "\r\n",
+        r"\'a1\'e7\'e1\'e5\'ea {\f1 \'d8}\tab\uc0\u1202\'e0\par",
+        "\r\n}",
+    );
+    let expected = concat!(
+        r"{\rtf1\ansi\ansicpg1251\deff0{\fonttbl{\f0\fcharset204 Times New Roman;}{\f1\fcharset0 Arial;}}",
+        "\r\n",
+        r"{\info{\title \'d8\'e0\'f5\'e0\'f0}}",
+        "\r\n",
+        r"\pard\plain\f0\fs24 {\b Sh}ahar qishloq\par",
+        "\r\n",
+        r#"{\field{\*\fldinst HYPERLINK "http://toshkent.uz"}{\fldrslt Toshkent}}\par"#,
+        "\r\n",
+        r"O\u699?zbek {\f1 \'d8}\tab\uc0 Ha\par",
+        "\r\n}",
+    );
+    assert_eq!(tr.oegir(input), expected);
+    assert_eq!(HujjatTuri::matndan_aniqla(input), Some(HujjatTuri::Rtf));
+}
+
+#[test]
+fn test_rtf_skips_binary_data_and_fallbacks_ending_inside_a_character() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Rtf);
+    // `\bin1` skips one byte of the two-byte Ж
+    assert_eq!(
+        tr.oegir("{\\rtf1\\ansi{\\bin1 Жаа} салом}"),
+        "{\\rtf1\\ansi{\\bin1 Жaa} salom}"
+    );
+    // A `\'` fallback without hex digits skips just the escape
+    assert_eq!(
+        tr.oegir("{\\rtf1\\ansi\\u1046\\'aа салом}"),
+        "{\\rtf1\\ansi Jaa salom}"
+    );
+}
+
+#[test]
+fn test_rtf_keeps_lone_low_surrogates_as_written() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Rtf);
+    assert_eq!(
+        tr.oegir("{\\rtf1 Шаҳар\\u56320?}"),
+        "{\\rtf1 Shahar\\u56320?}"
+    );
+    assert_eq!(
+        tr.oegir("{\\rtf1 a\\u56320?b салом}"),
+        "{\\rtf1 a\\u56320?b salom}"
+    );
+    // Text that merely lies in the placeholder plane is not a high half
+    assert_eq!(
+        tr.oegir("{\\rtf1 \u{F0041}\\u56320?}"),
+        "{\\rtf1 \u{F0041}\\u56320?}"
+    );
+}
+
+// --- Group 22: SQL dumps ---
+
+#[test]
+fn test_mysql_dump_converts_string_literals_only() {
+    let tr = document_translator(Tartib::Joriy, HujjatTuri::Sql);
+    let input = concat!(
+        "-- MySQL dump 10.13  Distrib 8.0.36\n",
+        "/*!40101 SET NAMES utf8mb4 */;\n",
+        "CREATE TABLE `шаҳарлар` (\n",
+        "  `id` int NOT NULL,\n",
+        "  `ном` varchar(64) DEFAULT 'Номсиз' COMMENT 'Шаҳар номи',\n",
+        "  PRIMARY KEY (`id`)\n",
+        ");\n",
+        "# Маълумотлар\n",
+        "INSERT INTO `шаҳарлар` VALUES (1,'Тошкент\\nпойтахт'),(2,'Шаҳар ''Бухоро'''),",
+        "(3,_utf8mb4'Қўқон\\'лик'),(4,X'D0A8');\n",
+    );
+    let expected = concat!(
+        "-- MySQL dump 10.13  Distrib 8.0.36\n",
+        "/*!40101 SET NAMES utf8mb4 */;\n",
+        "CREATE TABLE `шаҳарлар` (\n",
+        "  `id` int NOT NULL,\n",
+        "  `ном` varchar(64) DEFAULT 'Nomsiz' COMMENT 'Shahar nomi',\n",
+        "  PRIMARY KEY (`id`)\n",
+        ");\n",
+        "# Маълумотлар\n",
+        "INSERT INTO `шаҳарлар` VALUES (1,'Toshkent\\npoytaxt'),(2,'Shahar ''Buxoro'''),",
+        "(3,_utf8mb4'Qoʻqon\\'lik'),(4,X'D0A8');\n",
+    );
+    assert_eq!(tr.oegir(input), expected);
+    assert_eq!(HujjatTuri::matndan_aniqla(input), Some(HujjatTuri::Sql));
+}
+
+#[test]
+fn test_postgresql_dump_converts_selected_tables_and_columns() {
+    let mut tr = document_translator(Tartib::Joriy, HujjatTuri::Sql);
+    tr.sozlama.jadvallarni_belgila("public.kitoblar");
+    tr.sozlama.ustunlarni_belgila("nomi");
+    let input = concat!(
+        "--\n-- PostgreSQL database dump\n--\n\n",
+        "CREATE TABLE public.kitoblar (\n",
+        "    id integer NOT NULL,\n",
+        "    nomi text DEFAULT 'Номсиз'::text,\n",
+        "    muallif text\n",
+        ");\n\n",
+        "CREATE FUNCTION public.salom() RETURNS text\n",
+        "    LANGUAGE sql\n",
+        "    AS $_$SELECT 'Салом; дунё'$_$;\n\n",
+        "INSERT INTO public.kitoblar VALUES (1, 'Ўтган кунлар', 'Абдулла Қодирий');\n",
+        "INSERT INTO public.kitoblar (muallif, \"nomi\") VALUES ('Чўлпон', E'Кеча\\tва кундуз');\n",
+        "INSERT INTO public.mualliflar VALUES (1, 'Ойбек');\n\n",
+        "COPY public.kitoblar (id, nomi, muallif) FROM stdin;\n",
+        "3\tМеҳробдан чаён\tАбдулла Қодирий\n",
+        "4\t\\N\tҒафур Ғулом\n",
+        "5\tСариқ\\tдевнинг ўлими\tХудойберди Тўхтабоев\n",
+        "\\.\n\n",
+        "SELECT 'Тамом';\n",
+    );
+    let expected = concat!(
+        "--\n-- PostgreSQL database dump\n--\n\n",
+        "CREATE TABLE public.kitoblar (\n",
+        "    id integer NOT NULL,\n",
+        "    nomi text DEFAULT 'Номсиз'::text,\n",
+        "    muallif text\n",
+        ");\n\n",
+        "CREATE FUNCTION public.salom() RETURNS text\n",
+        "    LANGUAGE sql\n",
+        "    AS $_$SELECT 'Салом; дунё'$_$;\n\n",
+        "INSERT INTO public.kitoblar VALUES (1, 'Oʻtgan kunlar', 'Абдулла Қодирий');\n",
+        "INSERT INTO public.kitoblar (muallif, \"nomi\") VALUES ('Чўлпон', E'Kecha\\tva kunduz');\n",
+        "INSERT INTO public.mualliflar VALUES (1, 'Ойбек');\n\n",
+        "COPY public.kitoblar (id, nomi, muallif) FROM stdin;\n",
+        "3\tMehrobdan chayon\tАбдулла Қодирий\n",
+        "4\t\\N\tҒафур Ғулом\n",
+        "5\tSariq\\tdevning oʻlimi\tХудойберди Тўхтабоев\n",
+        "\\.\n\n",
+        "SELECT 'Тамом';\n",
+    );
+    assert_eq!(tr.oegir(input), expected);
+    assert_eq!(HujjatTuri::matndan_aniqla(input), Some(HujjatTuri::Sql));
+}